@@ -0,0 +1,66 @@
+// runs the wgsl shaders, so like the cli this needs dawn.node and a gpu. the
+// vectors are shared with the earthbucks_algo1627 crate.
+import fs from "node:fs";
+import { Buffer } from "buffer";
+import test from "ava";
+import { FixedBuf, Header } from "@earthbucks/lib";
+import { blake3 } from "@noble/hashes/blake3";
+import { WebBuf } from "webbuf";
+import { globals, create } from "../src/dawn.js";
+import { overrideNavigator } from "../src/navigator.js";
+import createAlgo1627 from "../src/algo1627.js";
+
+type Algo1627Data = {
+  get_work_par: Array<{ input: string; work_par: string }>;
+  elementary_iteration: Array<{
+    header: string;
+    lch_ids: string[];
+    id: string;
+  }>;
+};
+
+const data = JSON.parse(
+  fs.readFileSync(
+    new URL("../test-vectors/algo1627.json", import.meta.url),
+    "utf8",
+  ),
+) as Algo1627Data;
+
+Object.assign(globalThis, globals);
+overrideNavigator({ gpu: create([]) } as Navigator);
+
+test("get work par", async (t) => {
+  const algo1627 = await createAlgo1627();
+  for (const vector of data.get_work_par) {
+    const result = await algo1627(Buffer.from(vector.input, "hex"));
+    t.is(Buffer.from(result).toString("hex"), vector.work_par);
+  }
+});
+
+// the same steps as the mining loop
+test("elementary iteration", async (t) => {
+  const algo1627 = await createAlgo1627();
+  for (const vector of data.elementary_iteration) {
+    const header = Header.fromBuf(Buffer.from(vector.header, "hex"));
+    const workingHeader = header.toWorkingHeader();
+    const workingBlockId = workingHeader.id().buf;
+    const lch10Ids = vector.lch_ids
+      .map((id) => Buffer.from(id, "hex"))
+      .reverse();
+    const inputData = WebBuf.concat([
+      workingBlockId,
+      ...lch10Ids,
+      workingBlockId,
+    ]);
+    const parHash = WebBuf.from(await algo1627(inputData));
+    workingHeader.workParHash = new FixedBuf(
+      32,
+      Buffer.from(parHash.buffer, 0, 32),
+    );
+    workingHeader.workSerHash = new FixedBuf(
+      32,
+      Buffer.from(blake3(blake3(blake3(workingBlockId))).buffer, 0, 32),
+    );
+    t.is(Buffer.from(workingHeader.id().buf).toString("hex"), vector.id);
+  }
+});
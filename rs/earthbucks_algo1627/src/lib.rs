@@ -0,0 +1,274 @@
+use wasm_bindgen::prelude::*;
+
+// the header is in the layout of the typescript header, which is what the
+// miner hashes, with n_transactions between the merkle root and the timestamp.
+// this is Header::to_wire_buf in earthbucks_lib.
+const HEADER_SIZE: usize = 1 + 32 + 32 + 8 + 8 + 4 + 32 + 32 + 2 + 32 + 2 + 32; // 217
+const NONCE_START: usize = 1 + 32 + 32 + 8 + 8 + 4 + 32; // 117
+const NONCE_END: usize = NONCE_START + 32; // 149
+const WORK_SER_START: usize = NONCE_END + 2; // 151
+const WORK_SER_END: usize = WORK_SER_START + 32; // 183
+const WORK_PAR_START: usize = WORK_SER_END + 2; // 185
+const WORK_PAR_END: usize = WORK_PAR_START + 32; // 217
+const HASH_SIZE: usize = 32;
+const LCH_IDS_SIZE: usize = HASH_SIZE * 10; // 320
+const INPUT_SIZE: usize = HASH_SIZE + LCH_IDS_SIZE + HASH_SIZE; // 384
+const INPUT_WORDS: usize = INPUT_SIZE / 4; // 96
+
+const MATRIX_SIZE: usize = 1627;
+const INPUT_LENGTH: usize = 2816;
+const MAGIC_NUMBER: usize = 2771;
+const VECTOR_WORDS: usize = 51;
+const LAST_ELEMENT_MASK: u32 = 0xffffffe0;
+
+// this is a cpu port of the wgsl shaders used by the miner (swap, expand, pow
+// and reduce). the input is 384 bytes: the working header id, the ten most
+// recent longest chain header ids (newest first), and the working header id
+// again. only the first INPUT_LENGTH bits of the input are actually read.
+//
+// - the input is read as big-endian u32 words, which is the "row" input
+// - the "column" input is a permutation of the bits of the row input, where
+//   bit g of the column input is bit (1627 * g) % 2816 of the row input
+// - each of the 1627 rows and 1627 columns of the matrix is a 1627 bit vector
+//   taken from the row input or column input at a rotating bit offset
+// - each element of the matrix is the number of bits the row vector and
+//   column vector have in common, i.e. popcount(row & col)
+// - the matrix is reduced to the sum, max and min of each row, plus one
+//   "selected" row chosen by the first element of the matrix
+//
+// the gpu returns the four reduced arrays as u32s which are then converted to
+// bytes one element at a time, i.e. each value is truncated to its lowest
+// byte. each array is hashed with blake3, and the hashes are hashed together
+// to get the "parallel work" or "work_par".
+#[wasm_bindgen]
+pub fn get_work_par(input: Vec<u8>) -> Result<Vec<u8>, String> {
+    if input.len() != INPUT_SIZE {
+        return Err("input is not the correct size".to_string());
+    }
+    let [sums, maxs, mins, selected] = get_reduced(&input);
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(blake3::hash(&sums).as_bytes());
+    hasher.update(blake3::hash(&maxs).as_bytes());
+    hasher.update(blake3::hash(&mins).as_bytes());
+    hasher.update(blake3::hash(&selected).as_bytes());
+    let work_par = hasher.finalize();
+
+    Ok(work_par.as_bytes().to_vec())
+}
+
+// the lch ids are the ten most recent longest chain header ids in
+// chronological order, as they are stored in a work pack. the header is
+// converted to a working header (both work hashes zeroed), the work is
+// computed from the working header id, and the id of the resulting header is
+// returned.
+#[wasm_bindgen]
+pub fn elementary_iteration(header: Vec<u8>, lch_ids: Vec<u8>) -> Result<Vec<u8>, String> {
+    if header.len() != HEADER_SIZE {
+        return Err("header is not the correct size".to_string());
+    }
+    if lch_ids.len() != LCH_IDS_SIZE {
+        return Err("lch ids are not the correct size".to_string());
+    }
+
+    let mut working_header = header.clone();
+    working_header[WORK_SER_START..WORK_SER_END].fill(0);
+    working_header[WORK_PAR_START..WORK_PAR_END].fill(0);
+    let working_header_id = double_blake3_hash(&working_header);
+
+    let mut input = Vec::with_capacity(INPUT_SIZE);
+    input.extend_from_slice(&working_header_id);
+    for id in lch_ids.chunks(HASH_SIZE).rev() {
+        input.extend_from_slice(id);
+    }
+    input.extend_from_slice(&working_header_id);
+    let work_par = get_work_par(input)?;
+
+    // the serial work that goes with algo1627 is blake3_3, i.e. the working
+    // header id hashed three times
+    let mut work_ser = working_header_id;
+    for _ in 0..3 {
+        work_ser = *blake3::hash(&work_ser).as_bytes();
+    }
+
+    working_header[WORK_SER_START..WORK_SER_END].copy_from_slice(&work_ser);
+    working_header[WORK_PAR_START..WORK_PAR_END].copy_from_slice(&work_par);
+
+    Ok(double_blake3_hash(&working_header).to_vec())
+}
+
+#[wasm_bindgen]
+pub fn insert_nonce(header: Vec<u8>, nonce: u32) -> Result<Vec<u8>, String> {
+    if header.len() != HEADER_SIZE {
+        return Err("header is not the correct size".to_string());
+    }
+    let mut header = header.clone();
+    header[NONCE_START..NONCE_END].fill(0);
+    header[NONCE_END - 4..NONCE_END].copy_from_slice(&nonce.to_be_bytes());
+    Ok(header)
+}
+
+// returns the sums, maxs, mins and selected row of the matrix, each truncated
+// to one byte per element, in the order they are hashed
+pub fn get_reduced(input: &[u8]) -> [Vec<u8>; 4] {
+    let row_input = to_words(input);
+    let col_input = expand(&row_input);
+
+    let rows: Vec<[u32; VECTOR_WORDS]> = (0..MATRIX_SIZE)
+        .map(|y| vector(&row_input, y * MATRIX_SIZE))
+        .collect();
+    let cols: Vec<[u32; VECTOR_WORDS]> = (0..MATRIX_SIZE)
+        .map(|x| vector(&col_input, x * MAGIC_NUMBER))
+        .collect();
+
+    let selected_row = (dot(&rows[0], &cols[0]) as usize).min(MATRIX_SIZE - 1);
+
+    let mut sums = vec![0u8; MATRIX_SIZE];
+    let mut maxs = vec![0u8; MATRIX_SIZE];
+    let mut mins = vec![0u8; MATRIX_SIZE];
+    let mut selected = vec![0u8; MATRIX_SIZE];
+    for (y, row) in rows.iter().enumerate() {
+        let mut row_sum: u32 = 0;
+        let mut row_max: u32 = 0;
+        let mut row_min: u32 = u32::MAX;
+        for (x, col) in cols.iter().enumerate() {
+            let val = dot(row, col);
+            row_sum = row_sum.wrapping_add(val);
+            row_max = row_max.max(val);
+            row_min = row_min.min(val);
+            if y == selected_row {
+                selected[x] = val as u8;
+            }
+        }
+        sums[y] = row_sum as u8;
+        maxs[y] = row_max as u8;
+        mins[y] = row_min as u8;
+    }
+
+    [sums, maxs, mins, selected]
+}
+
+fn double_blake3_hash(data: &[u8]) -> [u8; 32] {
+    *blake3::hash(blake3::hash(data).as_bytes()).as_bytes()
+}
+
+fn to_words(input: &[u8]) -> [u32; INPUT_WORDS] {
+    let mut words = [0u32; INPUT_WORDS];
+    for (word, bytes) in words.iter_mut().zip(input.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    words
+}
+
+fn expand(row_input: &[u32; INPUT_WORDS]) -> [u32; INPUT_WORDS] {
+    let mut col_input = [0u32; INPUT_WORDS];
+    for g in 0..INPUT_WORDS * 32 {
+        let row_index = (MATRIX_SIZE * g) % INPUT_LENGTH;
+        let bit = (row_input[row_index / 32] >> (31 - row_index % 32)) & 1;
+        col_input[g / 32] |= bit << (31 - g % 32);
+    }
+    col_input
+}
+
+fn extract_u32(words: &[u32; INPUT_WORDS], bit_offset: usize) -> u32 {
+    let start_index = bit_offset / 32;
+    let start_bit = bit_offset % 32;
+    if start_bit == 0 {
+        return words[start_index];
+    }
+    (words[start_index] << start_bit) | (words[start_index + 1] >> (32 - start_bit))
+}
+
+fn vector(words: &[u32; INPUT_WORDS], start: usize) -> [u32; VECTOR_WORDS] {
+    let mut vector = [0u32; VECTOR_WORDS];
+    for (k, word) in vector.iter_mut().enumerate() {
+        *word = extract_u32(words, (start + k * 32) % INPUT_LENGTH);
+    }
+    vector[VECTOR_WORDS - 1] &= LAST_ELEMENT_MASK;
+    vector
+}
+
+fn dot(row: &[u32; VECTOR_WORDS], col: &[u32; VECTOR_WORDS]) -> u32 {
+    row.iter()
+        .zip(col.iter())
+        .map(|(r, c)| (r & c).count_ones())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::fs;
+
+    // the same vectors are checked against the wgsl shaders by the algo1627
+    // test of ebxminer
+    #[derive(Deserialize)]
+    struct Algo1627Data {
+        get_work_par: Vec<WorkParVector>,
+        elementary_iteration: Vec<ElementaryIterationVector>,
+    }
+
+    #[derive(Deserialize)]
+    struct WorkParVector {
+        input: String,
+        work_par: String,
+    }
+
+    #[derive(Deserialize)]
+    struct ElementaryIterationVector {
+        header: String,
+        lch_ids: Vec<String>,
+        id: String,
+    }
+
+    fn read_vectors() -> Algo1627Data {
+        let data = fs::read_to_string("./test_vectors/algo1627.json").expect("Unable to read file");
+        serde_json::from_str(&data).expect("Unable to parse JSON")
+    }
+
+    #[test]
+    fn test_get_work_par() {
+        for vector in read_vectors().get_work_par {
+            let result = get_work_par(hex::decode(vector.input).unwrap()).unwrap();
+            assert_eq!(hex::encode(result), vector.work_par);
+        }
+
+        assert!(get_work_par(vec![0; INPUT_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_get_reduced_all_zeroes() {
+        let reduced = get_reduced(&[0; INPUT_SIZE]);
+        for arr in reduced.iter() {
+            assert_eq!(arr, &vec![0; MATRIX_SIZE]);
+        }
+    }
+
+    #[test]
+    fn test_elementary_iteration() {
+        for vector in read_vectors().elementary_iteration {
+            let header = hex::decode(vector.header).unwrap();
+            let lch_ids: Vec<u8> = vector
+                .lch_ids
+                .iter()
+                .flat_map(|id| hex::decode(id).unwrap())
+                .collect();
+            let result = elementary_iteration(header, lch_ids).unwrap();
+            assert_eq!(hex::encode(result), vector.id);
+        }
+
+        // the 209 byte rust header is refused
+        assert!(elementary_iteration(vec![0; 209], vec![0; LCH_IDS_SIZE]).is_err());
+    }
+
+    #[test]
+    fn test_insert_nonce() {
+        let header = insert_nonce(vec![0x11; HEADER_SIZE], 0x01020304).unwrap();
+        assert_eq!(header[NONCE_START..NONCE_END - 4], [0; 28]);
+        assert_eq!(header[NONCE_END - 4..NONCE_END], [1, 2, 3, 4]);
+        assert_eq!(header[..NONCE_START], [0x11; NONCE_START]);
+        assert_eq!(header[NONCE_END..], [0x11; HEADER_SIZE - NONCE_END]);
+        assert!(insert_nonce(vec![0; HEADER_SIZE + 1], 0).is_err());
+    }
+}
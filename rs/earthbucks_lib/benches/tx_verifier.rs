@@ -0,0 +1,52 @@
+// measures verification time of a tx with many pkh inputs. with the tx borrowed
+// by the interpreter and the hash cache computed once per tx, the time per
+// input should stay roughly constant as the input count grows.
+//
+// run with: cargo bench -p earthbucks_lib --bench tx_verifier
+use earthbucks_lib::chain_params::ChainParams;
+use earthbucks_lib::key_pair::KeyPair;
+use earthbucks_lib::pkh::Pkh;
+use earthbucks_lib::pkh_key_map::PkhKeyMap;
+use earthbucks_lib::script::Script;
+use earthbucks_lib::tx::Tx;
+use earthbucks_lib::tx_builder::TxBuilder;
+use earthbucks_lib::tx_out::TxOut;
+use earthbucks_lib::tx_out_bn_map::TxOutBnMap;
+use earthbucks_lib::tx_signer::TxSigner;
+use earthbucks_lib::tx_verifier::TxVerifier;
+use std::time::Instant;
+
+fn signed_tx(n_inputs: u32) -> (Tx, TxOutBnMap) {
+    let mut tx_out_bn_map = TxOutBnMap::new();
+    let mut pkh_key_map = PkhKeyMap::new();
+    for i in 0..n_inputs {
+        let key = KeyPair::from_random();
+        let pkh = Pkh::from_pub_key_buffer(key.pub_key.buf.to_vec());
+        pkh_key_map.add(key, &pkh.buf);
+        let script = Script::from_pkh_output(&pkh.buf);
+        tx_out_bn_map.add(&[0; 32], i, TxOut::new(100, script), 0);
+    }
+    let params = ChainParams::mainnet();
+    let mut tx_builder = TxBuilder::new(&tx_out_bn_map, Script::from_empty(), 0, 0, &params);
+    tx_builder.add_output(TxOut::new(100 * n_inputs as u64, Script::from_empty()));
+    let tx = tx_builder.build().unwrap();
+    let mut tx_signer = TxSigner::new(tx, &tx_out_bn_map, &pkh_key_map, 0);
+    let tx = tx_signer.sign().unwrap();
+    (tx, tx_out_bn_map)
+}
+
+fn main() {
+    for n_inputs in [100, 200, 400, 800] {
+        let (tx, tx_out_bn_map) = signed_tx(n_inputs);
+        let start = Instant::now();
+        let mut tx_verifier = TxVerifier::new(&tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet());
+        assert!(tx_verifier.verify());
+        let elapsed = start.elapsed();
+        println!(
+            "{:>4} inputs: {:>10.3?} total, {:>8.3?} per input",
+            n_inputs,
+            elapsed,
+            elapsed / n_inputs
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::buf::EbxBuf;
 use crate::buf_reader::BufReader;
 use crate::buf_writer::BufWriter;
 use crate::error::EbxError;
@@ -5,27 +6,43 @@ use crate::header::Header;
 use crate::tx::Tx;
 use crate::var_int::VarInt;
 
+#[derive(Debug, Clone)]
 pub struct Block {
     pub header: Header,
     pub txs: Vec<Tx>,
 }
 
 impl Block {
+    // bytes
+    pub const MAX_SIZE: usize = 8_000_000;
+    pub const MAX_TX_COUNT: usize = 100_000;
+    // total signature operations in the scripts spent by the block
+    pub const MAX_SIG_OPS: usize = 160_000;
+
     pub fn new(header: Header, txs: Vec<Tx>) -> Self {
         Self { header, txs }
     }
 
     pub fn from_buf_reader(br: &mut BufReader) -> Result<Self, EbxError> {
+        let start_len = br.remainder_len();
         let header = Header::from_buf_reader(br)?;
         let tx_count_varint = VarInt::from_buf_reader(br)?;
         if !tx_count_varint.is_minimal() {
             return Err(EbxError::NonMinimalEncodingError { source: None });
         }
-        let tx_count = tx_count_varint.to_u64()? as usize;
+        let tx_count = tx_count_varint.to_u64()?;
+        if tx_count > Block::MAX_TX_COUNT as u64 {
+            return Err(Block::tx_count_limit_error(tx_count as usize));
+        }
         let mut txs = vec![];
         for _ in 0..tx_count {
             let tx = Tx::from_buf_reader(br)?;
             txs.push(tx);
+            // stop as soon as the block is too big rather than reading it all
+            let size = start_len - br.remainder_len();
+            if size > Block::MAX_SIZE {
+                return Err(Block::size_limit_error(size));
+            }
         }
         Ok(Self { header, txs })
     }
@@ -48,12 +65,52 @@ impl Block {
         let mut br = BufReader::new(buf);
         Self::from_buf_reader(&mut br)
     }
+
+    pub fn size_limit_error(size: usize) -> EbxError {
+        EbxError::GenericError {
+            source: None,
+            message: format!("block size {} exceeds limit of {}", size, Block::MAX_SIZE),
+        }
+    }
+
+    pub fn tx_count_limit_error(tx_count: usize) -> EbxError {
+        EbxError::GenericError {
+            source: None,
+            message: format!(
+                "block tx count {} exceeds limit of {}",
+                tx_count,
+                Block::MAX_TX_COUNT
+            ),
+        }
+    }
+
+    pub fn sig_ops_limit_error(sig_ops: usize) -> EbxError {
+        EbxError::GenericError {
+            source: None,
+            message: format!(
+                "block sig ops {} exceed limit of {}",
+                sig_ops,
+                Block::MAX_SIG_OPS
+            ),
+        }
+    }
+
+    pub fn to_strict_hex(&self) -> String {
+        self.to_buf().to_strict_hex()
+    }
+
+    pub fn from_strict_hex(hex: &str) -> Result<Self, EbxError> {
+        Self::from_buf(Vec::<u8>::from_strict_hex(hex)?)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::numbers::u256;
+    use crate::script::Script;
+    use crate::script_chunk::ScriptChunk;
+    use crate::tx_out::TxOut;
 
     #[test]
     fn test_to_buffer_writer() {
@@ -142,4 +199,24 @@ mod tests {
         let block = Block::new(header, vec![tx]);
         assert!(block.header.is_genesis());
     }
+
+    #[test]
+    fn test_from_buf_limits() {
+        let header = Header::from_genesis(0);
+
+        let mut bw = BufWriter::new();
+        bw.write(header.to_buf().to_vec());
+        bw.write(VarInt::from_u64(Block::MAX_TX_COUNT as u64 + 1).to_buf());
+        let err = Block::from_buf(bw.to_buf()).unwrap_err();
+        assert!(err.to_string().contains("tx count"));
+
+        // fails on the tx that crosses the limit, not at the end of the data
+        let big_script = Script::new(vec![ScriptChunk::from_data(vec![0; Tx::MAX_SIZE - 100])]);
+        let big_tx = Tx::new(1, vec![], vec![TxOut::new(0, big_script)], 0);
+        let block = Block::new(header.clone(), vec![big_tx.clone(); 9]);
+        let err = Block::from_buf(block.to_buf()).unwrap_err();
+        assert!(err.to_string().contains("block size"));
+        let block = Block::new(header, vec![big_tx; 8]);
+        assert_eq!(Block::from_buf(block.to_buf()).unwrap().txs.len(), 8);
+    }
 }
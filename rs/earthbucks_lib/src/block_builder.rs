@@ -1,43 +1,66 @@
 use crate::block::Block;
+use crate::chain_params::ChainParams;
+use crate::error::EbxError;
 use crate::header::Header;
-use crate::merkle_txs::MerkleTxs;
+use crate::merkle_tree::MerkleTree;
 use crate::script::Script;
+use crate::script_chunk::ScriptChunk;
 use crate::tx::Tx;
-use crate::tx_in::TxIn;
-use crate::tx_out::TxOut;
 
 pub struct BlockBuilder {
     pub header: Header,
     pub txs: Vec<Tx>,
-    pub merkle_txs: MerkleTxs,
+    pub root_merkle_tree: MerkleTree,
 }
 
 impl BlockBuilder {
-    pub fn new(header: Header, txs: Vec<Tx>, merkle_txs: MerkleTxs) -> Self {
+    pub fn new(header: Header, txs: Vec<Tx>, root_merkle_tree: MerkleTree) -> Self {
         Self {
             header,
             txs,
-            merkle_txs,
+            root_merkle_tree,
         }
     }
 
     pub fn from_block(block: Block) -> Self {
         let header = block.header;
         let txs = block.txs;
-        let merkle_txs = MerkleTxs::new(txs.clone());
-        Self::new(header, txs, merkle_txs)
+        let tx_ids: Vec<[u8; 32]> = txs.iter().map(|tx| tx.id()).collect();
+        let root_merkle_tree = MerkleTree::from_leaf_hashes(&tx_ids);
+        Self::new(header, txs, root_merkle_tree)
     }
 
-    pub fn from_genesis(output_script: Script, output_amount: u64, new_timestamp: u64) -> Self {
+    // the genesis block template. the coinbase pays the full block zero
+    // amount to output_script and, like every coinbase, pushes the domain of
+    // the miner. the header is valid except for the nonce.
+    pub fn from_genesis(
+        domain: &str,
+        output_script: Script,
+        new_timestamp: u64,
+        params: &ChainParams,
+    ) -> Self {
         let mut header = Header::from_genesis(new_timestamp);
-        let tx_input = TxIn::from_coinbase(output_script.clone());
-        let tx_output = TxOut::new(output_amount, output_script.clone());
-        let coinbase_tx = Tx::new(0, vec![tx_input], vec![tx_output], 0);
-        let txs = vec![coinbase_tx];
-        let merkle_txs = MerkleTxs::new(txs.clone());
-        let root: [u8; 32] = merkle_txs.root;
-        header.merkle_root = root;
-        Self::new(header, txs, merkle_txs)
+        let input_script = Script::new(vec![ScriptChunk::from_data(domain.as_bytes().to_vec())]);
+        let output_amount = params.coinbase_amount(0);
+        let coinbase_tx = Tx::from_coinbase(input_script, output_script, output_amount, 0);
+        let root_merkle_tree = MerkleTree::from_leaf_hashes(&[coinbase_tx.id()]);
+        header.merkle_root = root_merkle_tree.compute_merkle_root_id();
+        Self::new(header, vec![coinbase_tx], root_merkle_tree)
+    }
+
+    pub fn to_block(&self) -> Block {
+        Block::new(self.header.clone(), self.txs.clone())
+    }
+
+    // appends a tx to the block template, updating the merkle root without
+    // rehashing the txs already in the block.
+    pub fn add_tx(&mut self, tx: Tx) -> Result<(), EbxError> {
+        self.root_merkle_tree = self
+            .root_merkle_tree
+            .add_leaf_hash(tx.id(), Some(self.txs.len()))?;
+        self.header.merkle_root = self.root_merkle_tree.compute_merkle_root_id();
+        self.txs.push(tx);
+        Ok(())
     }
 }
 
@@ -46,8 +69,10 @@ mod tests {
     use super::*;
     use crate::block::Block;
     use crate::header::Header;
+    use crate::merkle_txs::MerkleTxs;
     use crate::numbers::u256;
     use crate::tx::Tx;
+    use crate::tx_out::TxOut;
 
     #[test]
     fn test_from_block() {
@@ -73,4 +98,18 @@ mod tests {
         assert_eq!(bb.header.timestamp, bh.timestamp);
         assert_eq!(bb.header.target, bh.target);
     }
+
+    #[test]
+    fn test_add_tx() {
+        let script = Script::from_empty();
+        let mut bb =
+            BlockBuilder::from_genesis("example.com", script.clone(), 0, &ChainParams::mainnet());
+        for i in 1..6u32 {
+            let tx = Tx::new(0, vec![], vec![TxOut::new(i as u64, script.clone())], 0);
+            bb.add_tx(tx).unwrap();
+            let block = bb.to_block();
+            assert_eq!(block.txs.len(), i as usize + 1);
+            assert_eq!(bb.header.merkle_root, MerkleTxs::new(&block.txs).root);
+        }
+    }
 }
@@ -1,15 +1,25 @@
 use crate::block::Block;
+use crate::buf_reader::BufReader;
+use crate::clock::Clock;
 use crate::domain::Domain;
+use crate::ebx_value::EbxValue;
+use crate::error::EbxError;
 use crate::header::Header;
 use crate::header_chain::HeaderChain;
 use crate::merkle_txs::MerkleTxs;
+use crate::sig_cache::SigCache;
+use crate::tx::Tx;
 use crate::tx_out_bn_map::TxOutBnMap;
 use crate::tx_verifier::TxVerifier;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
 
 pub struct BlockVerifier<'a> {
     pub block: Block,
     pub tx_out_bn_map: TxOutBnMap, // from earlier blocks
     pub lch: &'a HeaderChain,      // longest chain
+    pub sig_cache: Option<&'a SigCache>,
 }
 
 impl<'a> BlockVerifier<'a> {
@@ -18,97 +28,224 @@ impl<'a> BlockVerifier<'a> {
             block,
             tx_out_bn_map,
             lch,
+            sig_cache: None,
         }
     }
 
+    pub fn new_with_sig_cache(
+        block: Block,
+        tx_out_bn_map: TxOutBnMap,
+        lch: &'a HeaderChain,
+        sig_cache: &'a SigCache,
+    ) -> Self {
+        let mut block_verifier = Self::new(block, tx_out_bn_map, lch);
+        block_verifier.sig_cache = Some(sig_cache);
+        block_verifier
+    }
+
     pub fn header_is_valid_at(&mut self, timestamp: u64) -> bool {
         let header = &self.block.header;
         let lch = &self.lch;
         lch.new_header_is_valid_at(header, timestamp)
     }
 
+    // the same size limits that are enforced when parsing a block, plus the
+    // sig op limit, which needs the outputs the block spends
+    pub fn check_limits(&self) -> Result<(), EbxError> {
+        let txs = &self.block.txs;
+        if txs.len() > Block::MAX_TX_COUNT {
+            return Err(Block::tx_count_limit_error(txs.len()));
+        }
+        for tx in txs {
+            let size = tx.to_buf().len();
+            if size > Tx::MAX_SIZE {
+                return Err(Tx::size_limit_error(size));
+            }
+        }
+        let size = self.block.to_buf().len();
+        if size > Block::MAX_SIZE {
+            return Err(Block::size_limit_error(size));
+        }
+        let sig_ops = self.sig_op_count();
+        if sig_ops > Block::MAX_SIG_OPS {
+            return Err(Block::sig_ops_limit_error(sig_ops));
+        }
+        Ok(())
+    }
+
+    // counts the sig ops of every script spent by the block. spent outputs
+    // may come from earlier blocks or from earlier txs in this block. inputs
+    // spending unknown outputs are skipped, since the txs are invalid anyway.
+    // this includes the null input of the coinbase, which spends nothing.
+    pub fn sig_op_count(&self) -> usize {
+        let txs = &self.block.txs;
+        let mut block_txs: HashMap<[u8; 32], &Tx> = HashMap::new();
+        let mut sig_ops = 0;
+        for tx in txs {
+            for tx_input in &tx.inputs {
+                let tx_id = &tx_input.input_tx_id;
+                let tx_out_num = tx_input.input_tx_out_num;
+                let script = match self.tx_out_bn_map.get(tx_id, tx_out_num) {
+                    Some(tx_out_bn) => Some(&tx_out_bn.tx_out.script),
+                    None => block_txs
+                        .get(tx_id)
+                        .and_then(|tx| tx.outputs.get(tx_out_num as usize))
+                        .map(|tx_out| &tx_out.script),
+                };
+                sig_ops += script.map_or(0, |script| script.sig_op_count());
+            }
+            block_txs.insert(tx.id(), tx);
+        }
+        sig_ops
+    }
+
     pub fn merkle_root_is_valid(&self) -> bool {
         let txs = &self.block.txs;
         let merkle_root = self.block.header.merkle_root;
-        // TODO: Eliminate clone of txs
-        let merkle_txs = MerkleTxs::new(txs.clone());
+        let merkle_txs = MerkleTxs::new(txs);
         merkle_txs.root == merkle_root
     }
 
     pub fn has_valid_coinbase(&self) -> bool {
+        self.res_has_valid_coinbase().is_ok()
+    }
+
+    // the acceptance rules of resHasValidMintTx in the typescript library,
+    // which calls the coinbase a "mint tx", with these differences:
+    // - the coinbase is the first tx and has version 1, as it always has been
+    //   in this library
+    // - it may also pay to plain pkh outputs, and sweep expired outputs
+    // - the script needs only the domain. typescript also requires at least
+    //   two chunks, with a 32-byte block message id below the domain. this
+    //   library has no block messages, and the genesis blocks of every chain
+    //   push only the domain, so requiring the id would make them invalid.
+    // - the domain may not have surrounding whitespace, like Domain here
+    pub fn res_has_valid_coinbase(&self) -> Result<(), EbxError> {
+        let err = |message: &str| EbxError::GenericError {
+            source: None,
+            message: message.to_string(),
+        };
         // 1. coinbase tx is first tx
-        let txs = &self.block.txs;
-        if txs.is_empty() {
-            return false;
-        }
-        let coinbase_tx = &txs[0];
+        let coinbase_tx = match self.block.txs.first() {
+            Some(coinbase_tx) => coinbase_tx,
+            None => return Err(err("no txs")),
+        };
         if !coinbase_tx.is_coinbase() {
-            return false;
+            return Err(err("not a coinbase tx"));
         }
         // 2. lockNum equals block number
         if coinbase_tx.lock_abs != self.block.header.block_num {
-            return false;
+            return Err(err("lock_abs does not match block number"));
         }
         // 3. version is 1
         if coinbase_tx.version != 1 {
-            return false;
+            return Err(err("version is not 1"));
         }
-        // 4. all outputs are pkh
+        // 4. all outputs are pkh or standard
         for tx_output in &coinbase_tx.outputs {
-            if !tx_output.script.is_pkh_output() {
-                return false;
+            if !tx_output.script.is_pkh_output() && !tx_output.script.is_standard_output() {
+                return Err(err("output is not standard"));
             }
         }
-        // 5. output amount is correct
-        let total_output_value: u64 = coinbase_tx.outputs.iter().map(|output| output.value).sum();
-        let expected_coinbase_amount = Header::coinbase_amount(self.block.header.block_num);
-        if total_output_value != expected_coinbase_amount {
-            return false;
+        // 5. swept expired outputs are unspent and spendable
+        let block_num = self.block.header.block_num;
+        let median_time_past = self.lch.median_time_past().unwrap_or(0);
+        let mut tx_verifier = match self.sig_cache {
+            Some(sig_cache) => TxVerifier::new_with_sig_cache(
+                coinbase_tx,
+                &self.tx_out_bn_map,
+                block_num,
+                median_time_past,
+                &self.lch.params,
+                sig_cache,
+            ),
+            None => TxVerifier::new(
+                coinbase_tx,
+                &self.tx_out_bn_map,
+                block_num,
+                median_time_past,
+                &self.lch.params,
+            ),
+        };
+        if !tx_verifier.verify_coinbase_inputs() {
+            return Err(err("swept input is not valid"));
         }
-        // 5. coinbase script is valid (push only)
+        // 6. output amount is the coinbase amount plus the swept value
+        let output_values = coinbase_tx.outputs.iter().map(|output| output.value);
+        let expected_coinbase_amount = tx_verifier.coinbase_input_value().and_then(|swept_value| {
+            swept_value.checked_add(EbxValue::new(self.lch.params.coinbase_amount(block_num)))
+        });
+        if expected_coinbase_amount.is_none()
+            || EbxValue::checked_sum(output_values) != expected_coinbase_amount
+        {
+            return Err(err("output amount does not match expected coinbase amount"));
+        }
+        // 7. coinbase script is valid (push only)
         let coinbase_input = &coinbase_tx.inputs[0];
         let coinbase_script = &coinbase_input.script;
         if !coinbase_script.is_push_only() {
-            return false;
+            return Err(err("script is not push only"));
         }
-        // 6. domain name, top of the stack, is valid
-        let mut script_chunks = coinbase_script.chunks.clone();
-        if script_chunks.is_empty() {
-            return false;
-        }
-        let domain_chunk = script_chunks.pop().unwrap();
-        let domain_buf = domain_chunk.buffer.clone().unwrap();
-        let res_domain_str = String::from_utf8(domain_buf);
-        if res_domain_str.is_err() {
-            return false;
-        }
-        let domain_str = res_domain_str.unwrap();
-        if !Domain::is_valid_domain(&domain_str) {
-            return false;
+        // 8. domain name, top of the stack, is valid
+        let domain_buf = match coinbase_script.chunks.last() {
+            Some(domain_chunk) => domain_chunk.buffer.clone(),
+            None => return Err(err("no domain")),
+        };
+        let domain_str = domain_buf.and_then(|domain_buf| String::from_utf8(domain_buf).ok());
+        if domain_str.is_none_or(|domain_str| Domain::from_strict_str(&domain_str).is_err()) {
+            return Err(err("domain is not valid"));
         }
         // note that we do not verify whether domain is actually responsive and
         // delivers this block. that would require pinging the domain name,
-        // which is done elsewhere.
-        true
+        // which is done by DomainVerifier.
+        Ok(())
+    }
+
+    // adds the outputs of the coinbase and removes the expired outputs it
+    // sweeps. the coinbase must already be known to be valid.
+    fn add_coinbase_outputs(&mut self) {
+        let coinbase_tx = &self.block.txs[0];
+        for tx_input in coinbase_tx.inputs.iter().skip(1) {
+            self.tx_out_bn_map
+                .remove(&tx_input.input_tx_id, tx_input.input_tx_out_num);
+        }
+        self.tx_out_bn_map
+            .add_tx_outputs(coinbase_tx, self.block.header.block_num);
     }
 
     pub fn txs_are_valid(&mut self) -> bool {
         if !self.has_valid_coinbase() {
             return false;
         }
+        let block_num = self.block.header.block_num;
+        let median_time_past = self.lch.median_time_past().unwrap_or(0);
+        self.add_coinbase_outputs();
         let txs = &self.block.txs[1..];
         // iterate through all transactions except the first (coinbase tx)
         // verify with verifier
         // if invalid, return false
         // if valid, add outputs to tx_output_map and remove used outputs
         for tx in txs {
-            let mut tx_verifier =
-                TxVerifier::new(tx.clone(), &self.tx_out_bn_map, self.block.header.block_num);
+            let mut tx_verifier = match self.sig_cache {
+                Some(sig_cache) => TxVerifier::new_with_sig_cache(
+                    tx,
+                    &self.tx_out_bn_map,
+                    block_num,
+                    median_time_past,
+                    &self.lch.params,
+                    sig_cache,
+                ),
+                None => TxVerifier::new(
+                    tx,
+                    &self.tx_out_bn_map,
+                    block_num,
+                    median_time_past,
+                    &self.lch.params,
+                ),
+            };
             if !tx_verifier.verify() {
                 return false;
             }
-            let header = &self.block.header;
-            let block_num = header.block_num;
             self.tx_out_bn_map.add_tx_outputs(tx, block_num);
             // remove used outputs to prevent double spending
             for tx_input in &tx.inputs {
@@ -119,13 +256,132 @@ impl<'a> BlockVerifier<'a> {
         true
     }
 
-    pub fn is_valid_at(&mut self, timestamp: u64) -> bool {
-        if timestamp < self.block.header.timestamp {
+    // phase one of parallel verification. everything except the input scripts
+    // is checked here, in block order, so that txs spending outputs created
+    // earlier in the same block resolve correctly. returns, for each non-
+    // coinbase tx, a map holding only the outputs that tx spends. returns
+    // None if the block has no coinbase to skip.
+    fn resolve_tx_inputs(&mut self) -> Option<Vec<TxOutBnMap>> {
+        if self.block.txs.is_empty() {
+            return None;
+        }
+        let block_num = self.block.header.block_num;
+        let median_time_past = self.lch.median_time_past().unwrap_or(0);
+        self.add_coinbase_outputs();
+        let mut input_maps = Vec::new();
+        for tx in &self.block.txs[1..] {
+            let tx_verifier = TxVerifier::new(
+                tx,
+                &self.tx_out_bn_map,
+                block_num,
+                median_time_past,
+                &self.lch.params,
+            );
+            if !tx_verifier.verify_lock_abs()
+                || !tx_verifier.verify_is_not_coinbase()
+                || !tx_verifier.verify_no_double_spend()
+                || !tx_verifier.verify_output_values()
+            {
+                return None;
+            }
+            let mut input_map = TxOutBnMap::new();
+            for tx_input in &tx.inputs {
+                let tx_out_bn = self
+                    .tx_out_bn_map
+                    .get(&tx_input.input_tx_id, tx_input.input_tx_out_num)
+                    .unwrap();
+                input_map.add_tx_out_bn(
+                    &tx_input.input_tx_id,
+                    tx_input.input_tx_out_num,
+                    tx_out_bn.clone(),
+                );
+            }
+            input_maps.push(input_map);
+            self.tx_out_bn_map.add_tx_outputs(tx, block_num);
+            for tx_input in &tx.inputs {
+                self.tx_out_bn_map
+                    .remove(&tx_input.input_tx_id, tx_input.input_tx_out_num);
+            }
+        }
+        Some(input_maps)
+    }
+
+    // same result as txs_are_valid, but the input scripts, which dominate the
+    // cost of verification, are verified on n_threads threads.
+    pub fn txs_are_valid_parallel(&mut self, n_threads: usize) -> bool {
+        if !self.has_valid_coinbase() {
             return false;
         }
+        let input_maps = match self.resolve_tx_inputs() {
+            Some(input_maps) => input_maps,
+            None => return false,
+        };
+        let block_num = self.block.header.block_num;
+        let median_time_past = self.lch.median_time_past().unwrap_or(0);
+        let txs = &self.block.txs[1..];
+        let n_threads = n_threads.max(1);
+        let chunk_size = txs.len().div_ceil(n_threads).max(1);
+        let sig_cache = self.sig_cache;
+        let params = &self.lch.params;
+        let is_valid = AtomicBool::new(true);
+        thread::scope(|scope| {
+            for (txs, input_maps) in txs.chunks(chunk_size).zip(input_maps.chunks(chunk_size)) {
+                let is_valid = &is_valid;
+                scope.spawn(move || {
+                    for (tx, input_map) in txs.iter().zip(input_maps) {
+                        if !is_valid.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        let mut tx_verifier = match sig_cache {
+                            Some(sig_cache) => TxVerifier::new_with_sig_cache(
+                                tx,
+                                input_map,
+                                block_num,
+                                median_time_past,
+                                params,
+                                sig_cache,
+                            ),
+                            None => {
+                                TxVerifier::new(tx, input_map, block_num, median_time_past, params)
+                            }
+                        };
+                        if !tx_verifier.verify_inputs() {
+                            is_valid.store(false, Ordering::Relaxed);
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        is_valid.load(Ordering::Relaxed)
+    }
+
+    // checks a genesis block on its own, without comparing it to the genesis
+    // block of the chain parameters. this is how new genesis blocks for
+    // custom networks are checked before they are added to chain parameters.
+    pub fn is_valid_genesis(&self) -> bool {
+        let header = &self.block.header;
+        let max_target = BufReader::new(Header::MAX_TARGET_BYTES.to_vec())
+            .read_u256_be()
+            .unwrap();
+        header.is_genesis()
+            && header.is_version_valid()
+            && header.target == max_target
+            && header.is_work_ser_algo_valid()
+            && header.is_work_ser_hash_valid()
+            && header.is_id_valid()
+            && self.block.txs.len() == 1
+            && self.merkle_root_is_valid()
+            && self.has_valid_coinbase()
+    }
+
+    pub fn is_valid_at(&mut self, timestamp: u64) -> bool {
         if !self.header_is_valid_at(timestamp) {
             return false;
         }
+        if self.check_limits().is_err() {
+            return false;
+        }
         if !self.merkle_root_is_valid() {
             return false;
         }
@@ -135,8 +391,379 @@ impl<'a> BlockVerifier<'a> {
         true
     }
 
-    pub fn is_valid_now(&mut self) -> bool {
-        let timestamp = Header::get_new_timestamp();
-        self.is_valid_at(timestamp)
+    pub fn is_valid_now(&mut self, clock: &dyn Clock) -> bool {
+        self.is_valid_at(clock.now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_params::ChainParams;
+    use crate::key_pair::KeyPair;
+    use crate::opcode::Opcode;
+    use crate::pkh::Pkh;
+    use crate::pkh_key_map::PkhKeyMap;
+    use crate::script::Script;
+    use crate::script_chunk::ScriptChunk;
+    use crate::tx_builder::TxBuilder;
+    use crate::tx_in::TxIn;
+    use crate::tx_out::TxOut;
+    use crate::tx_signer::TxSigner;
+    use serde::Deserialize;
+    use std::fs;
+
+    struct Setup {
+        tx_out_bn_map: TxOutBnMap,
+        pkh_key_map: PkhKeyMap,
+        lch: HeaderChain,
+    }
+
+    fn setup(n_outputs: u32) -> Setup {
+        let mut tx_out_bn_map = TxOutBnMap::new();
+        let mut pkh_key_map = PkhKeyMap::new();
+        for i in 0..n_outputs {
+            let key = KeyPair::from_random();
+            let pkh = Pkh::from_pub_key_buffer(key.pub_key.buf.to_vec());
+            pkh_key_map.add(key, &pkh.buf);
+            let script = Script::from_pkh_output(&pkh.buf);
+            tx_out_bn_map.add(&[0; 32], i, TxOut::new(100, script), 0);
+        }
+        Setup {
+            tx_out_bn_map,
+            pkh_key_map,
+            lch: HeaderChain::new(),
+        }
+    }
+
+    fn make_block(setup: &Setup, txs: Vec<Tx>) -> Block {
+        let key = KeyPair::from_random();
+        let pkh = Pkh::from_pub_key_buffer(key.pub_key.buf.to_vec());
+        let coinbase_tx = setup.lch.get_next_coinbase_tx(&pkh, "example.com");
+        let mut all_txs = vec![coinbase_tx];
+        all_txs.extend(txs);
+        let header = Header::from_genesis(0);
+        Block::new(header, all_txs)
+    }
+
+    fn spend(tx_out_bn_map: &TxOutBnMap, pkh_key_map: &PkhKeyMap, n_in: usize) -> Tx {
+        let params = ChainParams::mainnet();
+        let mut tx_builder = TxBuilder::new(tx_out_bn_map, Script::from_empty(), 0, 0, &params);
+        tx_builder.add_output(TxOut::new(100 * n_in as u64, Script::from_empty()));
+        let tx = tx_builder.build().unwrap();
+        let mut tx_signer = TxSigner::new(tx, tx_out_bn_map, pkh_key_map, 0);
+        tx_signer.sign().unwrap()
+    }
+
+    fn both_paths(setup: &Setup, block: &Block) -> (bool, bool) {
+        let block2 = Block::new(block.header.clone(), block.txs.clone());
+        let mut sequential = BlockVerifier::new(block2, setup.tx_out_bn_map.clone(), &setup.lch);
+        let block3 = Block::new(block.header.clone(), block.txs.clone());
+        let mut parallel = BlockVerifier::new(block3, setup.tx_out_bn_map.clone(), &setup.lch);
+        let res = (
+            sequential.txs_are_valid(),
+            parallel.txs_are_valid_parallel(4),
+        );
+        if res.0 && res.1 {
+            let mut sequential_keys: Vec<_> = sequential.tx_out_bn_map.map.keys().collect();
+            let mut parallel_keys: Vec<_> = parallel.tx_out_bn_map.map.keys().collect();
+            sequential_keys.sort();
+            parallel_keys.sort();
+            assert_eq!(sequential_keys, parallel_keys);
+        }
+        res
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential_for_valid_block() {
+        let setup = setup(20);
+        let mut txs = vec![];
+        for i in 0..20 {
+            let mut input_map = TxOutBnMap::new();
+            let tx_out_bn = setup.tx_out_bn_map.get(&[0; 32], i).unwrap().clone();
+            input_map.add(&[0; 32], i, tx_out_bn.tx_out, 0);
+            txs.push(spend(&input_map, &setup.pkh_key_map, 1));
+        }
+        let block = make_block(&setup, txs);
+        assert_eq!(both_paths(&setup, &block), (true, true));
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential_for_intra_block_dependency() {
+        let mut setup = setup(2);
+        let key = KeyPair::from_random();
+        let pkh = Pkh::from_pub_key_buffer(key.pub_key.buf.to_vec());
+        setup.pkh_key_map.add(key, &pkh.buf);
+
+        let mut tx_builder = TxBuilder::new(
+            &setup.tx_out_bn_map,
+            Script::from_pkh_output(&pkh.buf),
+            0,
+            0,
+            &setup.lch.params,
+        );
+        tx_builder.add_output(TxOut::new(150, Script::from_pkh_output(&pkh.buf)));
+        let tx1 = tx_builder.build().unwrap();
+        let mut tx_signer = TxSigner::new(tx1, &setup.tx_out_bn_map, &setup.pkh_key_map, 0);
+        let tx1 = tx_signer.sign().unwrap();
+
+        let mut tx1_outputs = TxOutBnMap::new();
+        tx1_outputs.add_tx_outputs(&tx1, 0);
+        let tx2 = spend(&tx1_outputs, &setup.pkh_key_map, 2);
+
+        let block = make_block(&setup, vec![tx1.clone(), tx2.clone()]);
+        assert_eq!(both_paths(&setup, &block), (true, true));
+
+        // the dependent tx must come after the tx it spends
+        let block = make_block(&setup, vec![tx2, tx1]);
+        assert_eq!(both_paths(&setup, &block), (false, false));
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential_for_double_spend() {
+        let setup = setup(1);
+        let tx1 = spend(&setup.tx_out_bn_map, &setup.pkh_key_map, 1);
+        let mut tx2 = tx1.clone();
+        tx2.outputs[0].script = Script::from_pkh_output(&[1; 32]);
+        let tx2 = TxSigner::new(tx2, &setup.tx_out_bn_map, &setup.pkh_key_map, 0)
+            .sign()
+            .unwrap();
+        let block = make_block(&setup, vec![tx1, tx2]);
+        assert_eq!(both_paths(&setup, &block), (false, false));
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential_for_bad_signature() {
+        let setup = setup(8);
+        let mut txs = vec![];
+        for i in 0..8 {
+            let mut input_map = TxOutBnMap::new();
+            let tx_out_bn = setup.tx_out_bn_map.get(&[0; 32], i).unwrap().clone();
+            input_map.add(&[0; 32], i, tx_out_bn.tx_out, 0);
+            txs.push(spend(&input_map, &setup.pkh_key_map, 1));
+        }
+        let mut sig = txs[5].inputs[0].script.chunks[0].buffer.clone().unwrap();
+        sig[10] ^= 0xff;
+        txs[5].inputs[0].script.chunks[0].buffer = Some(sig);
+        let block = make_block(&setup, txs);
+        assert_eq!(both_paths(&setup, &block), (false, false));
+    }
+
+    #[test]
+    fn test_sig_cache_shared_with_mempool() {
+        let setup = setup(4);
+        let mut txs = vec![];
+        for i in 0..4 {
+            let mut input_map = TxOutBnMap::new();
+            let tx_out_bn = setup.tx_out_bn_map.get(&[0; 32], i).unwrap().clone();
+            input_map.add(&[0; 32], i, tx_out_bn.tx_out, 0);
+            txs.push(spend(&input_map, &setup.pkh_key_map, 1));
+        }
+
+        // accepting the txs into the mempool fills the cache
+        let sig_cache = SigCache::default();
+        for tx in &txs {
+            let mut tx_verifier = TxVerifier::new_with_sig_cache(
+                tx,
+                &setup.tx_out_bn_map,
+                0,
+                0,
+                &setup.lch.params,
+                &sig_cache,
+            );
+            assert!(tx_verifier.verify());
+        }
+        assert_eq!(sig_cache.len(), 4);
+
+        let block = make_block(&setup, txs);
+        let mut block_verifier = BlockVerifier::new_with_sig_cache(
+            Block::new(block.header.clone(), block.txs.clone()),
+            setup.tx_out_bn_map.clone(),
+            &setup.lch,
+            &sig_cache,
+        );
+        assert!(block_verifier.txs_are_valid());
+        let mut block_verifier = BlockVerifier::new_with_sig_cache(
+            block,
+            setup.tx_out_bn_map.clone(),
+            &setup.lch,
+            &sig_cache,
+        );
+        assert!(block_verifier.txs_are_valid_parallel(2));
+        assert_eq!(sig_cache.len(), 4);
+    }
+
+    #[test]
+    fn test_sig_op_limit() {
+        let mut setup = setup(2);
+        let mut tx1 = spend(&setup.tx_out_bn_map, &setup.pkh_key_map, 2);
+        tx1.outputs[0].script = Script::from_strict_str("CHECKSIG").unwrap();
+        let block = make_block(&setup, vec![tx1.clone()]);
+        let block_verifier = BlockVerifier::new(block, setup.tx_out_bn_map.clone(), &setup.lch);
+        assert_eq!(block_verifier.sig_op_count(), 2);
+        assert!(block_verifier.check_limits().is_ok());
+
+        // a tx spending an output of an earlier tx in the same block, and an
+        // output with too many sig ops
+        let mut tx2 = Tx::new(1, vec![], vec![], 0);
+        tx2.inputs
+            .push(TxIn::new(tx1.id(), 0, Script::from_empty(), 0));
+        let checksigs = vec![ScriptChunk::new(Opcode::OP_CHECKSIG, None); Block::MAX_SIG_OPS - 2];
+        setup
+            .tx_out_bn_map
+            .add(&[1; 32], 0, TxOut::new(0, Script::new(checksigs)), 0);
+        tx2.inputs
+            .push(TxIn::new([1; 32], 0, Script::from_empty(), 0));
+        let block = make_block(&setup, vec![tx1, tx2]);
+        let mut block_verifier = BlockVerifier::new(block, setup.tx_out_bn_map.clone(), &setup.lch);
+        assert_eq!(block_verifier.sig_op_count(), Block::MAX_SIG_OPS + 1);
+        let err = block_verifier.check_limits().unwrap_err();
+        assert!(err.to_string().contains("sig ops"));
+        assert!(!block_verifier.is_valid_at(0));
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let setup = setup(0);
+        let big_script = Script::new(vec![ScriptChunk::from_data(vec![0; Tx::MAX_SIZE - 100])]);
+        let big_tx = Tx::new(1, vec![], vec![TxOut::new(0, big_script)], 0);
+        assert!(big_tx.to_buf().len() <= Tx::MAX_SIZE);
+
+        let block = make_block(&setup, vec![big_tx.clone(); 8]);
+        let block_verifier = BlockVerifier::new(block, TxOutBnMap::new(), &setup.lch);
+        assert!(block_verifier.check_limits().is_ok());
+
+        let block = make_block(&setup, vec![big_tx.clone(); 9]);
+        let block_verifier = BlockVerifier::new(block, TxOutBnMap::new(), &setup.lch);
+        let err = block_verifier.check_limits().unwrap_err();
+        assert!(err.to_string().contains("block size"));
+
+        let mut huge_tx = big_tx;
+        huge_tx.outputs[0]
+            .script
+            .chunks
+            .push(ScriptChunk::from_data(vec![0; 100]));
+        let block = make_block(&setup, vec![huge_tx]);
+        let block_verifier = BlockVerifier::new(block, TxOutBnMap::new(), &setup.lch);
+        let err = block_verifier.check_limits().unwrap_err();
+        assert!(err.to_string().contains("tx size"));
+
+        let empty_tx = Tx::new(1, vec![], vec![], 0);
+        let block = make_block(&setup, vec![empty_tx; Block::MAX_TX_COUNT]);
+        let block_verifier = BlockVerifier::new(block, TxOutBnMap::new(), &setup.lch);
+        let err = block_verifier.check_limits().unwrap_err();
+        assert!(err.to_string().contains("tx count"));
+    }
+
+    #[test]
+    fn test_coinbase_maturity() {
+        let mut setup = setup(1);
+        let name = TxOutBnMap::name_from_output(&[0; 32], 0);
+        setup.tx_out_bn_map.map.get_mut(&name).unwrap().is_coinbase = true;
+        let tx = spend(&setup.tx_out_bn_map, &setup.pkh_key_map, 1);
+        let block = make_block(&setup, vec![tx]);
+        assert_eq!(both_paths(&setup, &block), (false, false));
+
+        // the outputs of the block's own coinbase are added as coinbase outputs
+        let block = make_block(&setup, vec![]);
+        let coinbase_id = block.txs[0].id();
+        let mut block_verifier = BlockVerifier::new(block, TxOutBnMap::new(), &setup.lch);
+        assert!(block_verifier.txs_are_valid());
+        let tx_out_bn = block_verifier.tx_out_bn_map.get(&coinbase_id, 0).unwrap();
+        assert!(tx_out_bn.is_coinbase);
+    }
+
+    #[test]
+    fn test_coinbase_sweeps_expired_outputs() {
+        let mut setup = setup(0);
+        let pkhx_script = Script::from_pkhx_90d_output(&[1; 32]);
+        setup
+            .tx_out_bn_map
+            .add(&[1; 32], 0, TxOut::new(500, pkhx_script), 0);
+        let sweep = |block_num: u32, amount: u64, n_inputs: usize| {
+            let expired_input = TxIn::new(
+                [1; 32],
+                0,
+                Script::from_expired_pkhx_input(),
+                Script::PKHX_90D_LOCK_REL,
+            );
+            let outputs = vec![TxOut::new(amount, Script::from_pkh_output(&[2; 32]))];
+            let input_script = Script::new(vec![ScriptChunk::from_data(b"example.com".to_vec())]);
+            let tx = Tx::from_coinbase_tx_ins(
+                input_script,
+                vec![expired_input; n_inputs],
+                outputs,
+                block_num,
+            );
+            let mut header = Header::from_genesis(0);
+            header.block_num = block_num;
+            Block::new(header, vec![tx])
+        };
+        let block_num = Script::PKHX_90D_LOCK_REL;
+        let subsidy = setup.lch.params.coinbase_amount(block_num);
+
+        // the coinbase amount becomes the subsidy plus the swept value
+        let block = sweep(block_num, subsidy + 500, 1);
+        assert_eq!(both_paths(&setup, &block), (true, true));
+        let mut block_verifier = BlockVerifier::new(block, setup.tx_out_bn_map.clone(), &setup.lch);
+        assert_eq!(block_verifier.sig_op_count(), 1);
+        assert!(block_verifier.txs_are_valid());
+        assert!(block_verifier.tx_out_bn_map.get(&[1; 32], 0).is_none());
+
+        let block = sweep(block_num, subsidy, 1);
+        let block_verifier = BlockVerifier::new(block, setup.tx_out_bn_map.clone(), &setup.lch);
+        let err = block_verifier.res_has_valid_coinbase().unwrap_err();
+        assert!(err.to_string().contains("output amount does not match"));
+
+        // outputs can only be swept once they have expired, and only once
+        let block = sweep(block_num - 1, subsidy + 500, 1);
+        let block_verifier = BlockVerifier::new(block, setup.tx_out_bn_map.clone(), &setup.lch);
+        let err = block_verifier.res_has_valid_coinbase().unwrap_err();
+        assert!(err.to_string().contains("swept input is not valid"));
+        let block = sweep(block_num, subsidy + 1000, 2);
+        assert_eq!(both_paths(&setup, &block), (false, false));
+
+        // the expired path of an unexpired output is refused by its script
+        let mut block = sweep(block_num, subsidy + 500, 1);
+        block.txs[0].inputs[1].lock_rel = 0;
+        let block_verifier = BlockVerifier::new(block, setup.tx_out_bn_map.clone(), &setup.lch);
+        assert!(!block_verifier.has_valid_coinbase());
+    }
+
+    #[derive(Deserialize)]
+    struct CoinbaseTxVectors {
+        coinbase_tx: Vec<CoinbaseTxVector>,
+    }
+
+    #[derive(Deserialize)]
+    struct CoinbaseTxVector {
+        description: String,
+        block_num: u32,
+        tx: String,
+        error: Option<String>,
+    }
+
+    #[test]
+    fn test_vectors_coinbase_tx() {
+        let data =
+            fs::read_to_string("./test_vectors/coinbase_tx.json").expect("Unable to read file");
+        let test_vectors: CoinbaseTxVectors =
+            serde_json::from_str(&data).expect("Unable to parse JSON");
+        let lch = HeaderChain::new();
+        for test_vector in test_vectors.coinbase_tx {
+            let tx = Tx::from_strict_hex(&test_vector.tx).unwrap();
+            let mut header = Header::from_genesis(0);
+            header.block_num = test_vector.block_num;
+            let block = Block::new(header, vec![tx]);
+            let block_verifier = BlockVerifier::new(block, TxOutBnMap::new(), &lch);
+            let res = block_verifier.res_has_valid_coinbase();
+            match test_vector.error {
+                None => assert!(res.is_ok(), "{}", test_vector.description),
+                Some(error) => {
+                    let err = res.expect_err(&test_vector.description);
+                    assert!(err.to_string().ends_with(&error), "{}", error);
+                }
+            }
+        }
     }
 }
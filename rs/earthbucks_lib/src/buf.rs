@@ -10,7 +10,7 @@ lazy_static! {
 }
 
 pub fn is_valid(hex: &str) -> bool {
-    RE.is_match(hex) && hex.len() % 2 == 0
+    RE.is_match(hex) && hex.len().is_multiple_of(2)
 }
 
 pub fn encode(data: &[u8]) -> String {
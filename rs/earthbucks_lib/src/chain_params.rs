@@ -0,0 +1,256 @@
+use crate::block::Block;
+use crate::error::EbxError;
+
+// genesis blocks pay the coinbase to a pkh of all zeros, which nobody has the
+// key for, with earthbucks.com as the domain. mainnet is at 2025-01-01, testnet
+// at 2025-01-02 and regtest at timestamp zero.
+const MAINNET_GENESIS_BLOCK_HEX: &str = concat!(
+    "000000000000000000000000000000000000000000000000000000000000000000342f2149b76ab1",
+    "46189fa941ff72ed4f13ee369799c658c42b1b8119aa4d26a7000001941f297c0000000000ffffff",
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000000000000000",
+    "00000000000000000000000000000000000000000000000000000000000000000000000000000000",
+    "00000000000000000000000000000000000000000000000000000000000000000000000000000000",
+    "00000000000000000001010100000000000000000000000000000000000000000000000000000000",
+    "00000000ffffffff104c0e65617274686275636b732e636f6d0000000001000009184e72a0002676",
+    "a74c20000000000000000000000000000000000000000000000000000000000000000088ac000000",
+    "00",
+);
+const TESTNET_GENESIS_BLOCK_HEX: &str = concat!(
+    "000000000000000000000000000000000000000000000000000000000000000000342f2149b76ab1",
+    "46189fa941ff72ed4f13ee369799c658c42b1b8119aa4d26a700000194244fd80000000000ffffff",
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000000000000000",
+    "00000000000000000000000000000000000000000000000000000000000000000000000000000000",
+    "00000000000000000000000000000000000000000000000000000000000000000000000000000000",
+    "00000000000000000001010100000000000000000000000000000000000000000000000000000000",
+    "00000000ffffffff104c0e65617274686275636b732e636f6d0000000001000009184e72a0002676",
+    "a74c20000000000000000000000000000000000000000000000000000000000000000088ac000000",
+    "00",
+);
+const REGTEST_GENESIS_BLOCK_HEX: &str = concat!(
+    "000000000000000000000000000000000000000000000000000000000000000000342f2149b76ab1",
+    "46189fa941ff72ed4f13ee369799c658c42b1b8119aa4d26a7000000000000000000000000ffffff",
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000000000000000",
+    "00000000000000000000000000000000000000000000000000000000000000000000000000000000",
+    "00000000000000000000000000000000000000000000000000000000000000000000000000000000",
+    "00000000000000000001010100000000000000000000000000000000000000000000000000000000",
+    "00000000ffffffff104c0e65617274686275636b732e636f6d0000000001000009184e72a0002676",
+    "a74c20000000000000000000000000000000000000000000000000000000000000000088ac000000",
+    "00",
+);
+
+// consensus parameters that differ between networks. mainnet is the real
+// network. testnet has the same timing as mainnet but a lower minimum
+// difficulty. regtest never adjusts the target away from the maximum target,
+// so that any header is valid work, and has a short block_interval,
+// halving_interval and coinbase_maturity so that tests can mine real chains
+// in seconds. each network has its own genesis block, and a chain is only
+// valid if it starts with that block.
+//
+// the relative locks of expiring outputs, such as Script::PKHX_90D_LOCK_REL,
+// are not chain parameters: they are pushed by the output scripts themselves,
+// so they are the same on every network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainParams {
+    pub name: &'static str,
+
+    // milliseconds
+    pub block_interval: u64,
+
+    // difficulty never adjusts below this
+    pub min_difficulty: u64,
+
+    // if false, the target never changes from the maximum target
+    pub adjust_difficulty: bool,
+
+    // milliseconds a header timestamp may be ahead of our clock. this must be
+    // small compared to the block interval, because a later timestamp makes
+    // the header easier to mine.
+    pub max_future_drift: u64,
+
+    // number of recent headers whose median timestamp is the median time past
+    pub median_time_span: usize,
+
+    // adams
+    pub initial_coinbase_amount: u64,
+
+    // the coinbase amount halves every this many blocks
+    pub halving_interval: u32,
+
+    // number of blocks before the outputs of a coinbase tx may be spent
+    pub coinbase_maturity: u32,
+
+    // the serialized genesis block. generate new ones with the genesis command
+    // of the earthbucks_lib binary.
+    pub genesis_block_hex: String,
+}
+
+impl ChainParams {
+    pub fn mainnet() -> Self {
+        Self {
+            name: "mainnet",
+            // 600_000 milliseconds = 600 seconds = 10 minutes
+            block_interval: 600_000,
+            min_difficulty: 2_000,
+            adjust_difficulty: true,
+            max_future_drift: 60_000,
+            median_time_span: 11,
+            // EBX: 100_000_000_000 adams = 1 earthbuck
+            // 100 earthbucks per block for the first 210,000 blocks
+            initial_coinbase_amount: 100 * 100_000_000_000,
+            halving_interval: 210_000,
+            coinbase_maturity: 100,
+            genesis_block_hex: MAINNET_GENESIS_BLOCK_HEX.to_string(),
+        }
+    }
+
+    pub fn testnet() -> Self {
+        Self {
+            name: "testnet",
+            min_difficulty: 1,
+            genesis_block_hex: TESTNET_GENESIS_BLOCK_HEX.to_string(),
+            ..Self::mainnet()
+        }
+    }
+
+    pub fn regtest() -> Self {
+        Self {
+            name: "regtest",
+            block_interval: 1_000,
+            min_difficulty: 1,
+            adjust_difficulty: false,
+            max_future_drift: 60_000,
+            median_time_span: 11,
+            initial_coinbase_amount: 100 * 100_000_000_000,
+            halving_interval: 150,
+            coinbase_maturity: 10,
+            genesis_block_hex: REGTEST_GENESIS_BLOCK_HEX.to_string(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, EbxError> {
+        match name {
+            "mainnet" => Ok(Self::mainnet()),
+            "testnet" => Ok(Self::testnet()),
+            "regtest" => Ok(Self::regtest()),
+            _ => Err(EbxError::GenericError {
+                source: None,
+                message: format!("unknown chain: {}", name),
+            }),
+        }
+    }
+
+    pub fn coinbase_amount(&self, block_num: u32) -> u64 {
+        // shift every halving interval ("halving")
+        let shift_by = block_num / self.halving_interval;
+        if shift_by >= 64 {
+            return 0;
+        }
+        self.initial_coinbase_amount >> shift_by
+    }
+
+    pub fn genesis_block(&self) -> Result<Block, EbxError> {
+        Block::from_strict_hex(&self.genesis_block_hex)
+    }
+
+    pub fn genesis_id(&self) -> Result<[u8; 32], EbxError> {
+        Ok(self.genesis_block()?.header.id())
+    }
+}
+
+impl Default for ChainParams {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_builder::BlockBuilder;
+    use crate::block_verifier::BlockVerifier;
+    use crate::header_chain::HeaderChain;
+    use crate::script::Script;
+    use crate::tx_out_bn_map::TxOutBnMap;
+
+    #[test]
+    fn test_from_name() {
+        for params in [
+            ChainParams::mainnet(),
+            ChainParams::testnet(),
+            ChainParams::regtest(),
+        ] {
+            assert_eq!(ChainParams::from_name(params.name).unwrap(), params);
+        }
+        assert!(ChainParams::from_name("devnet").is_err());
+        assert_eq!(ChainParams::default(), ChainParams::mainnet());
+    }
+
+    #[test]
+    fn test_coinbase_amount() {
+        let params = ChainParams::mainnet();
+        assert_eq!(params.coinbase_amount(0), 10_000_000_000_000);
+        assert_eq!(params.coinbase_amount(210_000), 5_000_000_000_000);
+        assert_eq!(params.coinbase_amount(420_000), 2_500_000_000_000);
+        assert_eq!(params.coinbase_amount(630_000), 1_250_000_000_000);
+        assert_eq!(params.coinbase_amount(840_000), 625_000_000_000);
+        assert_eq!(params.coinbase_amount(1_050_000), 312_500_000_000);
+        assert_eq!(params.coinbase_amount(1_260_000), 156_250_000_000);
+
+        let mut sum = 0;
+        for i in 0..2_000_000 {
+            sum += params.coinbase_amount(i);
+        }
+        // max u64: 18_446_744_073_709_551_616 - 1
+        // max val:  4_193_945_312_500_000_000
+        assert_eq!(sum, 4_193_945_312_500_000_000);
+    }
+
+    #[test]
+    fn test_regtest_coinbase_amount() {
+        let params = ChainParams::regtest();
+        assert_eq!(params.coinbase_amount(149), 10_000_000_000_000);
+        assert_eq!(params.coinbase_amount(150), 5_000_000_000_000);
+        assert_eq!(params.coinbase_amount(150 * 64), 0);
+        assert_eq!(params.coinbase_amount(u32::MAX), 0);
+    }
+
+    #[test]
+    fn test_genesis_blocks() {
+        let mut genesis_ids = vec![];
+        for params in [
+            ChainParams::mainnet(),
+            ChainParams::testnet(),
+            ChainParams::regtest(),
+        ] {
+            let block = params.genesis_block().unwrap();
+            let timestamp = block.header.timestamp;
+            let lch = HeaderChain::from_params(params.clone());
+            let mut block_verifier = BlockVerifier::new(block.clone(), TxOutBnMap::new(), &lch);
+            assert!(block_verifier.is_valid_genesis());
+            assert!(block_verifier.is_valid_at(timestamp));
+
+            // the genesis can be regenerated from its parts
+            let bb = BlockBuilder::from_genesis(
+                "earthbucks.com",
+                Script::from_pkh_output(&[0; 32]),
+                timestamp,
+                &params,
+            );
+            assert_eq!(bb.to_block().to_strict_hex(), params.genesis_block_hex);
+
+            genesis_ids.push(params.genesis_id().unwrap());
+        }
+        genesis_ids.dedup();
+        assert_eq!(genesis_ids.len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_genesis_block_hex() {
+        let params = ChainParams {
+            genesis_block_hex: "00".to_string(),
+            ..ChainParams::regtest()
+        };
+        assert!(params.genesis_block().is_err());
+        assert!(HeaderChain::with_genesis(params).is_err());
+    }
+}
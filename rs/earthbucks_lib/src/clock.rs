@@ -0,0 +1,70 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// the source of the current time, in milliseconds since the unix epoch, for
+// all validation that depends on the time. nodes use the system clock. tests
+// use a manual clock so that time-dependent validation is deterministic.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> u64;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis() as u64
+    }
+}
+
+// a clock that only moves when it is told to
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    timestamp: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn new(timestamp: u64) -> Self {
+        Self {
+            timestamp: AtomicU64::new(timestamp),
+        }
+    }
+
+    pub fn set(&self, timestamp: u64) {
+        self.timestamp.store(timestamp, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, millis: u64) {
+        self.timestamp.fetch_add(millis, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.timestamp.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new(1_000);
+        assert_eq!(clock.now(), 1_000);
+        clock.advance(500);
+        assert_eq!(clock.now(), 1_500);
+        clock.set(10);
+        assert_eq!(clock.now(), 10);
+    }
+
+    #[test]
+    fn test_system_clock() {
+        // 2024-01-01
+        assert!(SystemClock.now() > 1_704_067_200_000);
+    }
+}
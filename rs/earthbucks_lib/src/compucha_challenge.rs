@@ -0,0 +1,205 @@
+use crate::buf::EbxBuf;
+use crate::buf_reader::BufReader;
+use crate::buf_writer::BufWriter;
+use crate::error::EbxError;
+use crate::hash::double_blake3_hash;
+use crate::header::Header;
+use crate::numbers::u256;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+// a computational captcha: to use a challenge id issued by a server, a client
+// must find a nonce that makes the id of the challenge less than a target. the
+// same as CompuchaChallenge in the typescript library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompuchaChallenge {
+    pub challenge_id: [u8; 16],
+    pub nonce: u128,
+}
+
+impl CompuchaChallenge {
+    pub const SIZE: usize = 16 + 16;
+
+    pub fn new(challenge_id: [u8; 16], nonce: u128) -> Self {
+        Self {
+            challenge_id,
+            nonce,
+        }
+    }
+
+    pub fn from_random_nonce(challenge_id: [u8; 16]) -> Self {
+        Self::new(challenge_id, u128::from_be_bytes(EbxBuf::from_random()))
+    }
+
+    // searches for a nonce that meets the target on n_threads threads. each
+    // thread counts up from its own random nonce. no id is less than a target
+    // of zero, so that target is refused rather than searched forever.
+    pub fn from_solving(
+        challenge_id: [u8; 16],
+        target: u256,
+        n_threads: usize,
+    ) -> Result<Self, EbxError> {
+        if target == u256::ZERO {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "target is zero".to_string(),
+            });
+        }
+        let is_solved = AtomicBool::new(false);
+        let solution = Mutex::new(None);
+        thread::scope(|scope| {
+            for _ in 0..n_threads.max(1) {
+                let is_solved = &is_solved;
+                let solution = &solution;
+                scope.spawn(move || {
+                    let mut challenge = Self::from_random_nonce(challenge_id);
+                    while !is_solved.load(Ordering::Relaxed) {
+                        if challenge.is_target_valid(target) {
+                            is_solved.store(true, Ordering::Relaxed);
+                            solution.lock().unwrap().get_or_insert(challenge);
+                            return;
+                        }
+                        challenge.nonce = challenge.nonce.wrapping_add(1);
+                    }
+                });
+            }
+        });
+        Ok(solution.into_inner().unwrap().unwrap())
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut writer = BufWriter::new();
+        writer.write(self.challenge_id.to_vec());
+        writer.write_u128_be(self.nonce);
+        writer.to_buf()
+    }
+
+    pub fn from_buf(buf: Vec<u8>) -> Result<Self, EbxError> {
+        if buf.len() != Self::SIZE {
+            return Err(EbxError::InvalidSizeError { source: None });
+        }
+        let mut reader = BufReader::new(buf);
+        let challenge_id: [u8; 16] = reader.read(16)?.try_into().unwrap();
+        let nonce = reader.read_u128_be()?;
+        Ok(Self::new(challenge_id, nonce))
+    }
+
+    pub fn to_strict_hex(&self) -> String {
+        self.to_buf().to_strict_hex()
+    }
+
+    pub fn from_strict_hex(hex: &str) -> Result<Self, EbxError> {
+        Self::from_buf(Vec::<u8>::from_strict_hex(hex)?)
+    }
+
+    pub fn id(&self) -> [u8; 32] {
+        double_blake3_hash(&self.to_buf())
+    }
+
+    pub fn is_target_valid(&self, target: u256) -> bool {
+        let id_num = u256::from_be_slice(&self.id()).unwrap();
+        id_num < target
+    }
+
+    // the target of a difficulty, on the same scale as header difficulty: on
+    // average, a challenge of difficulty n takes n tries to solve
+    pub fn target_from_difficulty(difficulty: u64) -> u256 {
+        Header::target_from_difficulty(u256::from(difficulty.max(1)))
+    }
+
+    pub fn is_difficulty_valid(&self, difficulty: u64) -> bool {
+        self.is_target_valid(Self::target_from_difficulty(difficulty))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::fs;
+
+    #[derive(Deserialize)]
+    struct TestVectors {
+        compucha_challenge: Vec<TestVectorCompuchaChallenge>,
+        difficulty: Vec<TestVectorDifficulty>,
+    }
+
+    #[derive(Deserialize)]
+    struct TestVectorCompuchaChallenge {
+        challenge_id: String,
+        nonce: String,
+        compucha_challenge: String,
+        id: String,
+    }
+
+    #[derive(Deserialize)]
+    struct TestVectorDifficulty {
+        difficulty: u64,
+        compucha_challenge: String,
+    }
+
+    fn test_vectors() -> TestVectors {
+        let data = fs::read_to_string("./test_vectors/compucha_challenge.json")
+            .expect("Unable to read file");
+        serde_json::from_str(&data).expect("Unable to parse JSON")
+    }
+
+    #[test]
+    fn test_vectors_compucha_challenge() {
+        for test_vector in test_vectors().compucha_challenge {
+            let challenge_id = <[u8; 16]>::from_strict_hex(&test_vector.challenge_id).unwrap();
+            let nonce = u128::from_str_radix(&test_vector.nonce, 16).unwrap();
+            let challenge = CompuchaChallenge::new(challenge_id, nonce);
+            assert_eq!(challenge.to_strict_hex(), test_vector.compucha_challenge);
+            assert_eq!(challenge.id().to_strict_hex(), test_vector.id);
+            let challenge2 =
+                CompuchaChallenge::from_strict_hex(&test_vector.compucha_challenge).unwrap();
+            assert_eq!(challenge2, challenge);
+        }
+    }
+
+    #[test]
+    fn test_vectors_difficulty() {
+        for test_vector in test_vectors().difficulty {
+            let challenge =
+                CompuchaChallenge::from_strict_hex(&test_vector.compucha_challenge).unwrap();
+            assert!(challenge.is_difficulty_valid(test_vector.difficulty));
+        }
+    }
+
+    #[test]
+    fn test_to_from_buf() {
+        let challenge = CompuchaChallenge::from_random_nonce([7; 16]);
+        let buf = challenge.to_buf();
+        assert_eq!(buf.len(), CompuchaChallenge::SIZE);
+        assert_eq!(&buf[..16], &[7; 16]);
+        assert_eq!(&buf[16..], &challenge.nonce.to_be_bytes());
+        assert_eq!(CompuchaChallenge::from_buf(buf).unwrap(), challenge);
+        assert!(CompuchaChallenge::from_buf(vec![0; CompuchaChallenge::SIZE - 1]).is_err());
+        assert!(CompuchaChallenge::from_buf(vec![0; CompuchaChallenge::SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn test_from_solving() {
+        let challenge_id: [u8; 16] = EbxBuf::from_random();
+        for n_threads in [1, 4] {
+            let challenge = CompuchaChallenge::from_solving(
+                challenge_id,
+                CompuchaChallenge::target_from_difficulty(1_000),
+                n_threads,
+            )
+            .unwrap();
+            assert_eq!(challenge.challenge_id, challenge_id);
+            assert!(challenge.is_difficulty_valid(1_000));
+        }
+
+        // no challenge meets a target of zero
+        assert!(CompuchaChallenge::from_solving(challenge_id, u256::ZERO, 1).is_err());
+
+        // every challenge meets the lowest difficulty
+        let challenge = CompuchaChallenge::from_random_nonce(challenge_id);
+        assert!(challenge.is_difficulty_valid(0));
+        assert!(challenge.is_difficulty_valid(1));
+    }
+}
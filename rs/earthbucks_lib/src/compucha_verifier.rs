@@ -0,0 +1,101 @@
+use crate::buf::EbxBuf;
+use crate::clock::Clock;
+use crate::compucha_challenge::CompuchaChallenge;
+use crate::error::EbxError;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// the server side of compuchas. the server issues random challenge ids, and a
+// client proves it did the work by sending back a challenge that meets the
+// difficulty before it expires. each challenge id can be used only once.
+pub struct CompuchaVerifier<'a> {
+    pub difficulty: u64,
+    clock: &'a dyn Clock,
+    // the time each unused challenge id was issued
+    challenge_ids: Mutex<HashMap<[u8; 16], u64>>,
+}
+
+impl<'a> CompuchaVerifier<'a> {
+    pub const VALIDITY: u64 = 15 * 60 * 1000; // 15 minutes
+
+    pub fn new(difficulty: u64, clock: &'a dyn Clock) -> Self {
+        Self {
+            difficulty,
+            clock,
+            challenge_ids: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn new_challenge_id(&self) -> [u8; 16] {
+        let now = self.clock.now();
+        let challenge_id: [u8; 16] = EbxBuf::from_random();
+        let mut challenge_ids = self.challenge_ids.lock().unwrap();
+        challenge_ids.retain(|_, &mut issued_at| Self::is_issued_at_valid(issued_at, now));
+        challenge_ids.insert(challenge_id, now);
+        challenge_id
+    }
+
+    // the work is checked first, so that a wrong nonce does not use up the
+    // challenge id
+    pub fn verify(&self, challenge: &CompuchaChallenge) -> Result<(), EbxError> {
+        let err = |message: &str| EbxError::GenericError {
+            source: None,
+            message: message.to_string(),
+        };
+        if !challenge.is_difficulty_valid(self.difficulty) {
+            return Err(err("compucha does not meet the difficulty"));
+        }
+        let now = self.clock.now();
+        let mut challenge_ids = self.challenge_ids.lock().unwrap();
+        match challenge_ids.remove(&challenge.challenge_id) {
+            Some(issued_at) if Self::is_issued_at_valid(issued_at, now) => Ok(()),
+            Some(_) => Err(err("compucha challenge expired")),
+            None => Err(err("unknown compucha challenge")),
+        }
+    }
+
+    fn is_issued_at_valid(issued_at: u64, now: u64) -> bool {
+        now.saturating_sub(issued_at) < Self::VALIDITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn test_verify() {
+        let clock = ManualClock::new(1_700_000_000_000);
+        let verifier = CompuchaVerifier::new(100, &clock);
+        let target = CompuchaChallenge::target_from_difficulty(verifier.difficulty);
+
+        let challenge_id = verifier.new_challenge_id();
+        let challenge = CompuchaChallenge::from_solving(challenge_id, target, 2).unwrap();
+        assert!(verifier.verify(&challenge).is_ok());
+        let err = verifier.verify(&challenge).unwrap_err();
+        assert!(err.to_string().contains("unknown"));
+
+        // a challenge id that was never issued
+        let challenge = CompuchaChallenge::from_solving([0; 16], target, 2).unwrap();
+        assert!(verifier.verify(&challenge).is_err());
+
+        // a nonce that does not meet the difficulty leaves the id usable
+        let challenge_id = verifier.new_challenge_id();
+        let mut challenge = CompuchaChallenge::from_random_nonce(challenge_id);
+        while challenge.is_target_valid(target) {
+            challenge.nonce = challenge.nonce.wrapping_add(1);
+        }
+        let err = verifier.verify(&challenge).unwrap_err();
+        assert!(err.to_string().contains("difficulty"));
+        let challenge = CompuchaChallenge::from_solving(challenge_id, target, 2).unwrap();
+        assert!(verifier.verify(&challenge).is_ok());
+
+        // a challenge solved too late
+        let challenge_id = verifier.new_challenge_id();
+        let challenge = CompuchaChallenge::from_solving(challenge_id, target, 2).unwrap();
+        clock.advance(CompuchaVerifier::VALIDITY);
+        let err = verifier.verify(&challenge).unwrap_err();
+        assert!(err.to_string().contains("expired"));
+    }
+}
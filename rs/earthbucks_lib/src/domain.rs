@@ -1,22 +1,46 @@
-use regex::Regex;
+use crate::error::EbxError;
+use std::fmt;
 
+// a domain name. domains name mines in coinbase txs, and bind keys to mines in
+// domain keys. the rules are those of Domain in the typescript library, except
+// that surrounding whitespace is refused rather than ignored, so that a domain
+// has only one string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Domain {
     domain_str: String,
 }
 
 impl Domain {
-    pub fn new(domain_str: String) -> Self {
+    fn new(domain_str: String) -> Self {
         Self { domain_str }
     }
 
-    pub fn from_strict_str(domain_str: String) -> Self {
-        Self::new(domain_str)
+    pub fn from_strict_str(domain_str: &str) -> Result<Self, EbxError> {
+        if domain_str.trim() != domain_str || !Self::is_valid_domain(domain_str) {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: format!("invalid domain: {}", domain_str),
+            });
+        }
+        Ok(Self::new(domain_str.to_string()))
+    }
+
+    pub fn to_strict_str(&self) -> String {
+        self.domain_str.clone()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.domain_str
     }
 
     pub fn is_valid(&self) -> bool {
         Self::is_valid_domain(&self.domain_str)
     }
 
+    // our domain name validation is intentionally simpler, and different, than
+    // real domain name validation. it is intended to be a simple check to
+    // prevent common mistakes, not a full validation of a domain name. the
+    // rules are the same as Domain.isValidDomain in the typescript library.
     pub fn is_valid_domain(domain_str: &str) -> bool {
         let domain_str = domain_str.trim();
         if domain_str.len() < 4 {
@@ -44,21 +68,39 @@ impl Domain {
         if domain_parts.iter().any(|&part| part.len() > 63) {
             return false;
         }
-        let re = Regex::new(r"^[a-z0-9]+$").unwrap();
-        if domain_parts.iter().any(|&part| !re.is_match(part)) {
+        let is_lower_alphanumeric = |part: &str| {
+            !part.is_empty()
+                && part
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+        };
+        if !domain_parts.iter().all(|&part| is_lower_alphanumeric(part)) {
             return false;
         }
+        true
+    }
 
-        if domain_parts.iter().any(|&part| part.starts_with('-')) {
-            return false;
-        }
-        if domain_parts.iter().any(|&part| part.ends_with('-')) {
-            return false;
+    // "domain" may start with a port number when it is a localhost domain in
+    // development, e.g. 4189.localhost goes to http://4189.localhost:4189.
+    // otherwise, assume https and no extra www.
+    pub fn domain_to_base_url(domain_str: &str) -> String {
+        if domain_str.ends_with("localhost") {
+            let port = domain_str.split('.').next().unwrap_or_default();
+            if port.parse::<u16>().is_ok_and(|port| port > 0) {
+                return format!("http://{}.localhost:{}", port, port);
+            }
         }
-        if domain_parts.iter().any(|&part| part.contains("--")) {
-            return false;
-        }
-        true
+        format!("https://{}", domain_str)
+    }
+
+    pub fn to_base_url(&self) -> String {
+        Self::domain_to_base_url(&self.domain_str)
+    }
+}
+
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.domain_str)
     }
 }
 
@@ -68,15 +110,48 @@ mod tests {
 
     #[test]
     fn test_is_valid_domain() {
-        assert!(Domain::from_strict_str("earthbucks.com".to_string()).is_valid());
-        assert!(!Domain::from_strict_str("earthbucks.com.".to_string()).is_valid());
-        assert!(!Domain::from_strict_str(".earthbucks.com".to_string()).is_valid());
-        assert!(
-            Domain::from_strict_str("node.node.node.node.earthbucks.com".to_string()).is_valid()
+        assert!(Domain::is_valid_domain("earthbucks.com"));
+        assert!(!Domain::is_valid_domain("earth-bucks.com"));
+        assert!(!Domain::is_valid_domain("EarthBucks.com"));
+        assert!(!Domain::is_valid_domain("earthbucks.com."));
+        assert!(!Domain::is_valid_domain(".earthbucks.com"));
+        assert!(!Domain::is_valid_domain("earthbucks..com"));
+        assert!(!Domain::is_valid_domain("earthbucks"));
+        assert!(!Domain::is_valid_domain("a.b"));
+        assert!(Domain::is_valid_domain(
+            "node.node.node.node.earthbucks.com"
+        ));
+        assert!(!Domain::is_valid_domain(
+            "node.node.node.node.node.node.node.node.node.earthbucks.com"
+        ));
+        assert!(Domain::is_valid_domain(&format!("{}.com", "a".repeat(63))));
+        assert!(!Domain::is_valid_domain(&format!("{}.com", "a".repeat(64))));
+    }
+
+    #[test]
+    fn test_from_strict_str() {
+        let domain = Domain::from_strict_str("earthbucks.com").unwrap();
+        assert_eq!(domain.to_strict_str(), "earthbucks.com");
+        assert_eq!(domain.to_string(), "earthbucks.com");
+        assert!(domain.is_valid());
+        // surrounding whitespace is refused
+        assert!(Domain::from_strict_str(" earthbucks.com\n").is_err());
+        assert!(Domain::from_strict_str("earth-bucks.com").is_err());
+    }
+
+    #[test]
+    fn test_domain_to_base_url() {
+        assert_eq!(
+            Domain::domain_to_base_url("earthbucks.com"),
+            "https://earthbucks.com"
+        );
+        assert_eq!(
+            Domain::domain_to_base_url("4189.localhost"),
+            "http://4189.localhost:4189"
+        );
+        assert_eq!(
+            Domain::domain_to_base_url("node.localhost"),
+            "https://node.localhost"
         );
-        assert!(!Domain::from_strict_str(
-            "node.node.node.node.node.node.node.node.node.earthbucks.com".to_string()
-        )
-        .is_valid());
     }
 }
@@ -0,0 +1,83 @@
+use crate::domain::Domain;
+use crate::error::EbxError;
+use crate::priv_key::PrivKey;
+
+// the private key a mine uses to sign on behalf of its domain, e.g. to sign
+// in users or to sign work data. the string encoding is "domain:ebxprv...",
+// the same as DomainPrivKey in the typescript library.
+#[derive(Debug, Clone)]
+pub struct DomainPrivKey {
+    pub domain: Domain,
+    pub priv_key: PrivKey,
+}
+
+impl DomainPrivKey {
+    pub fn new(domain: Domain, priv_key: PrivKey) -> Self {
+        Self { domain, priv_key }
+    }
+
+    pub fn from_random(domain: Domain) -> Self {
+        Self::new(domain, PrivKey::from_random())
+    }
+
+    pub fn to_strict_str(&self) -> String {
+        format!("{}:{}", self.domain, self.priv_key.to_strict_str())
+    }
+
+    // the same as fromString in the typescript library: the string is split on
+    // ":", and anything after a second ":" is ignored
+    pub fn from_strict_str(s: &str) -> Result<Self, EbxError> {
+        let mut parts = s.split(':');
+        let (domain_str, priv_key_str) = match (parts.next(), parts.next()) {
+            (Some(domain_str), Some(priv_key_str))
+                if !domain_str.is_empty() && !priv_key_str.is_empty() =>
+            {
+                (domain_str, priv_key_str)
+            }
+            _ => {
+                return Err(EbxError::GenericError {
+                    source: None,
+                    message: "invalid domain private key string".to_string(),
+                })
+            }
+        };
+        let domain = Domain::from_strict_str(domain_str)?;
+        let priv_key = PrivKey::from_strict_str(priv_key_str)?;
+        Ok(Self::new(domain, priv_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_from_strict_str() {
+        let domain = Domain::from_strict_str("earthbucks.com").unwrap();
+        let domain_priv_key = DomainPrivKey::from_random(domain);
+        let s = domain_priv_key.to_strict_str();
+        assert!(s.starts_with("earthbucks.com:ebxprv"));
+        let domain_priv_key2 = DomainPrivKey::from_strict_str(&s).unwrap();
+        assert_eq!(domain_priv_key2.domain, domain_priv_key.domain);
+        assert_eq!(domain_priv_key2.priv_key.buf, domain_priv_key.priv_key.buf);
+
+        let priv_key_str = domain_priv_key.priv_key.to_strict_str();
+        for s in [
+            priv_key_str.clone(),
+            format!(":{}", priv_key_str),
+            format!("earth-bucks.com:{}", priv_key_str),
+            "earthbucks.com:".to_string(),
+        ] {
+            assert!(DomainPrivKey::from_strict_str(&s).is_err(), "{}", s);
+        }
+
+        // the same as the typescript library, anything after a second ":" is
+        // ignored, but unlike it a domain with surrounding whitespace is refused
+        let s = format!("earthbucks.com:{}:extra", priv_key_str);
+        let domain_priv_key2 = DomainPrivKey::from_strict_str(&s).unwrap();
+        assert_eq!(domain_priv_key2.domain.as_str(), "earthbucks.com");
+        assert_eq!(domain_priv_key2.priv_key.buf, domain_priv_key.priv_key.buf);
+        let s = format!(" earthbucks.com:{}", priv_key_str);
+        assert!(DomainPrivKey::from_strict_str(&s).is_err());
+    }
+}
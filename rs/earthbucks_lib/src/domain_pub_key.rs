@@ -0,0 +1,118 @@
+use crate::domain::Domain;
+use crate::domain_priv_key::DomainPrivKey;
+use crate::error::EbxError;
+use crate::pub_key::PubKey;
+
+// the public key of a mine's domain, which anyone can use to check messages
+// the mine signs. the string encoding is "domain:ebxpub...", the same as
+// DomainPubKey in the typescript library.
+#[derive(Debug, Clone)]
+pub struct DomainPubKey {
+    pub domain: Domain,
+    pub pub_key: PubKey,
+}
+
+impl DomainPubKey {
+    pub fn new(domain: Domain, pub_key: PubKey) -> Self {
+        Self { domain, pub_key }
+    }
+
+    pub fn from_domain_priv_key(domain_priv_key: &DomainPrivKey) -> Result<Self, EbxError> {
+        let pub_key = PubKey::from_priv_key(&domain_priv_key.priv_key)?;
+        Ok(Self::new(domain_priv_key.domain.clone(), pub_key))
+    }
+
+    pub fn to_strict_str(&self) -> String {
+        format!("{}:{}", self.domain, self.pub_key.to_strict_str())
+    }
+
+    // the same as fromString in the typescript library: the string is split on
+    // ":", and anything after a second ":" is ignored
+    pub fn from_strict_str(s: &str) -> Result<Self, EbxError> {
+        let mut parts = s.split(':');
+        let (domain_str, pub_key_str) = match (parts.next(), parts.next()) {
+            (Some(domain_str), Some(pub_key_str))
+                if !domain_str.is_empty() && !pub_key_str.is_empty() =>
+            {
+                (domain_str, pub_key_str)
+            }
+            _ => {
+                return Err(EbxError::GenericError {
+                    source: None,
+                    message: "invalid domain public key string".to_string(),
+                })
+            }
+        };
+        let domain = Domain::from_strict_str(domain_str)?;
+        let pub_key = PubKey::from_strict_str(pub_key_str)?;
+        Ok(Self::new(domain, pub_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::priv_key::PrivKey;
+    use serde::Deserialize;
+    use std::fs;
+
+    #[derive(Deserialize)]
+    struct DomainKeyVectors {
+        domain_key: Vec<DomainKeyVector>,
+        domain_pub_key_from_str: Vec<FromStrVector>,
+    }
+
+    #[derive(Deserialize)]
+    struct DomainKeyVector {
+        domain_priv_key: String,
+        domain_pub_key: String,
+    }
+
+    #[derive(Deserialize)]
+    struct FromStrVector {
+        str: String,
+        domain: Option<String>,
+    }
+
+    #[test]
+    fn test_from_domain_priv_key() {
+        let domain = Domain::from_strict_str("earthbucks.com").unwrap();
+        let domain_priv_key = DomainPrivKey::from_random(domain);
+        let domain_pub_key = DomainPubKey::from_domain_priv_key(&domain_priv_key).unwrap();
+        assert_eq!(domain_pub_key.domain, domain_priv_key.domain);
+        let pub_key = PubKey::from_priv_key(&domain_priv_key.priv_key).unwrap();
+        assert_eq!(domain_pub_key.pub_key.buf, pub_key.buf);
+    }
+
+    #[test]
+    fn test_vectors_domain_key() {
+        let data =
+            fs::read_to_string("./test_vectors/domain_key.json").expect("Unable to read file");
+        let test_vectors: DomainKeyVectors =
+            serde_json::from_str(&data).expect("Unable to parse JSON");
+        for test_vector in &test_vectors.domain_key {
+            let domain_priv_key =
+                DomainPrivKey::from_strict_str(&test_vector.domain_priv_key).unwrap();
+            assert_eq!(domain_priv_key.to_strict_str(), test_vector.domain_priv_key);
+            let domain_pub_key = DomainPubKey::from_domain_priv_key(&domain_priv_key).unwrap();
+            assert_eq!(domain_pub_key.to_strict_str(), test_vector.domain_pub_key);
+            let domain_pub_key =
+                DomainPubKey::from_strict_str(&test_vector.domain_pub_key).unwrap();
+            assert_eq!(domain_pub_key.to_strict_str(), test_vector.domain_pub_key);
+        }
+        for test_vector in test_vectors.domain_pub_key_from_str {
+            let res = DomainPubKey::from_strict_str(&test_vector.str);
+            let domain = res
+                .ok()
+                .map(|domain_pub_key| domain_pub_key.domain.to_strict_str());
+            assert_eq!(domain, test_vector.domain, "{}", test_vector.str);
+        }
+        let priv_key_str = PrivKey::from_random().to_strict_str();
+        assert!(
+            DomainPubKey::from_strict_str(&format!("earthbucks.com:{}", priv_key_str)).is_err()
+        );
+        // the typescript library keeps surrounding whitespace, but it is refused
+        let pub_key_str = &test_vectors.domain_key[0].domain_pub_key;
+        assert!(DomainPubKey::from_strict_str(&format!(" {} ", pub_key_str)).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::block::Block;
+use crate::clock::Clock;
+use crate::domain::Domain;
+use crate::domain_pub_key::DomainPubKey;
+use crate::error::EbxError;
+use crate::http_client::HttpClient;
+use crate::pub_key::PubKey;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// the coinbase of every block names the domain of the mine that made it. a
+// mine proves that it controls its domain by serving a well-known document,
+// in which it lists the blocks it has made and its domain pubkey. the format
+// of the document is described in docs/well-known.md.
+pub struct DomainVerifier<'a> {
+    http_client: &'a dyn HttpClient,
+    clock: &'a dyn Clock,
+    // the time each (domain, entry) was last found in its domain's document.
+    // entries older than the ttl are removed whenever an entry is added.
+    cache: Mutex<HashMap<(String, String), u64>>,
+    pub cache_ttl: u64,
+}
+
+impl<'a> DomainVerifier<'a> {
+    pub const WELL_KNOWN_PATH: &'static str = "/.well-known/earthbucks.txt";
+    pub const DEFAULT_CACHE_TTL: u64 = 10 * 60 * 1000; // 10 minutes
+
+    pub fn new(http_client: &'a dyn HttpClient, clock: &'a dyn Clock) -> Self {
+        Self {
+            http_client,
+            clock,
+            cache: Mutex::new(HashMap::new()),
+            cache_ttl: Self::DEFAULT_CACHE_TTL,
+        }
+    }
+
+    pub fn well_known_url(domain: &str) -> String {
+        format!(
+            "{}{}",
+            Domain::domain_to_base_url(domain),
+            Self::WELL_KNOWN_PATH
+        )
+    }
+
+    pub fn block_entry(block_id: &[u8; 32]) -> String {
+        format!("block {}", hex::encode(block_id))
+    }
+
+    pub fn pub_key_entry(pub_key: &PubKey) -> String {
+        format!("pub_key {}", pub_key.to_strict_str())
+    }
+
+    // the domain in the coinbase script, as checked by
+    // BlockVerifier::res_has_valid_coinbase
+    pub fn coinbase_domain(block: &Block) -> Result<Domain, EbxError> {
+        let domain = block
+            .txs
+            .first()
+            .and_then(|coinbase_tx| coinbase_tx.inputs.first())
+            .and_then(|coinbase_input| coinbase_input.script.chunks.last())
+            .and_then(|domain_chunk| domain_chunk.buffer.clone())
+            .and_then(|domain_buf| String::from_utf8(domain_buf).ok())
+            .and_then(|domain_str| Domain::from_strict_str(&domain_str).ok());
+        domain.ok_or_else(|| EbxError::GenericError {
+            source: None,
+            message: "block has no valid coinbase domain".to_string(),
+        })
+    }
+
+    // the mine at the coinbase domain lists the block
+    pub fn verify_block(&self, block: &Block) -> Result<(), EbxError> {
+        let domain = Self::coinbase_domain(block)?;
+        let entry = Self::block_entry(&block.header.id());
+        self.verify_entry(&domain, &entry)
+    }
+
+    // the mine at the domain lists the pubkey as its domain pubkey
+    pub fn verify_domain_pub_key(&self, domain_pub_key: &DomainPubKey) -> Result<(), EbxError> {
+        let entry = Self::pub_key_entry(&domain_pub_key.pub_key);
+        self.verify_entry(&domain_pub_key.domain, &entry)
+    }
+
+    // only entries that were found are cached, so that a block which is
+    // listed after the document was last fetched is still found
+    fn verify_entry(&self, domain: &Domain, entry: &str) -> Result<(), EbxError> {
+        let now = self.clock.now();
+        let key = (domain.to_strict_str(), entry.to_string());
+        if let Some(&verified_at) = self.cache.lock().unwrap().get(&key) {
+            if now < verified_at.saturating_add(self.cache_ttl) {
+                return Ok(());
+            }
+        }
+        let url = Self::well_known_url(domain.as_str());
+        let document = self.http_client.get(&url)?;
+        let is_listed = document
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .any(|line| line == entry);
+        if !is_listed {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: format!("{} does not list {}", url, entry),
+            });
+        }
+        let mut cache = self.cache.lock().unwrap();
+        cache.retain(|_, &mut verified_at| now < verified_at.saturating_add(self.cache_ttl));
+        cache.insert(key, now);
+        Ok(())
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    pub fn cache_len(&self) -> usize {
+        self.cache.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::header::Header;
+    use crate::header_chain::HeaderChain;
+    use crate::http_client::ManualHttpClient;
+    use crate::key_pair::KeyPair;
+    use crate::pkh::Pkh;
+
+    fn make_block(domain: &str) -> Block {
+        let key = KeyPair::from_random();
+        let pkh = Pkh::from_pub_key_buffer(key.pub_key.buf.to_vec());
+        let coinbase_tx = HeaderChain::new().get_next_coinbase_tx(&pkh, domain);
+        Block::new(Header::from_genesis(0), vec![coinbase_tx])
+    }
+
+    #[test]
+    fn test_well_known_url() {
+        assert_eq!(
+            DomainVerifier::well_known_url("earthbucks.com"),
+            "https://earthbucks.com/.well-known/earthbucks.txt"
+        );
+        assert_eq!(
+            DomainVerifier::well_known_url("4189.localhost"),
+            "http://4189.localhost:4189/.well-known/earthbucks.txt"
+        );
+    }
+
+    #[test]
+    fn test_verify_block() {
+        let http_client = ManualHttpClient::new();
+        let clock = ManualClock::new(0);
+        let domain_verifier = DomainVerifier::new(&http_client, &clock);
+        let url = DomainVerifier::well_known_url("example.com");
+        let block = make_block("example.com");
+        let block_entry = DomainVerifier::block_entry(&block.header.id());
+
+        // the domain is not serving a document
+        assert!(domain_verifier.verify_block(&block).is_err());
+
+        // the block is not listed yet, and misses are not cached
+        http_client.set(&url, "# example.com\n\n");
+        let err = domain_verifier.verify_block(&block).unwrap_err();
+        assert!(err.to_string().contains("does not list"));
+        http_client.set(&url, &format!("# example.com\n{}\n", block_entry));
+        assert!(domain_verifier.verify_block(&block).is_ok());
+        assert_eq!(http_client.n_requests(), 3);
+
+        // hits are cached until the ttl expires
+        http_client.remove(&url);
+        clock.advance(DomainVerifier::DEFAULT_CACHE_TTL - 1);
+        assert!(domain_verifier.verify_block(&block).is_ok());
+        assert_eq!(http_client.n_requests(), 3);
+        clock.advance(1);
+        assert!(domain_verifier.verify_block(&block).is_err());
+        assert_eq!(http_client.n_requests(), 4);
+
+        // expired entries are removed when another entry is added
+        let block2 = make_block("example.com");
+        let block2_entry = DomainVerifier::block_entry(&block2.header.id());
+        http_client.set(&url, &format!("{}\n{}\n", block_entry, block2_entry));
+        assert!(domain_verifier.verify_block(&block).is_ok());
+        assert_eq!(domain_verifier.cache_len(), 1);
+        clock.advance(DomainVerifier::DEFAULT_CACHE_TTL);
+        assert!(domain_verifier.verify_block(&block2).is_ok());
+        assert_eq!(domain_verifier.cache_len(), 1);
+
+        // a block naming another domain is checked against that domain
+        http_client.set(&url, &block_entry);
+        let other_block = make_block("other.com");
+        assert!(domain_verifier.verify_block(&other_block).is_err());
+    }
+
+    #[test]
+    fn test_verify_domain_pub_key() {
+        let http_client = ManualHttpClient::new();
+        let clock = ManualClock::new(0);
+        let domain_verifier = DomainVerifier::new(&http_client, &clock);
+        let domain = Domain::from_strict_str("example.com").unwrap();
+        let pub_key = KeyPair::from_random().pub_key;
+        let url = DomainVerifier::well_known_url("example.com");
+        let document = format!("  {}  \n", DomainVerifier::pub_key_entry(&pub_key));
+        http_client.set(&url, &document);
+        let domain_pub_key = DomainPubKey::new(domain.clone(), pub_key.clone());
+        assert!(domain_verifier
+            .verify_domain_pub_key(&domain_pub_key)
+            .is_ok());
+        let other_pub_key = KeyPair::from_random().pub_key;
+        let domain_pub_key = DomainPubKey::new(domain, other_pub_key);
+        assert!(domain_verifier
+            .verify_domain_pub_key(&domain_pub_key)
+            .is_err());
+        let other_domain = Domain::from_strict_str("other.com").unwrap();
+        let domain_pub_key = DomainPubKey::new(other_domain, pub_key);
+        assert!(domain_verifier
+            .verify_domain_pub_key(&domain_pub_key)
+            .is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::error::EbxError;
+use std::fmt;
+
+// the "value" of a tx output is measured in "adams". 1 EBX = 10^11 adams. all
+// arithmetic on values is checked, because the values in a tx or block are
+// chosen by whoever made it, and a sum that wraps around could make an invalid
+// tx look valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct EbxValue {
+    pub value: u64,
+}
+
+impl EbxValue {
+    pub const DECIMALS: u32 = 11;
+    pub const ADAMS_PER_EBX: u64 = 100_000_000_000;
+
+    // more than all the coinbase amounts of mainnet added together, which is
+    // 100 EBX per block for 210,000 blocks, halving every 210,000 blocks. no
+    // single value, and no sum of values, can be more than this.
+    pub const MAX_MONEY: u64 = 42_000_000 * Self::ADAMS_PER_EBX;
+
+    pub fn new(value: u64) -> Self {
+        Self { value }
+    }
+
+    pub fn zero() -> Self {
+        Self { value: 0 }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.value <= Self::MAX_MONEY
+    }
+
+    pub fn checked_add(&self, other: EbxValue) -> Option<Self> {
+        let value = Self::new(self.value.checked_add(other.value)?);
+        value.is_valid().then_some(value)
+    }
+
+    pub fn checked_sub(&self, other: EbxValue) -> Option<Self> {
+        Some(Self::new(self.value.checked_sub(other.value)?))
+    }
+
+    // none if any value, or the total, is more than the max money
+    pub fn checked_sum<I: IntoIterator<Item = u64>>(values: I) -> Option<Self> {
+        values.into_iter().try_fold(Self::zero(), |sum, value| {
+            let value = Self::new(value);
+            if !value.is_valid() {
+                return None;
+            }
+            sum.checked_add(value)
+        })
+    }
+
+    pub fn to_ebx_raw(&self) -> f64 {
+        self.value as f64 / Self::ADAMS_PER_EBX as f64
+    }
+
+    pub fn to_ebx(&self, decimals: u32) -> f64 {
+        let factor = 10f64.powi(decimals as i32);
+        (self.to_ebx_raw() * factor).round() / factor
+    }
+
+    // the exact value in EBX, without trailing zeros, e.g. "1.5"
+    pub fn to_ebx_str(&self) -> String {
+        let whole = self.value / Self::ADAMS_PER_EBX;
+        let fraction = self.value % Self::ADAMS_PER_EBX;
+        if fraction == 0 {
+            return whole.to_string();
+        }
+        let fraction_str = format!("{:011}", fraction);
+        format!("{}.{}", whole, fraction_str.trim_end_matches('0'))
+    }
+
+    pub fn from_ebx_str(s: &str) -> Result<Self, EbxError> {
+        let invalid = || EbxError::GenericError {
+            source: None,
+            message: format!("invalid ebx value: {}", s),
+        };
+        let (whole_str, fraction_str) = s.split_once('.').unwrap_or((s, ""));
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if whole_str.is_empty() || !is_digits(whole_str) || !is_digits(fraction_str) {
+            return Err(invalid());
+        }
+        if s.contains('.') && fraction_str.is_empty() {
+            return Err(invalid());
+        }
+        if fraction_str.len() > Self::DECIMALS as usize {
+            return Err(invalid());
+        }
+        let whole: u64 = whole_str.parse().map_err(|_| invalid())?;
+        let fraction: u64 = format!("{:0<11}", fraction_str)
+            .parse()
+            .map_err(|_| invalid())?;
+        let value = whole
+            .checked_mul(Self::ADAMS_PER_EBX)
+            .and_then(|value| value.checked_add(fraction))
+            .map(Self::new)
+            .ok_or_else(invalid)?;
+        if !value.is_valid() {
+            return Err(invalid());
+        }
+        Ok(value)
+    }
+}
+
+impl From<u64> for EbxValue {
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl fmt::Display for EbxValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} EBX", self.to_ebx_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_params::ChainParams;
+
+    #[test]
+    fn test_to_ebx() {
+        assert_eq!(EbxValue::new(100_000_000_000).to_ebx(2), 1.0);
+        let value = EbxValue::new(109_999_000_000);
+        assert_eq!(value.to_ebx(2), 1.1);
+        assert_eq!(value.to_ebx(4), 1.1);
+        assert_eq!(value.to_ebx(5), 1.09999);
+        assert_eq!(value.to_ebx(6), 1.09999);
+    }
+
+    #[test]
+    fn test_to_ebx_str() {
+        assert_eq!(EbxValue::zero().to_ebx_str(), "0");
+        assert_eq!(EbxValue::new(1).to_ebx_str(), "0.00000000001");
+        assert_eq!(EbxValue::new(150_000_000_000).to_ebx_str(), "1.5");
+        assert_eq!(EbxValue::new(10_000_000_000_000).to_ebx_str(), "100");
+        assert_eq!(EbxValue::new(109_999_000_000).to_string(), "1.09999 EBX");
+    }
+
+    #[test]
+    fn test_from_ebx_str() {
+        for value in [
+            0,
+            1,
+            150_000_000_000,
+            10_000_000_000_000,
+            EbxValue::MAX_MONEY,
+        ] {
+            let value = EbxValue::new(value);
+            assert_eq!(EbxValue::from_ebx_str(&value.to_ebx_str()).unwrap(), value);
+        }
+        assert_eq!(
+            EbxValue::from_ebx_str("1.50").unwrap(),
+            EbxValue::new(150_000_000_000)
+        );
+        for s in [
+            "",
+            ".5",
+            "1.",
+            "-1",
+            "+1",
+            "1.5.5",
+            "1 ",
+            "0.000000000001",
+            "42000000.00000000001",
+            "184467440737",
+            "99999999999999999999999",
+        ] {
+            assert!(EbxValue::from_ebx_str(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let max = EbxValue::new(EbxValue::MAX_MONEY);
+        assert_eq!(max.checked_add(EbxValue::zero()), Some(max));
+        assert_eq!(max.checked_add(EbxValue::new(1)), None);
+        assert_eq!(EbxValue::zero().checked_sub(EbxValue::new(1)), None);
+        assert_eq!(
+            EbxValue::new(3).checked_sub(EbxValue::new(1)),
+            Some(EbxValue::new(2))
+        );
+
+        assert_eq!(EbxValue::checked_sum([1, 2, 3]), Some(EbxValue::new(6)));
+        assert_eq!(EbxValue::checked_sum([]), Some(EbxValue::zero()));
+        assert_eq!(EbxValue::checked_sum([EbxValue::MAX_MONEY, 1]), None);
+        assert_eq!(EbxValue::checked_sum([u64::MAX, 1]), None);
+        // would wrap around to 1 with unchecked u64 addition
+        assert_eq!(EbxValue::checked_sum([u64::MAX, 2]), None);
+    }
+
+    #[test]
+    fn test_max_money_covers_all_coinbases() {
+        let params = ChainParams::mainnet();
+        let mut sum = EbxValue::zero();
+        let mut block_num: u32 = 0;
+        while params.coinbase_amount(block_num) > 0 {
+            let amount = params.coinbase_amount(block_num);
+            let period = EbxValue::new(amount * params.halving_interval as u64);
+            sum = sum.checked_add(period).unwrap();
+            block_num += params.halving_interval;
+        }
+        assert!(sum.is_valid());
+    }
+}
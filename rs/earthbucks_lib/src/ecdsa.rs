@@ -1,8 +1,10 @@
 use crate::hash::blake3_mac;
-use earthbucks_secp256k1::secp256k1;
 use crate::priv_key::PrivKey;
-use crate::pub_key::PubKey;
+use earthbucks_secp256k1::secp256k1;
 
+// ecdsa on secp256k1 with a blake3 mac of the data, keyed by the private key,
+// as the deterministic k value. the same as ecdsab3Sign and ecdsab3Verify in
+// the typescript library.
 pub fn sign(data: &[u8], priv_key: PrivKey) -> [u8; 64] {
     let key = priv_key.buf;
     let k = blake3_mac(&key, data);
@@ -13,6 +15,33 @@ pub fn sign(data: &[u8], priv_key: PrivKey) -> [u8; 64] {
 }
 
 pub fn verify(data: &[u8], sig: &[u8; 64], pubkey: &[u8; 33]) -> bool {
-    let res = secp256k1::verify(data, sig, pubkey);
+    let res = secp256k1::verify(sig, data, pubkey);
     res.is_ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::blake3_hash;
+    use crate::pub_key::PubKey;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let priv_key = PrivKey::from_random();
+        let pub_key = PubKey::from_priv_key(&priv_key).unwrap();
+        let data = blake3_hash(b"data");
+        let sig = sign(&data, priv_key.clone());
+        assert!(verify(&data, &sig, &pub_key.buf));
+
+        // the same data and key always give the same signature
+        assert_eq!(sign(&data, priv_key), sig);
+
+        let other_data = blake3_hash(b"other data");
+        assert!(!verify(&other_data, &sig, &pub_key.buf));
+        let other_pub_key = PubKey::from_priv_key(&PrivKey::from_random()).unwrap();
+        assert!(!verify(&data, &sig, &other_pub_key.buf));
+        let mut bad_sig = sig;
+        bad_sig[0] ^= 1;
+        assert!(!verify(&data, &bad_sig, &pub_key.buf));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::chain_params::ChainParams;
+use crate::ebx_value::EbxValue;
+use crate::error::EbxError;
+use crate::tx_out_bn_map::TxOutBnMap;
+
+// the only way new coins are made is the coinbase tx of each block, whose
+// amount is fixed by the chain params. this computes the subsidy and the
+// total supply at any block number from the chain params alone, so supply
+// figures never depend on trusting the contents of a database.
+pub struct Emission<'a> {
+    pub params: &'a ChainParams,
+}
+
+impl<'a> Emission<'a> {
+    pub fn new(params: &'a ChainParams) -> Self {
+        Self { params }
+    }
+
+    pub fn subsidy(&self, block_num: u32) -> EbxValue {
+        EbxValue::new(self.params.coinbase_amount(block_num))
+    }
+
+    // the total of all coinbase amounts from the genesis block up to and
+    // including the given block number
+    pub fn issued_supply(&self, block_num: u32) -> EbxValue {
+        let halving_interval = self.params.halving_interval as u64;
+        let n_blocks = block_num as u64 + 1;
+        let mut supply: u128 = 0;
+        let mut period_start: u64 = 0;
+        while period_start < n_blocks {
+            let subsidy = self.subsidy(period_start as u32).value;
+            if subsidy == 0 {
+                break;
+            }
+            let period_blocks = halving_interval.min(n_blocks - period_start);
+            supply += subsidy as u128 * period_blocks as u128;
+            period_start += halving_interval;
+        }
+        EbxValue::new(u64::try_from(supply).unwrap_or(u64::MAX))
+    }
+
+    // the supply once the subsidy has halved to zero
+    pub fn final_supply(&self) -> EbxValue {
+        self.issued_supply(u32::MAX)
+    }
+
+    // the first block number with no subsidy
+    pub fn final_block_num(&self) -> u32 {
+        let halving_interval = self.params.halving_interval;
+        let mut block_num: u32 = 0;
+        while self.params.coinbase_amount(block_num) > 0 {
+            block_num = match block_num.checked_add(halving_interval) {
+                Some(block_num) => block_num,
+                None => return u32::MAX,
+            };
+        }
+        block_num
+    }
+
+    // sums the unspent outputs of a utxo set that is up to date as of the given
+    // block number, and checks that they are no more than was issued. outputs
+    // are destroyed by spending them to nothing, so the unspent total may be
+    // less, but never more. returns the unspent total.
+    pub fn audit(&self, tx_out_bn_map: &TxOutBnMap, block_num: u32) -> Result<EbxValue, EbxError> {
+        let issued_supply = self.issued_supply(block_num);
+        let mut unspent: u128 = 0;
+        for tx_out_bn in tx_out_bn_map.values() {
+            if tx_out_bn.block_num > block_num {
+                return Err(EbxError::GenericError {
+                    source: None,
+                    message: format!(
+                        "output from block {} is after block {}",
+                        tx_out_bn.block_num, block_num
+                    ),
+                });
+            }
+            unspent += tx_out_bn.tx_out.value as u128;
+        }
+        if unspent > issued_supply.value as u128 {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: format!(
+                    "unspent outputs of {} adams exceed issued supply of {} adams",
+                    unspent, issued_supply.value
+                ),
+            });
+        }
+        Ok(EbxValue::new(unspent as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::Script;
+    use crate::tx::Tx;
+    use crate::tx_out::TxOut;
+
+    #[test]
+    fn test_issued_supply_matches_subsidies() {
+        let params = ChainParams::regtest();
+        let emission = Emission::new(&params);
+        let mut sum = EbxValue::zero();
+        for block_num in 0..150 * 70 {
+            sum = sum.checked_add(emission.subsidy(block_num)).unwrap();
+            assert_eq!(emission.issued_supply(block_num), sum);
+        }
+        assert_eq!(emission.final_supply(), sum);
+    }
+
+    #[test]
+    fn test_mainnet_supply() {
+        let params = ChainParams::mainnet();
+        let emission = Emission::new(&params);
+        assert_eq!(emission.subsidy(0), EbxValue::new(10_000_000_000_000));
+        assert_eq!(emission.issued_supply(0), EbxValue::new(10_000_000_000_000));
+        assert_eq!(
+            emission.issued_supply(209_999),
+            EbxValue::new(210_000 * 10_000_000_000_000)
+        );
+        assert_eq!(
+            emission.issued_supply(1_999_999),
+            EbxValue::new(4_193_945_312_500_000_000)
+        );
+        assert_eq!(
+            emission.final_supply(),
+            EbxValue::new(4_199_999_999_997_060_000)
+        );
+        assert!(emission.final_supply().is_valid());
+        assert_eq!(emission.final_block_num(), 44 * 210_000);
+        assert_eq!(
+            emission.issued_supply(emission.final_block_num()),
+            emission.final_supply()
+        );
+    }
+
+    #[test]
+    fn test_audit() {
+        let params = ChainParams::regtest();
+        let emission = Emission::new(&params);
+        let mut tx_out_bn_map = TxOutBnMap::new();
+        for block_num in 0..3 {
+            let coinbase_tx = Tx::from_coinbase(
+                Script::from_empty(),
+                Script::from_pkh_output(&[0; 32]),
+                params.coinbase_amount(block_num),
+                block_num,
+            );
+            tx_out_bn_map.add_tx_outputs(&coinbase_tx, block_num);
+        }
+        assert_eq!(
+            emission.audit(&tx_out_bn_map, 2).unwrap(),
+            emission.issued_supply(2)
+        );
+
+        // outputs from later blocks are not covered by the issued supply
+        assert!(emission.audit(&tx_out_bn_map, 1).is_err());
+
+        // coins that were never issued
+        let tx_out = TxOut::new(1, Script::from_empty());
+        tx_out_bn_map.add(&[1; 32], 0, tx_out, 2);
+        let err = emission.audit(&tx_out_bn_map, 2).unwrap_err();
+        assert!(err.to_string().contains("exceed issued supply"));
+    }
+}
@@ -1,11 +1,13 @@
 use crate::buf::EbxBuf;
 use crate::buf_reader::BufReader;
 use crate::buf_writer::BufWriter;
+use crate::chain_params::ChainParams;
+use crate::clock::Clock;
 use crate::error::EbxError;
 use crate::hash::{blake3_hash, double_blake3_hash};
 use crate::numbers::u256;
+use crate::work_ser_algo::WorkSerAlgo;
 use num_bigint::BigUint;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone)]
 pub struct Header {
@@ -23,13 +25,13 @@ pub struct Header {
 }
 
 impl Header {
-    // exactly two weeks if block interval is 10 minutes
-    pub const BLOCKS_PER_TARGET_ADJ_PERIOD: u32 = 2016;
-
-    // 600_000 milliseconds = 600 seconds = 10 minutes
-    pub const BLOCK_INTERVAL: u64 = 600_000;
+    // fixed-point scale and ln(10) at that scale, for the difficulty adjustment
+    const FIXED_POINT_SCALE: u128 = 1_000_000_000_000_000_000;
+    const FIXED_POINT_LN_10: u128 = 2_302_585_092_994_045_684;
 
     pub const SIZE: usize = 1 + 32 + 32 + 8 + 4 + 32 + 32 + 2 + 32 + 2 + 32;
+    pub const WIRE_SIZE: usize = Header::SIZE + 8;
+    const WIRE_N_TRANSACTIONS_START: usize = 1 + 32 + 32;
     pub const MAX_TARGET_BYTES: [u8; 32] = [0xff; 32];
 
     pub fn to_buf(&self) -> [u8; Header::SIZE] {
@@ -43,7 +45,7 @@ impl Header {
 
     pub fn from_buf_reader(br: &mut BufReader) -> Result<Header, EbxError> {
         if br.remainder_len() < Header::SIZE {
-            panic!("Invalid block header size");
+            return Err(EbxError::NotEnoughDataError { source: None });
         }
         let version = br.read_u8()?;
         let prev_block_id: [u8; 32] = br.read(32)?.try_into().unwrap();
@@ -99,6 +101,29 @@ impl Header {
         Header::from_buf(buf)
     }
 
+    // the typescript header also has the number of transactions in the block,
+    // between the merkle root and the timestamp. work packs and work data use
+    // this layout, and it is what pow5 hashes.
+    pub fn to_wire_buf(&self, n_transactions: u64) -> [u8; Header::WIRE_SIZE] {
+        let buf = self.to_buf();
+        let mut bw = BufWriter::new();
+        bw.write(buf[..Header::WIRE_N_TRANSACTIONS_START].to_vec());
+        bw.write_u64_be(n_transactions);
+        bw.write(buf[Header::WIRE_N_TRANSACTIONS_START..].to_vec());
+        bw.to_buf().try_into().unwrap()
+    }
+
+    pub fn from_wire_buf_reader(br: &mut BufReader) -> Result<(Header, u64), EbxError> {
+        if br.remainder_len() < Header::WIRE_SIZE {
+            return Err(EbxError::NotEnoughDataError { source: None });
+        }
+        let mut buf = br.read(Header::WIRE_N_TRANSACTIONS_START)?;
+        let n_transactions = br.read_u64_be()?;
+        buf.extend(br.read(Header::SIZE - Header::WIRE_N_TRANSACTIONS_START)?);
+        let header = Header::from_buf(buf.try_into().unwrap())?;
+        Ok((header, n_transactions))
+    }
+
     pub fn to_strict_str(&self) -> String {
         self.to_strict_hex()
     }
@@ -107,8 +132,10 @@ impl Header {
         Header::from_strict_hex(hex)
     }
 
-    pub fn is_target_valid(&self, lch: &[Header]) -> bool {
-        let new_target_res = Header::new_target_from_lch(lch, self.timestamp);
+    // the target must be exactly the one given by the difficulty adjustment
+    // from the previous two headers
+    pub fn is_target_valid(&self, lch: &[Header], params: &ChainParams) -> bool {
+        let new_target_res = Header::new_target_from_lch(lch, self.timestamp, params);
         if new_target_res.is_err() {
             return false;
         }
@@ -126,16 +153,48 @@ impl Header {
         self.version == 0
     }
 
-    pub fn is_timestamp_valid_at(&self, timestamp: u64) -> bool {
-        self.timestamp <= timestamp
+    // a header may be ahead of our clock by at most the allowed drift
+    pub fn is_timestamp_valid_at(&self, timestamp: u64, params: &ChainParams) -> bool {
+        self.timestamp <= timestamp.saturating_add(params.max_future_drift)
+    }
+
+    // the median timestamp of the most recent headers. unlike the timestamp of
+    // the tip, this cannot be moved far by a single miner, so it is the time
+    // that time-based locks are checked against.
+    pub fn median_time_past(lch: &[Header], params: &ChainParams) -> Option<u64> {
+        let start = lch.len().saturating_sub(params.median_time_span);
+        let mut timestamps: Vec<u64> = lch[start..].iter().map(|h| h.timestamp).collect();
+        if timestamps.is_empty() {
+            return None;
+        }
+        timestamps.sort_unstable();
+        Some(timestamps[timestamps.len() / 2])
     }
 
-    pub fn is_valid_in_lch(&self, lch: &[Header]) -> bool {
+    // only null and blake3_3 are allowed in blocks
+    pub fn is_work_ser_algo_valid(&self) -> bool {
+        matches!(
+            WorkSerAlgo::from_u16(self.work_ser_algo),
+            Ok(WorkSerAlgo::Null) | Ok(WorkSerAlgo::Blake3_3)
+        )
+    }
+
+    pub fn is_work_ser_hash_valid(&self) -> bool {
+        match WorkSerAlgo::from_u16(self.work_ser_algo) {
+            Ok(algo) => self.work_ser_hash == algo.work(&self.to_working_header().id()),
+            Err(_) => false,
+        }
+    }
+
+    pub fn is_valid_in_lch(&self, lch: &[Header], params: &ChainParams) -> bool {
         if !self.is_version_valid() {
             return false;
         }
         if self.block_num == 0 {
-            return self.is_genesis();
+            return self.is_genesis()
+                && params
+                    .genesis_id()
+                    .is_ok_and(|genesis_id| genesis_id == self.id());
         }
         if lch.is_empty() {
             return false;
@@ -146,10 +205,19 @@ impl Header {
         if self.prev_block_id != lch.last().unwrap().id() {
             return false;
         }
+        // timestamps strictly increase
         if self.timestamp <= lch.last().unwrap().timestamp {
             return false;
         }
-        if !self.is_target_valid(lch) {
+        // and are after the median time past, which time locks are checked
+        // against
+        if self.timestamp <= Header::median_time_past(lch, params).unwrap() {
+            return false;
+        }
+        if !self.is_target_valid(lch, params) {
+            return false;
+        }
+        if !self.is_work_ser_algo_valid() || !self.is_work_ser_hash_valid() {
             return false;
         }
         if !self.is_id_valid() {
@@ -158,12 +226,12 @@ impl Header {
         true
     }
 
-    pub fn is_valid_at(&self, lch: &[Header], timestamp: u64) -> bool {
-        self.is_timestamp_valid_at(timestamp) && self.is_valid_in_lch(lch)
+    pub fn is_valid_at(&self, lch: &[Header], timestamp: u64, params: &ChainParams) -> bool {
+        self.is_timestamp_valid_at(timestamp, params) && self.is_valid_in_lch(lch, params)
     }
 
-    pub fn is_valid_now(&self, lch: &[Header]) -> bool {
-        self.is_valid_at(lch, Header::get_new_timestamp())
+    pub fn is_valid_now(&self, lch: &[Header], params: &ChainParams, clock: &dyn Clock) -> bool {
+        self.is_valid_at(lch, clock.now(), params)
     }
 
     pub fn is_genesis(&self) -> bool {
@@ -190,6 +258,16 @@ impl Header {
         }
     }
 
+    // the header with both work hashes zeroed, whose id is the input to the
+    // work algorithms
+    pub fn to_working_header(&self) -> Header {
+        Header {
+            work_ser_hash: [0; 32],
+            work_par_hash: [0; 32],
+            ..self.clone()
+        }
+    }
+
     pub fn hash(&self) -> [u8; 32] {
         blake3_hash(&self.to_buf())
     }
@@ -198,18 +276,15 @@ impl Header {
         double_blake3_hash(&self.to_buf())
     }
 
-    pub fn get_new_timestamp() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_millis() as u64
-    }
-
-    pub fn from_lch(lch: &[Header], new_timestamp: u64) -> Result<Self, EbxError> {
+    pub fn from_lch(
+        lch: &[Header],
+        new_timestamp: u64,
+        params: &ChainParams,
+    ) -> Result<Self, EbxError> {
         if lch.is_empty() {
             return Ok(Header::from_genesis(new_timestamp));
         }
-        let new_target = Header::new_target_from_lch(lch, new_timestamp)?;
+        let new_target = Header::new_target_from_lch(lch, new_timestamp, params)?;
         let prev_block = lch.last().unwrap();
         let prev_block_id = prev_block.id();
         let block_num = lch.len() as u32;
@@ -234,63 +309,131 @@ impl Header {
         })
     }
 
-    pub fn new_target_from_lch(lch: &[Header], new_timestamp: u64) -> Result<u256, EbxError> {
-        let adjh: Vec<Header> = if lch.len() > Header::BLOCKS_PER_TARGET_ADJ_PERIOD as usize {
-            lch[lch.len() - Header::BLOCKS_PER_TARGET_ADJ_PERIOD as usize..].to_vec()
-        } else {
-            lch.to_vec()
+    pub fn new_target_from_lch(
+        lch: &[Header],
+        new_timestamp: u64,
+        params: &ChainParams,
+    ) -> Result<u256, EbxError> {
+        let prev_header = match lch.last() {
+            Some(prev_header) if params.adjust_difficulty => prev_header,
+            _ => {
+                return Ok(BufReader::new(Header::MAX_TARGET_BYTES.to_vec())
+                    .read_u256_be()
+                    .unwrap())
+            }
         };
-        let len: u32 = adjh.len() as u32;
-        if len == 0 {
-            return Ok(BufReader::new(Header::MAX_TARGET_BYTES.to_vec())
-                .read_u256_be()
-                .unwrap());
+        let prev_prev_header = lch.len().checked_sub(2).map(|i| &lch[i]);
+        Header::new_target_from_prev_headers(prev_header, prev_prev_header, new_timestamp, params)
+    }
+
+    pub fn new_target_from_prev_headers(
+        prev_header: &Header,
+        prev_prev_header: Option<&Header>,
+        new_timestamp: u64,
+        params: &ChainParams,
+    ) -> Result<u256, EbxError> {
+        let new_difficulty = Header::new_difficulty_from_prev_headers(
+            prev_header,
+            prev_prev_header,
+            new_timestamp,
+            params,
+        )?;
+        Ok(Header::target_from_difficulty(new_difficulty))
+    }
+
+    // the difficulty adjusts every block, based on the previous two headers.
+    pub fn new_difficulty_from_prev_headers(
+        prev_header: &Header,
+        prev_prev_header: Option<&Header>,
+        new_timestamp: u64,
+        params: &ChainParams,
+    ) -> Result<u256, EbxError> {
+        if prev_header.target == u256::ZERO {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "target must be greater than zero".to_string(),
+            });
         }
-        let first_header = adjh[0].clone();
-        let mut targets: Vec<BigUint> = Vec::new();
-        for header in adjh {
-            let target =
-                BigUint::from_bytes_be(&BufWriter::new().write_u256_be(header.target).to_buf());
-            targets.push(target);
+        let prev_difficulty = prev_header.difficulty();
+        let prev_prev_header = match prev_prev_header {
+            Some(prev_prev_header) => prev_prev_header,
+            None => return Ok(prev_difficulty),
+        };
+        if prev_header.timestamp <= prev_prev_header.timestamp
+            || new_timestamp < prev_header.timestamp
+        {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "timestamps must be increasing".to_string(),
+            });
         }
-        let target_sum: BigUint = targets.iter().sum();
-        if new_timestamp <= first_header.timestamp {
+        let prev_time_diff = prev_header.timestamp - prev_prev_header.timestamp;
+        let time_diff = new_timestamp - prev_header.timestamp;
+        Header::new_difficulty_from_time_diffs(prev_difficulty, prev_time_diff, time_diff, params)
+    }
+
+    // first, the difficulty is scaled by how far the time between the previous
+    // two blocks was from the ideal block interval. second, it is multiplied
+    // by an exponential decay factor exp(-ln(10) * (time_diff / interval - 1)),
+    // so that mining a block early is up to 10x harder and mining it late
+    // becomes exponentially easier. the factor equals 10^(1 - time_diff /
+    // interval) and is computed in fixed point so that every implementation
+    // gets exactly the same result.
+    pub fn new_difficulty_from_time_diffs(
+        prev_difficulty: u256,
+        prev_time_diff: u64,
+        time_diff: u64,
+        params: &ChainParams,
+    ) -> Result<u256, EbxError> {
+        if prev_time_diff == 0 {
             return Err(EbxError::GenericError {
                 source: None,
                 message: "timestamps must be increasing".to_string(),
             });
         }
-        let real_time_diff: u64 = new_timestamp - first_header.timestamp;
-        let new_target: u256 = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        Ok(new_target)
-    }
-
-    pub fn new_target_from_old_targets(target_sum: BigUint, real_time_diff: u64, len: u32) -> u256 {
-        // - target_sum is sum of all targets in the adjustment period
-        // - real_time_diff is the time difference between the first block in
-        //   the adjustment period and now (the new block)
-        // new target = average target * real time diff / intended time diff
-        // let new_target = (target_sum / len) * real_time_diff / intended_time_diff;
-        // let new_target = (target_sum * real_time_diff) / intended_time_diff / len;
-        // let new_target = (target_sum * real_time_diff) / len / intended_time_diff;
-        // let new_target = (target_sum * real_time_diff) / (len * intended_time_diff);
-        // the fewest divisions is the most accurate in integer arithmetic...
-        let intended_time_diff = len as u64 * Header::BLOCK_INTERVAL;
-        let res: BigUint = (target_sum * real_time_diff) / (len as u64 * intended_time_diff);
-        u256::from_be_slice(&res.to_bytes_be()).unwrap()
-        //u256::from_be_bytes(&res.to_bytes_be())
-    }
-
-    pub fn coinbase_amount(block_num: u32) -> u64 {
-        // shift every 210,000 blocks ("halving")
-        let shift_by = block_num / 210_000;
-        // BTC: 100_000_000 satoshis = 1 bitcoin
-        // 100 bitcoins per block for the first 210,000 blocks
-        // 100 million satoshis per block for the first 210,000 blocks
-        // EBX: 100_000_000_000 adams = 1 earthbuck
-        // 100 earthbucks per block for the first 210,000 blocks
-        // 100 billion adams per block for the first 210,000 blocks
-        (100 * 100_000_000_000) >> shift_by
+        let min_difficulty = u256::from(params.min_difficulty);
+
+        // 10^(-time_diff / interval) = 10^(-whole_intervals) * e^(-y)
+        let whole_intervals = time_diff / params.block_interval;
+        if whole_intervals >= 100 {
+            return Ok(min_difficulty);
+        }
+        let partial_interval = (time_diff % params.block_interval) as u128;
+        let y = Header::FIXED_POINT_LN_10 * partial_interval / params.block_interval as u128;
+        let exp_y = Header::fixed_point_exp(y);
+
+        let numerator =
+            BigUint::from_bytes_be(&BufWriter::new().write_u256_be(prev_difficulty).to_buf())
+                * params.block_interval
+                * 10u32
+                * Header::FIXED_POINT_SCALE;
+        let denominator = BigUint::from(prev_time_diff)
+            * BigUint::from(10u32).pow(whole_intervals as u32)
+            * exp_y;
+        let new_difficulty = numerator / denominator;
+        let new_difficulty =
+            u256::from_be_slice(&new_difficulty.to_bytes_be()).ok_or_else(|| {
+                EbxError::GenericError {
+                    source: None,
+                    message: "difficulty out of range".to_string(),
+                }
+            })?;
+        Ok(new_difficulty.max(min_difficulty))
+    }
+
+    // e^x for 0 <= x < ln(10), both in fixed point, by its taylor series
+    fn fixed_point_exp(x: u128) -> u128 {
+        let mut sum = Header::FIXED_POINT_SCALE;
+        let mut term = Header::FIXED_POINT_SCALE;
+        let mut n = 1;
+        loop {
+            term = term * x / (n * Header::FIXED_POINT_SCALE);
+            if term == 0 {
+                return sum;
+            }
+            sum += term;
+            n += 1;
+        }
     }
 
     pub fn difficulty_from_target(target: u256) -> u256 {
@@ -306,11 +449,17 @@ impl Header {
             .unwrap();
         max_target / difficulty
     }
+
+    pub fn difficulty(&self) -> u256 {
+        Header::difficulty_from_target(self.target)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde::Deserialize;
+    use std::fs;
 
     #[test]
     fn test_to_buf_and_from_buf() {
@@ -364,6 +513,27 @@ mod tests {
         assert_eq!(bh1.block_num, bh2.block_num);
     }
 
+    #[test]
+    fn test_to_from_wire_buf() {
+        let mut header = Header::from_genesis(1_700_000_000_000);
+        header.nonce = u256::from(0x01020304u32) << 224;
+        let buf = header.to_wire_buf(5);
+        assert_eq!(buf.len(), Header::WIRE_SIZE);
+        assert_eq!(&buf[65..73], &5u64.to_be_bytes());
+        assert_eq!(&buf[73..81], &header.timestamp.to_be_bytes());
+        // the nonce starts where pow5 inserts it
+        assert_eq!(&buf[117..121], &[1, 2, 3, 4]);
+        assert_eq!(&buf[Header::WIRE_SIZE - 32..], &header.work_par_hash);
+
+        let mut br = BufReader::new(buf.to_vec());
+        let (header2, n_transactions) = Header::from_wire_buf_reader(&mut br).unwrap();
+        assert!(br.eof());
+        assert_eq!(header2.to_buf(), header.to_buf());
+        assert_eq!(n_transactions, 5);
+        let mut br = BufReader::new(buf[..Header::WIRE_SIZE - 1].to_vec());
+        assert!(Header::from_wire_buf_reader(&mut br).is_err());
+    }
+
     #[test]
     fn test_is_version_valid() {
         let bh1 = Header {
@@ -446,25 +616,6 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_coinbase_amount() {
-        assert_eq!(Header::coinbase_amount(0), 10_000_000_000_000);
-        assert_eq!(Header::coinbase_amount(210_000), 5_000_000_000_000);
-        assert_eq!(Header::coinbase_amount(420_000), 2_500_000_000_000);
-        assert_eq!(Header::coinbase_amount(630_000), 1_250_000_000_000);
-        assert_eq!(Header::coinbase_amount(840_000), 625_000_000_000);
-        assert_eq!(Header::coinbase_amount(1_050_000), 312_500_000_000);
-        assert_eq!(Header::coinbase_amount(1_260_000), 156_250_000_000);
-
-        let mut sum = 0;
-        for i in 0..2_000_000 {
-            sum += Header::coinbase_amount(i);
-        }
-        // max u64: 18_446_744_073_709_551_616 - 1
-        // max val:  4_193_945_312_500_000_000
-        assert_eq!(sum, 4_193_945_312_500_000_000);
-    }
-
     #[test]
     fn test_difficulty_from_target_1() {
         let target_1_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
@@ -575,201 +726,214 @@ mod tests {
         assert_eq!(target_5_hex, expected_hex);
     }
 
-    #[test]
-    fn test_new_target_from_old_targets_1() {
-        let target_1_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-
-        let target_sum = target_1;
-        let real_time_diff: u64 = 600_000;
-        let len: u32 = 1;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex = hex::encode(BufWriter::new().write_u256_be(new_target).to_buf());
-        let expected_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        assert_eq!(new_target_hex, expected_hex);
+    fn header_at(timestamp: u64, difficulty: u64) -> Header {
+        let mut header = Header::from_genesis(timestamp);
+        header.target = Header::target_from_difficulty(u256::from(difficulty));
+        header
     }
 
-    #[test]
-    fn test_new_target_from_old_targets_1_2() {
-        let target_1_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-
-        let target_sum = target_1;
-        let real_time_diff: u64 = 300_000;
-        let len: u32 = 1;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex = hex::encode(BufWriter::new().write_u256_be(new_target).to_buf());
-        let expected_hex = "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        assert_eq!(new_target_hex, expected_hex);
+    #[derive(Deserialize)]
+    struct DifficultyData {
+        new_difficulty: Vec<DifficultyVector>,
     }
 
-    #[test]
-    fn test_new_target_from_old_targets_1_3() {
-        let target_1_hex = "8000000000000000000000000000000000000000000000000000000000000000";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-
-        let target_sum = target_1;
-        let real_time_diff: u64 = 600_000;
-        let len: u32 = 1;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex = hex::encode(BufWriter::new().write_u256_be(new_target).to_buf());
-        let expected_hex = "8000000000000000000000000000000000000000000000000000000000000000";
-        assert_eq!(new_target_hex, expected_hex);
+    #[derive(Deserialize)]
+    struct DifficultyVector {
+        prev_difficulty: String,
+        prev_time_diff: u64,
+        time_diff: u64,
+        new_difficulty: String,
     }
 
     #[test]
-    fn test_new_target_from_old_targets_1_4() {
-        let target_1_hex = "8000000000000000000000000000000000000000000000000000000000000000";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-
-        let target_sum = target_1;
-        let real_time_diff: u64 = 300_000;
-        let len: u32 = 1;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex = hex::encode(BufWriter::new().write_u256_be(new_target).to_buf());
-        let expected_hex = "4000000000000000000000000000000000000000000000000000000000000000";
-        assert_eq!(new_target_hex, expected_hex);
+    fn test_new_difficulty_from_prev_headers_vectors() {
+        let data = fs::read_to_string("./test_vectors/header_difficulty.json")
+            .expect("Unable to read file");
+        let difficulty_data: DifficultyData =
+            serde_json::from_str(&data).expect("Unable to parse JSON");
+
+        let params = ChainParams::mainnet();
+        for vector in difficulty_data.new_difficulty {
+            let prev_difficulty: u64 = vector.prev_difficulty.parse().unwrap();
+            let prev_prev_header = header_at(1_000_000, prev_difficulty);
+            let prev_header = header_at(1_000_000 + vector.prev_time_diff, prev_difficulty);
+            let new_timestamp = prev_header.timestamp + vector.time_diff;
+            let new_difficulty = Header::new_difficulty_from_prev_headers(
+                &prev_header,
+                Some(&prev_prev_header),
+                new_timestamp,
+                &params,
+            )
+            .unwrap();
+            assert_eq!(new_difficulty.to_string(), vector.new_difficulty);
+        }
     }
 
     #[test]
-    fn test_new_target_from_old_targets_1_5() {
-        let target_1_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-
-        let target_sum = target_1;
-        let real_time_diff: u64 = 1_200_000;
-        let len: u32 = 1;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex = hex::encode(BufWriter::new().write_u256_be(new_target).to_buf());
-        let expected_hex = "0100000000000000000000000000000000000000000000000000000000000000";
-        assert_eq!(new_target_hex, expected_hex);
+    fn test_new_difficulty_from_prev_headers() {
+        let params = ChainParams::mainnet();
+        let prev_prev_header = header_at(0, 10_000);
+        let prev_header = header_at(600_000, 10_000);
+
+        // on time: no change
+        let difficulty = Header::new_difficulty_from_prev_headers(
+            &prev_header,
+            Some(&prev_prev_header),
+            1_200_000,
+            &params,
+        )
+        .unwrap();
+        assert_eq!(difficulty, u256::from(10_000u32));
+
+        // immediately after the previous block: 10x harder
+        let difficulty = Header::new_difficulty_from_prev_headers(
+            &prev_header,
+            Some(&prev_prev_header),
+            600_000,
+            &params,
+        )
+        .unwrap();
+        assert_eq!(difficulty, u256::from(100_000u32));
+
+        // very late: floored at the minimum
+        let difficulty = Header::new_difficulty_from_prev_headers(
+            &prev_header,
+            Some(&prev_prev_header),
+            600_000 * 1_000,
+            &params,
+        )
+        .unwrap();
+        assert_eq!(difficulty, u256::from(params.min_difficulty));
+
+        // with only one previous header the difficulty is unchanged
+        let difficulty =
+            Header::new_difficulty_from_prev_headers(&prev_header, None, 5_000_000, &params)
+                .unwrap();
+        assert_eq!(difficulty, u256::from(10_000u32));
+
+        // timestamps must be increasing
+        assert!(Header::new_difficulty_from_prev_headers(
+            &prev_header,
+            Some(&prev_header),
+            1_200_000,
+            &params
+        )
+        .is_err());
+        assert!(Header::new_difficulty_from_prev_headers(
+            &prev_header,
+            Some(&prev_prev_header),
+            599_999,
+            &params
+        )
+        .is_err());
     }
 
     #[test]
-    fn test_new_target_from_old_targets_2() {
-        let target_1_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-        let target_2_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        let target_2: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_2_hex).unwrap());
-        let target_sum = target_1 + target_2;
-        let real_time_diff: u64 = 600_000 + 600_000;
-        let len: u32 = 2;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex = hex::encode(BufWriter::new().write_u256_be(new_target).to_buf());
-        let expected_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        assert_eq!(new_target_hex, expected_hex);
+    fn test_is_target_valid() {
+        let params = ChainParams::mainnet();
+        let prev_prev_header = header_at(0, 10_000);
+        let prev_header = header_at(600_000, 10_000);
+        let lch = vec![prev_prev_header, prev_header];
+
+        let mut header = header_at(900_000, 10_000);
+        header.target = Header::new_target_from_lch(&lch, header.timestamp, &params).unwrap();
+        assert!(header.is_target_valid(&lch, &params));
+        assert_eq!(header.difficulty(), u256::from(31_622u32));
+
+        header.target = Header::target_from_difficulty(u256::from(10_000u32));
+        assert!(!header.is_target_valid(&lch, &params));
     }
 
     #[test]
-    fn test_new_target_from_old_targets_2_2() {
-        let target_1_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-        let target_2_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_2: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_2_hex).unwrap());
-        let target_sum = target_1 + target_2;
-        let real_time_diff: u64 = 600_000 + 300_000;
-        let len: u32 = 2;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex: String = format!(
-            "{:0>64}",
-            hex::encode(BufWriter::new().write_u256_be(new_target).to_buf())
-        );
-        let expected_hex = "0060000000000000000000000000000000000000000000000000000000000000";
-        assert_eq!(new_target_hex, expected_hex);
+    fn test_is_timestamp_valid_at() {
+        let params = ChainParams::mainnet();
+        let header = header_at(1_000_000, 10_000);
+        assert!(header.is_timestamp_valid_at(1_000_000, &params));
+        assert!(header.is_timestamp_valid_at(1_000_000 - params.max_future_drift, &params));
+        assert!(!header.is_timestamp_valid_at(1_000_000 - params.max_future_drift - 1, &params));
+        assert!(header.is_timestamp_valid_at(u64::MAX, &params));
     }
 
     #[test]
-    fn test_new_target_from_old_targets_2_3() {
-        let target_1_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-        let target_2_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_2: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_2_hex).unwrap());
-        let target_sum = target_1 + target_2;
-        let real_time_diff: u64 = 600_000 + 1_200_000;
-        let len: u32 = 2;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex: String = format!(
-            "{:0>64}",
-            hex::encode(BufWriter::new().write_u256_be(new_target).to_buf())
-        );
-        let expected_hex = "00c0000000000000000000000000000000000000000000000000000000000000";
-        assert_eq!(new_target_hex, expected_hex);
+    fn test_median_time_past() {
+        let params = ChainParams::mainnet();
+        assert_eq!(Header::median_time_past(&[], &params), None);
+
+        let lch = vec![header_at(5, 10_000)];
+        assert_eq!(Header::median_time_past(&lch, &params), Some(5));
+
+        // only the most recent headers count, and order does not matter
+        let mut lch: Vec<Header> = (0..20).map(|i| header_at(i * 10, 10_000)).collect();
+        lch.swap(15, 18);
+        assert_eq!(Header::median_time_past(&lch, &params), Some(140));
     }
 
     #[test]
-    fn test_new_target_from_old_targets_3() {
-        let target_1_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-        let target_2_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        let target_2: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_2_hex).unwrap());
-        let target_3_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        let target_3: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_3_hex).unwrap());
-        let target_sum = target_1 + target_2 + target_3;
-        let real_time_diff: u64 = 600_000 + 600_000 + 600_000;
-        let len: u32 = 3;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex = hex::encode(BufWriter::new().write_u256_be(new_target).to_buf());
-        let expected_hex = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
-        assert_eq!(new_target_hex, expected_hex);
+    fn test_is_valid_in_lch_after_median_time_past() {
+        let params = ChainParams::regtest();
+        // the tip is older than the median time past
+        let lch: Vec<Header> = [0, 100, 200, 300, 50]
+            .iter()
+            .map(|&timestamp| header_at(timestamp, 1))
+            .collect();
+        assert_eq!(Header::median_time_past(&lch, &params), Some(100));
+        let mine = |timestamp: u64| {
+            let mut header = Header::from_lch(&lch, timestamp, &params).unwrap();
+            while !header.is_id_valid() {
+                header.nonce += u256::ONE;
+            }
+            header
+        };
+
+        // after the tip, but not after the median time past
+        assert!(!mine(60).is_valid_in_lch(&lch, &params));
+        assert!(!mine(100).is_valid_in_lch(&lch, &params));
+        assert!(mine(101).is_valid_in_lch(&lch, &params));
     }
 
     #[test]
-    fn test_new_target_from_old_targets_3_2() {
-        let target_1_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-        let target_2_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_2: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_2_hex).unwrap());
-        let target_3_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_3: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_3_hex).unwrap());
-        let target_sum = target_1 + target_2 + target_3;
-        let real_time_diff: u64 = 600_000 + 600_000 + 601_000;
-        let len: u32 = 3;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex: String = format!(
-            "{:0>64}",
-            hex::encode(BufWriter::new().write_u256_be(new_target).to_buf())
+    fn test_new_target_from_lch_regtest() {
+        let params = ChainParams::regtest();
+        let max_target = BufReader::new(Header::MAX_TARGET_BYTES.to_vec())
+            .read_u256_be()
+            .unwrap();
+        let lch = vec![header_at(0, 10_000), header_at(1, 10_000)];
+        let new_target = Header::new_target_from_lch(&lch, 2, &params).unwrap();
+        assert_eq!(new_target, max_target);
+        assert_ne!(
+            Header::new_target_from_lch(&lch, 2, &ChainParams::mainnet()).unwrap(),
+            max_target
         );
-        let expected_hex = "0080123456789abcdf0123456789abcdf0123456789abcdf0123456789abcdf0";
-        assert_eq!(new_target_hex, expected_hex);
     }
 
     #[test]
-    fn test_new_target_from_old_targets_3_3() {
-        let target_1_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_1: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_1_hex).unwrap());
-        let target_2_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_2: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_2_hex).unwrap());
-        let target_3_hex = "0080000000000000000000000000000000000000000000000000000000000000";
-        let target_3: BigUint =
-            BigUint::from_bytes_be(&Vec::<u8>::from_strict_hex(target_3_hex).unwrap());
-        let target_sum = target_1 + target_2 + target_3;
-        let real_time_diff: u64 = 600_000 + 600_000 + 599_000;
-        let len: u32 = 3;
-        let new_target = Header::new_target_from_old_targets(target_sum, real_time_diff, len);
-        let new_target_hex: String = format!(
-            "{:0>64}",
-            hex::encode(BufWriter::new().write_u256_be(new_target).to_buf())
-        );
-        let expected_hex = "007fedcba987654320fedcba987654320fedcba987654320fedcba987654320f";
-        assert_eq!(new_target_hex, expected_hex);
+    fn test_is_work_ser_hash_valid() {
+        let mut header = header_at(0, 10_000);
+        assert!(header.is_work_ser_algo_valid());
+        assert!(header.is_work_ser_hash_valid());
+
+        header.work_ser_hash = [1; 32];
+        assert!(!header.is_work_ser_hash_valid());
+
+        header.work_ser_algo = WorkSerAlgo::Blake3_3.to_u16();
+        header.work_par_hash = [2; 32];
+        let working_header_id = header.to_working_header().id();
+        header.work_ser_hash = WorkSerAlgo::Blake3_3.work(&working_header_id);
+        assert!(header.is_work_ser_algo_valid());
+        assert!(header.is_work_ser_hash_valid());
+
+        header.work_ser_hash = WorkSerAlgo::Blake3_2.work(&working_header_id);
+        assert!(!header.is_work_ser_hash_valid());
+
+        // the algo is part of the working header, so the hash must be redone
+        header.work_ser_algo = WorkSerAlgo::Blake3_2.to_u16();
+        assert!(!header.is_work_ser_hash_valid());
+        header.work_ser_hash = WorkSerAlgo::Blake3_2.work(&header.to_working_header().id());
+        assert!(header.is_work_ser_hash_valid());
+        assert!(!header.is_work_ser_algo_valid());
+
+        header.work_ser_algo = 4;
+        assert!(!header.is_work_ser_algo_valid());
+        assert!(!header.is_work_ser_hash_valid());
     }
 }
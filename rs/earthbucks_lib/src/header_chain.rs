@@ -1,61 +1,135 @@
+use crate::chain_params::ChainParams;
+use crate::clock::Clock;
+use crate::ebx_value::EbxValue;
 use crate::error::EbxError;
 use crate::header::Header;
 use crate::pkh::Pkh;
 use crate::script::Script;
 use crate::script_chunk::ScriptChunk;
 use crate::tx::Tx;
+use crate::tx_out::TxOut;
 
 // add Default
 #[derive(Default, Clone)]
 pub struct HeaderChain {
     pub headers: Vec<Header>,
+    pub params: ChainParams,
 }
 
 impl HeaderChain {
-    pub const LENGTH_TARGET_ADJ_PERIOD: u32 = Header::BLOCKS_PER_TARGET_ADJ_PERIOD;
-    pub const LENGTH_EXPIRY_PERIOD: u32 = Script::PKHXR_90D_60D_X_LOCK_REL;
-    pub const LENGTH_SAFETY_PERIOD: u32 = HeaderChain::LENGTH_EXPIRY_PERIOD * 2;
-
     pub fn new() -> Self {
+        Self::from_params(ChainParams::mainnet())
+    }
+
+    pub fn from_params(params: ChainParams) -> Self {
         Self {
             headers: Vec::new(),
+            params,
+        }
+    }
+
+    // a chain holding only the genesis header of the chain parameters
+    pub fn with_genesis(params: ChainParams) -> Result<Self, EbxError> {
+        let genesis_header = params.genesis_block()?.header;
+        let mut chain = Self::from_params(params);
+        chain.try_add(genesis_header)?;
+        Ok(chain)
+    }
+
+    // chains that start with any other genesis header are for a different
+    // network and must be refused
+    pub fn has_valid_genesis(&self) -> bool {
+        self.headers
+            .first()
+            .is_some_and(|header| self.is_genesis_of_params(header))
+    }
+
+    fn is_genesis_of_params(&self, header: &Header) -> bool {
+        match self.params.genesis_id() {
+            Ok(genesis_id) => header.id() == genesis_id,
+            Err(_) => false,
         }
     }
 
+    // adds the header without checking it
     pub fn add(&mut self, header: Header) -> &mut Self {
         self.headers.push(header);
         self
     }
 
+    // adds the header, refusing a first header that is not the genesis header
+    // of the chain parameters
+    pub fn try_add(&mut self, header: Header) -> Result<&mut Self, EbxError> {
+        if self.headers.is_empty() && !self.is_genesis_of_params(&header) {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "header is not the genesis header of the chain".to_string(),
+            });
+        }
+        Ok(self.add(header))
+    }
+
     pub fn get_tip(&self) -> Option<&Header> {
         self.headers.last()
     }
 
     pub fn new_header_is_valid_at(&self, header: &Header, timestamp: u64) -> bool {
-        header.is_valid_at(&self.headers, timestamp)
+        header.is_valid_at(&self.headers, timestamp, &self.params)
+    }
+
+    pub fn new_header_is_valid_now(&self, header: &Header, clock: &dyn Clock) -> bool {
+        header.is_valid_now(&self.headers, &self.params, clock)
     }
 
-    pub fn new_header_is_valid_now(&self, header: &Header) -> bool {
-        header.is_valid_now(&self.headers)
+    pub fn median_time_past(&self) -> Option<u64> {
+        Header::median_time_past(&self.headers, &self.params)
     }
 
-    pub fn get_next_coinbase_tx(&self, pkh: &Pkh, domain: &String) -> Tx {
+    pub fn get_next_coinbase_tx(&self, pkh: &Pkh, domain: &str) -> Tx {
         let building_block_n: u32 = self.headers.len() as u32;
-        let domain_buf = domain.as_bytes();
-        let script_chunk_domain = ScriptChunk::from_data(domain_buf.to_vec());
-        let input_script = Script::new(vec![script_chunk_domain]);
+        let input_script = Self::coinbase_input_script(domain);
         let output_script = Script::from_pkh_output(&pkh.buf);
-        let output_amount = Header::coinbase_amount(building_block_n);
+        let output_amount = self.params.coinbase_amount(building_block_n);
         Tx::from_coinbase(input_script, output_script, output_amount, building_block_n)
     }
 
+    // the outputs must add up to exactly the coinbase amount of the next block
+    pub fn get_next_coinbase_tx_from_tx_outs(
+        &self,
+        tx_outs: Vec<TxOut>,
+        domain: &str,
+    ) -> Result<Tx, EbxError> {
+        let building_block_n: u32 = self.headers.len() as u32;
+        let output_amount = EbxValue::new(self.params.coinbase_amount(building_block_n));
+        let output_values = tx_outs.iter().map(|tx_out| tx_out.value);
+        if EbxValue::checked_sum(output_values) != Some(output_amount) {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "output amount does not match sum of output amounts".to_string(),
+            });
+        }
+        let input_script = Self::coinbase_input_script(domain);
+        Ok(Tx::from_coinbase_tx_outs(
+            input_script,
+            tx_outs,
+            building_block_n,
+        ))
+    }
+
+    fn coinbase_input_script(domain: &str) -> Script {
+        let domain_buf = domain.as_bytes();
+        let script_chunk_domain = ScriptChunk::from_data(domain_buf.to_vec());
+        Script::new(vec![script_chunk_domain])
+    }
+
     pub fn get_next_header(
         &self,
         merkle_root: [u8; 32],
         new_timestamp: u64,
     ) -> Result<Header, EbxError> {
         // valid block header, except for PoW
-        let mut block_header: Header = Header::from_lch(&self.headers, new_timestamp)?;
+        let mut block_header: Header =
+            Header::from_lch(&self.headers, new_timestamp, &self.params)?;
         block_header.merkle_root = merkle_root;
         Ok(block_header)
     }
@@ -64,6 +138,7 @@ impl HeaderChain {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::ManualClock;
     use crate::header::Header;
     use crate::numbers::u256;
     #[test]
@@ -105,4 +180,91 @@ mod tests {
         chain.add(header);
         assert_eq!(chain.get_tip().unwrap().version, 0);
     }
+
+    #[test]
+    fn test_mine_regtest_chain() {
+        let mut chain = HeaderChain::with_genesis(ChainParams::regtest()).unwrap();
+        assert!(chain.has_valid_genesis());
+        for block_num in 1..21 {
+            // one millisecond apart, far faster than the block interval
+            let timestamp = block_num as u64;
+            let mut header = chain.get_next_header([0; 32], timestamp).unwrap();
+            while !header.is_id_valid() {
+                header.nonce += u256::ONE;
+            }
+            assert_eq!(header.block_num, block_num);
+            assert!(chain.new_header_is_valid_at(&header, timestamp));
+            chain.add(header);
+        }
+        assert_eq!(chain.headers.len(), 21);
+    }
+
+    #[test]
+    fn test_new_header_is_valid_now() {
+        let mut chain = HeaderChain::with_genesis(ChainParams::regtest()).unwrap();
+        let timestamp = 1_000_000;
+        let mut header = chain.get_next_header([0; 32], timestamp).unwrap();
+        while !header.is_id_valid() {
+            header.nonce += u256::ONE;
+        }
+
+        // the header may be ahead of the clock by the allowed drift, but no more
+        let clock = ManualClock::new(timestamp - chain.params.max_future_drift - 1);
+        assert!(!chain.new_header_is_valid_now(&header, &clock));
+        clock.advance(1);
+        assert!(chain.new_header_is_valid_now(&header, &clock));
+        clock.set(timestamp);
+        assert!(chain.new_header_is_valid_now(&header, &clock));
+
+        chain.add(header);
+        assert_eq!(chain.median_time_past(), Some(timestamp));
+    }
+
+    #[test]
+    fn test_refuses_other_genesis() {
+        let mut chain = HeaderChain::from_params(ChainParams::regtest());
+        assert!(!chain.has_valid_genesis());
+
+        // a structurally valid genesis header that is not the regtest genesis
+        let header = chain.get_next_header([0; 32], 0).unwrap();
+        assert!(header.is_genesis());
+        assert!(!chain.new_header_is_valid_at(&header, 0));
+        assert!(chain.try_add(header.clone()).is_err());
+        assert!(chain.headers.is_empty());
+        chain.add(header);
+        assert!(!chain.has_valid_genesis());
+
+        // the genesis of another network
+        let mainnet_genesis = ChainParams::mainnet().genesis_block().unwrap().header;
+        let chain = HeaderChain::from_params(ChainParams::regtest());
+        assert!(!chain.new_header_is_valid_at(&mainnet_genesis, u64::MAX));
+
+        let regtest_genesis = ChainParams::regtest().genesis_block().unwrap().header;
+        assert!(chain.new_header_is_valid_at(&regtest_genesis, 0));
+        let mut chain = HeaderChain::from_params(ChainParams::regtest());
+        assert!(chain.try_add(mainnet_genesis).is_err());
+        chain.try_add(regtest_genesis).unwrap();
+        assert!(chain.has_valid_genesis());
+    }
+
+    #[test]
+    fn test_get_next_coinbase_tx_from_tx_outs() {
+        let chain = HeaderChain::with_genesis(ChainParams::regtest()).unwrap();
+        let amount = chain.params.coinbase_amount(1);
+        let tx_outs = vec![
+            TxOut::new(amount / 2, Script::from_pkh_output(&[1; 32])),
+            TxOut::new(amount / 2, Script::from_pkhx_90d_output(&[2; 32])),
+        ];
+        let tx = chain
+            .get_next_coinbase_tx_from_tx_outs(tx_outs.clone(), "example.com")
+            .unwrap();
+        assert!(tx.is_coinbase());
+        assert_eq!(tx.lock_abs, 1);
+        assert_eq!(tx.outputs, tx_outs);
+
+        let tx_outs = vec![TxOut::new(amount - 1, Script::from_pkh_output(&[1; 32]))];
+        assert!(chain
+            .get_next_coinbase_tx_from_tx_outs(tx_outs, "example.com")
+            .is_err());
+    }
 }
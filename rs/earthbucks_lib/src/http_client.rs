@@ -0,0 +1,193 @@
+use crate::error::EbxError;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// the way the library makes http requests, so that it does not depend on any
+// particular http library. nodes supply a client that speaks https. tests use
+// a manual client, or the plain client against a local stand-in server.
+pub trait HttpClient: Send + Sync {
+    // the body of a successful response to a GET request
+    fn get(&self, url: &str) -> Result<String, EbxError>;
+}
+
+fn http_error(url: &str, message: &str) -> EbxError {
+    EbxError::GenericError {
+        source: None,
+        message: format!("http GET {}: {}", url, message),
+    }
+}
+
+// a client that speaks plain http/1.0 over tcp, with no tls. this is enough
+// for localhost domains in development, and for tests.
+#[derive(Debug, Clone, Copy)]
+pub struct PlainHttpClient {
+    pub timeout: Duration,
+}
+
+impl PlainHttpClient {
+    pub const MAX_RESPONSE_SIZE: u64 = 1_000_000;
+
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl Default for PlainHttpClient {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10))
+    }
+}
+
+impl HttpClient for PlainHttpClient {
+    fn get(&self, url: &str) -> Result<String, EbxError> {
+        let rest = match url.strip_prefix("http://") {
+            Some(rest) => rest,
+            None => return Err(http_error(url, "only http urls are supported")),
+        };
+        let (host_port, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) => match port.parse::<u16>() {
+                Ok(port) => (host, port),
+                Err(_) => return Err(http_error(url, "invalid port")),
+            },
+            None => (host_port, 80),
+        };
+
+        let mut stream =
+            TcpStream::connect((host, port)).map_err(|e| http_error(url, &e.to_string()))?;
+        stream
+            .set_read_timeout(Some(self.timeout))
+            .and_then(|_| stream.set_write_timeout(Some(self.timeout)))
+            .map_err(|e| http_error(url, &e.to_string()))?;
+        let request = format!(
+            "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            path, host_port
+        );
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| http_error(url, &e.to_string()))?;
+        let mut response = Vec::new();
+        stream
+            .take(Self::MAX_RESPONSE_SIZE)
+            .read_to_end(&mut response)
+            .map_err(|e| http_error(url, &e.to_string()))?;
+
+        let response =
+            String::from_utf8(response).map_err(|_| http_error(url, "response is not utf-8"))?;
+        let (head, body) = match response.split_once("\r\n\r\n") {
+            Some((head, body)) => (head, body),
+            None => return Err(http_error(url, "invalid response")),
+        };
+        let status = head.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some("200") => Ok(body.to_string()),
+            _ => Err(http_error(url, status)),
+        }
+    }
+}
+
+// a client that serves responses from memory, and counts the requests made
+#[derive(Debug, Default)]
+pub struct ManualHttpClient {
+    responses: Mutex<HashMap<String, String>>,
+    n_requests: AtomicUsize,
+}
+
+impl ManualHttpClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, url: &str, body: &str) {
+        let mut responses = self.responses.lock().unwrap();
+        responses.insert(url.to_string(), body.to_string());
+    }
+
+    pub fn remove(&self, url: &str) {
+        let mut responses = self.responses.lock().unwrap();
+        responses.remove(url);
+    }
+
+    pub fn n_requests(&self) -> usize {
+        self.n_requests.load(Ordering::SeqCst)
+    }
+}
+
+impl HttpClient for ManualHttpClient {
+    fn get(&self, url: &str) -> Result<String, EbxError> {
+        self.n_requests.fetch_add(1, Ordering::SeqCst);
+        let responses = self.responses.lock().unwrap();
+        match responses.get(url) {
+            Some(body) => Ok(body.clone()),
+            None => Err(http_error(url, "404 Not Found")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // answers one request with the given status line and body, and returns
+    // the request line it received
+    fn serve_once(status: &'static str, body: &'static str) -> (u16, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request_line = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            reader.read_line(&mut request_line).unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let response = format!("{}\r\nContent-Type: text/plain\r\n\r\n{}", status, body);
+            stream.write_all(response.as_bytes()).unwrap();
+            request_line
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn test_plain_http_client() {
+        let (port, handle) = serve_once("HTTP/1.0 200 OK", "hello");
+        let url = format!("http://127.0.0.1:{}/.well-known/test.txt", port);
+        assert_eq!(PlainHttpClient::default().get(&url).unwrap(), "hello");
+        let request_line = handle.join().unwrap();
+        assert_eq!(request_line, "GET /.well-known/test.txt HTTP/1.0\r\n");
+
+        let (port, handle) = serve_once("HTTP/1.0 404 Not Found", "");
+        let url = format!("http://127.0.0.1:{}/", port);
+        let err = PlainHttpClient::default().get(&url).unwrap_err();
+        assert!(err.to_string().contains("404"));
+        handle.join().unwrap();
+
+        let err = PlainHttpClient::default()
+            .get("https://earthbucks.com/")
+            .unwrap_err();
+        assert!(err.to_string().contains("only http"));
+    }
+
+    #[test]
+    fn test_manual_http_client() {
+        let client = ManualHttpClient::new();
+        assert!(client.get("https://earthbucks.com/").is_err());
+        client.set("https://earthbucks.com/", "hello");
+        assert_eq!(client.get("https://earthbucks.com/").unwrap(), "hello");
+        client.remove("https://earthbucks.com/");
+        assert!(client.get("https://earthbucks.com/").is_err());
+        assert_eq!(client.n_requests(), 3);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::buf::EbxBuf;
+use crate::buf_reader::BufReader;
+use crate::buf_writer::BufWriter;
+use crate::error::EbxError;
+use crate::header_chain::HeaderChain;
+use crate::var_int::VarInt;
+
+// lch: longest chain headers. the ids of the 10 most recent headers of the
+// longest chain, in chronological order, so that a miner can tell which chain
+// it is being asked to extend. the same as Lch10Ids in the typescript library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lch10Ids {
+    pub ids: Vec<[u8; 32]>,
+}
+
+impl Lch10Ids {
+    pub const MAX_IDS: usize = 10;
+
+    pub fn new(ids: Vec<[u8; 32]>) -> Result<Self, EbxError> {
+        if ids.len() > Self::MAX_IDS {
+            return Err(Self::too_many_ids_error(ids.len()));
+        }
+        Ok(Self { ids })
+    }
+
+    fn too_many_ids_error(ids_len: usize) -> EbxError {
+        EbxError::GenericError {
+            source: None,
+            message: format!("lch10 ids has {} ids, more than 10", ids_len),
+        }
+    }
+
+    pub fn from_header_chain(lch: &HeaderChain) -> Self {
+        let start = lch.headers.len().saturating_sub(Self::MAX_IDS);
+        let ids = lch.headers[start..]
+            .iter()
+            .map(|header| header.id())
+            .collect();
+        Self { ids }
+    }
+
+    // the ids are those of the tip of the chain
+    pub fn is_valid_in(&self, lch: &HeaderChain) -> bool {
+        *self == Self::from_header_chain(lch)
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut writer = BufWriter::new();
+        writer.write(VarInt::from_u64(self.ids.len() as u64).to_buf());
+        for id in &self.ids {
+            writer.write(id.to_vec());
+        }
+        writer.to_buf()
+    }
+
+    pub fn from_buf_reader(br: &mut BufReader) -> Result<Self, EbxError> {
+        let ids_len = VarInt::from_buf_reader(br)?.to_u64()? as usize;
+        if ids_len > Self::MAX_IDS {
+            return Err(Self::too_many_ids_error(ids_len));
+        }
+        let mut ids = Vec::with_capacity(ids_len);
+        for _ in 0..ids_len {
+            let id: [u8; 32] = br.read(32)?.try_into().unwrap();
+            ids.push(id);
+        }
+        Self::new(ids)
+    }
+
+    pub fn from_buf(buf: Vec<u8>) -> Result<Self, EbxError> {
+        let mut br = BufReader::new(buf);
+        Self::from_buf_reader(&mut br)
+    }
+
+    pub fn to_strict_hex(&self) -> String {
+        self.to_buf().to_strict_hex()
+    }
+
+    pub fn from_strict_hex(hex: &str) -> Result<Self, EbxError> {
+        Self::from_buf(Vec::<u8>::from_strict_hex(hex)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_params::ChainParams;
+    use crate::header::Header;
+
+    #[test]
+    fn test_to_from_buf() {
+        let lch10_ids = Lch10Ids::new(vec![[1; 32], [2; 32]]).unwrap();
+        let buf = lch10_ids.to_buf();
+        assert_eq!(buf.len(), 1 + 2 * 32);
+        assert_eq!(buf[0], 2);
+        assert_eq!(Lch10Ids::from_buf(buf).unwrap(), lch10_ids);
+        assert_eq!(
+            Lch10Ids::from_strict_hex(&lch10_ids.to_strict_hex()).unwrap(),
+            lch10_ids
+        );
+
+        assert!(Lch10Ids::new(vec![[0; 32]; 11]).is_err());
+        let mut buf = Lch10Ids::new(vec![[0; 32]; 10]).unwrap().to_buf();
+        buf[0] = 11;
+        buf.extend([0; 32]);
+        assert!(Lch10Ids::from_buf(buf).is_err());
+        assert!(Lch10Ids::from_buf(vec![1]).is_err());
+    }
+
+    #[test]
+    fn test_is_valid_in() {
+        let mut lch = HeaderChain::from_params(ChainParams::regtest());
+        assert!(Lch10Ids::new(vec![]).unwrap().is_valid_in(&lch));
+        for block_num in 0..12 {
+            let mut header = Header::from_genesis(0);
+            header.block_num = block_num;
+            lch.add(header);
+        }
+        let lch10_ids = Lch10Ids::from_header_chain(&lch);
+        assert_eq!(lch10_ids.ids.len(), 10);
+        assert_eq!(lch10_ids.ids[0], lch.headers[2].id());
+        assert_eq!(lch10_ids.ids[9], lch.headers[11].id());
+        assert!(lch10_ids.is_valid_in(&lch));
+
+        // a chain that has moved on
+        let mut header = Header::from_genesis(0);
+        header.block_num = 12;
+        lch.add(header);
+        assert!(!lch10_ids.is_valid_in(&lch));
+    }
+}
@@ -4,27 +4,50 @@ pub mod block_verifier;
 pub mod buf;
 pub mod buf_reader;
 pub mod buf_writer;
+pub mod chain_params;
+pub mod clock;
+pub mod compucha_challenge;
+pub mod compucha_verifier;
 pub mod domain;
+pub mod domain_priv_key;
+pub mod domain_pub_key;
+pub mod domain_verifier;
+pub mod ebx_value;
+pub mod ecdsa;
+pub mod emission;
 pub mod error;
 pub mod hash;
 pub mod header;
 pub mod header_chain;
+pub mod http_client;
 pub mod key_pair;
+pub mod lch10_ids;
 pub mod merkle_node;
 pub mod merkle_proof;
+pub mod merkle_tree;
 pub mod merkle_txs;
 pub mod numbers;
 pub mod opcode;
+pub mod payout_ledger;
+pub mod permission_token;
 pub mod pkh;
 pub mod pkh_key_map;
+pub mod pool;
 pub mod priv_key;
 pub mod pub_key;
 pub mod script;
 pub mod script_chunk;
 pub mod script_interpreter;
 pub mod script_num;
+pub mod sig_cache;
+pub mod signed_message;
+pub mod signed_work_data;
+pub mod signin_challenge;
+pub mod signin_response;
+pub mod signin_verifier;
 pub mod tx;
 pub mod tx_builder;
+pub mod tx_fee;
 pub mod tx_in;
 pub mod tx_out;
 pub mod tx_out_bn;
@@ -33,3 +56,6 @@ pub mod tx_signature;
 pub mod tx_signer;
 pub mod tx_verifier;
 pub mod var_int;
+pub mod work_data;
+pub mod work_pack;
+pub mod work_ser_algo;
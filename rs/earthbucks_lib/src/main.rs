@@ -1,42 +1,127 @@
+use earthbucks_lib::block::Block;
+use earthbucks_lib::block_builder::BlockBuilder;
+use earthbucks_lib::block_verifier::BlockVerifier;
+use earthbucks_lib::buf::EbxBuf;
+use earthbucks_lib::chain_params::ChainParams;
+use earthbucks_lib::emission::Emission;
+use earthbucks_lib::header_chain::HeaderChain;
 use earthbucks_lib::key_pair::KeyPair;
+use earthbucks_lib::numbers::u256;
 use earthbucks_lib::pkh;
+use earthbucks_lib::script::Script;
+use earthbucks_lib::tx_out_bn_map::TxOutBnMap;
 use std::env;
 
+const USAGE: &str = "Usage:
+  key
+  pkh
+  genesis <chain>                                  verify the genesis block of a chain
+  genesis <chain> <timestamp> <domain> [address]   generate a genesis block
+  supply <chain> [block_num]                       issued supply at a block";
+
 fn main() {
     let args: Vec<String> = env::args().collect();
+    let args: Vec<&str> = args.iter().skip(1).map(String::as_str).collect();
 
-    match args.len() {
-        1 => {
-            println!("Please provide an argument: key or pkh");
+    match args.as_slice() {
+        [] => {
+            println!("Please provide an argument: key, pkh or genesis");
         }
-        2 => match args[1].as_str() {
-            "key" => {
-                let key = KeyPair::from_random();
-                let priv_key_str = key.priv_key.to_strict_str();
-                let pub_key_str = key.pub_key.to_strict_str();
-
-                println!("Private key: {}", priv_key_str);
-                println!("Public key: {}", pub_key_str);
-            }
-            "pkh" => {
-                let key = KeyPair::from_random();
-                let public_key = key.pub_key.buf;
-                let pkh = pkh::Pkh::from_pub_key_buffer(public_key.to_vec());
-
-                let prv_key_str = key.priv_key.to_strict_str();
-                let pub_key_str = key.pub_key.to_strict_str();
-                let pkh_str = pkh.to_strict_str();
-
-                println!("Private key: {}", prv_key_str);
-                println!("Public key: {}", pub_key_str);
-                println!("Address: {}", pkh_str);
+        ["key"] => {
+            let key = KeyPair::from_random();
+            let priv_key_str = key.priv_key.to_strict_str();
+            let pub_key_str = key.pub_key.to_strict_str();
+
+            println!("Private key: {}", priv_key_str);
+            println!("Public key: {}", pub_key_str);
+        }
+        ["pkh"] => {
+            let key = KeyPair::from_random();
+            let public_key = key.pub_key.buf;
+            let pkh = pkh::Pkh::from_pub_key_buffer(public_key.to_vec());
+
+            let prv_key_str = key.priv_key.to_strict_str();
+            let pub_key_str = key.pub_key.to_strict_str();
+            let pkh_str = pkh.to_strict_str();
+
+            println!("Private key: {}", prv_key_str);
+            println!("Public key: {}", pub_key_str);
+            println!("Address: {}", pkh_str);
+        }
+        ["genesis", chain] => {
+            let params = match ChainParams::from_name(chain) {
+                Ok(params) => params,
+                Err(err) => return println!("{}", err),
+            };
+            let block = match params.genesis_block() {
+                Ok(block) => block,
+                Err(err) => return println!("Invalid genesis block: {}", err),
+            };
+            let is_valid = is_valid_genesis(block.clone(), &params);
+            print_genesis(&block);
+            println!("Valid: {}", is_valid);
+        }
+        ["genesis", chain, timestamp, domain, address @ ..] if address.len() <= 1 => {
+            let params = match ChainParams::from_name(chain) {
+                Ok(params) => params,
+                Err(err) => return println!("{}", err),
+            };
+            let timestamp: u64 = match timestamp.parse() {
+                Ok(timestamp) => timestamp,
+                Err(_) => return println!("Invalid timestamp: {}", timestamp),
+            };
+            // without an address, the coinbase pays to a pkh nobody has the key
+            // for, so the genesis coins can never be spent
+            let pkh_buf = match address.first() {
+                Some(address) => match pkh::Pkh::from_strict_str(address) {
+                    Ok(pkh) => pkh.buf,
+                    Err(_) => return println!("Invalid address: {}", address),
+                },
+                None => [0; 32],
+            };
+            let output_script = Script::from_pkh_output(&pkh_buf);
+            let mut bb = BlockBuilder::from_genesis(domain, output_script, timestamp, &params);
+            while !bb.header.is_id_valid() {
+                bb.header.nonce += u256::ONE;
             }
-            _ => {
-                println!("Invalid argument. Please provide key or pkh");
+            let block = bb.to_block();
+            let is_valid = is_valid_genesis(block.clone(), &params);
+            print_genesis(&block);
+            println!("Valid: {}", is_valid);
+        }
+        ["supply", chain, block_num @ ..] if block_num.len() <= 1 => {
+            let params = match ChainParams::from_name(chain) {
+                Ok(params) => params,
+                Err(err) => return println!("{}", err),
+            };
+            let emission = Emission::new(&params);
+            match block_num.first() {
+                Some(block_num) => {
+                    let block_num: u32 = match block_num.parse() {
+                        Ok(block_num) => block_num,
+                        Err(_) => return println!("Invalid block number: {}", block_num),
+                    };
+                    println!("Subsidy: {}", emission.subsidy(block_num));
+                    println!("Issued supply: {}", emission.issued_supply(block_num));
+                }
+                None => {
+                    println!("Final supply: {}", emission.final_supply());
+                    println!("Final block number: {}", emission.final_block_num());
+                }
             }
-        },
+        }
         _ => {
-            println!("Too many arguments. Please provide only one argument: key or pkh");
+            println!("{}", USAGE);
         }
     }
 }
+
+fn is_valid_genesis(block: Block, params: &ChainParams) -> bool {
+    let lch = HeaderChain::from_params(params.clone());
+    BlockVerifier::new(block, TxOutBnMap::new(), &lch).is_valid_genesis()
+}
+
+fn print_genesis(block: &Block) {
+    println!("Genesis block: {}", block.to_strict_hex());
+    println!("Genesis id: {}", block.header.id().to_strict_hex());
+}
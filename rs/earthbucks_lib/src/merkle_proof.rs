@@ -2,6 +2,7 @@ use crate::buf::EbxBuf;
 use crate::buf_reader::BufReader;
 use crate::buf_writer::BufWriter;
 use crate::hash::double_blake3_hash;
+use crate::merkle_tree::MerkleTree;
 
 #[derive(Debug, Clone)]
 pub struct MerkleProof {
@@ -14,17 +15,21 @@ impl MerkleProof {
         Self { root, proof }
     }
 
+    // the proof runs from the leaf up to the root, as produced by MerkleTree.
+    // is_left means the node being proven is on the left. a zeroed sibling is
+    // a null subtree, which contributes no bytes to its parent's hash.
     pub fn verify(&self, hashed_data: &[u8; 32]) -> bool {
-        let mut hash = hashed_data.to_vec();
+        let mut hash = *hashed_data;
         for (sibling, is_left) in &self.proof {
+            let sibling = if *sibling == [0; 32] {
+                None
+            } else {
+                Some(sibling)
+            };
             hash = if *is_left {
-                let mut new_hash = sibling.to_vec();
-                new_hash.extend_from_slice(&hash);
-                double_blake3_hash(&new_hash).to_vec()
+                double_blake3_hash(&MerkleTree::concat(Some(&hash), sibling))
             } else {
-                let mut new_hash = hash.clone();
-                new_hash.extend_from_slice(sibling);
-                double_blake3_hash(&new_hash).to_vec()
+                double_blake3_hash(&MerkleTree::concat(sibling, Some(&hash)))
             }
         }
         hash == self.root
@@ -0,0 +1,507 @@
+use crate::error::EbxError;
+use crate::hash::double_blake3_hash;
+use crate::merkle_proof::MerkleProof;
+use std::sync::Arc;
+
+// sibling hashes from a leaf up to the root, each paired with is_left
+pub type MerkleTreeProof = Vec<(Option<[u8; 32]>, bool)>;
+
+// a balanced merkle tree whose leaf count is always a power of two. unused
+// leaves are null (no hash) and contribute no bytes when hashing their parent.
+// subtrees are shared with Arc, so updating or appending a leaf only rebuilds
+// the path from that leaf to the root.
+#[derive(Debug, Clone, Default)]
+pub struct MerkleTree {
+    pub left: Option<Arc<MerkleTree>>,
+    pub right: Option<Arc<MerkleTree>>,
+    pub hash: Option<[u8; 32]>,
+}
+
+impl MerkleTree {
+    pub fn new(
+        left: Option<Arc<MerkleTree>>,
+        right: Option<Arc<MerkleTree>>,
+        hash: Option<[u8; 32]>,
+    ) -> Self {
+        Self { left, right, hash }
+    }
+
+    fn from_leaf_hash(hash: Option<[u8; 32]>) -> Self {
+        Self::new(None, None, hash)
+    }
+
+    fn from_children(left: Option<Arc<MerkleTree>>, right: Option<Arc<MerkleTree>>) -> Self {
+        let left_hash = left.as_ref().and_then(|left| left.hash);
+        let right_hash = right.as_ref().and_then(|right| right.hash);
+        let hash = double_blake3_hash(&Self::concat(left_hash.as_ref(), right_hash.as_ref()));
+        Self::new(left, right, Some(hash))
+    }
+
+    pub fn count_non_null_leaves(&self) -> usize {
+        if self.left.is_some() || self.right.is_some() {
+            return self
+                .left
+                .as_ref()
+                .map_or(0, |left| left.count_non_null_leaves())
+                + self
+                    .right
+                    .as_ref()
+                    .map_or(0, |right| right.count_non_null_leaves());
+        }
+        if self.hash.is_some() {
+            1
+        } else {
+            0
+        }
+    }
+
+    pub fn count_all_leaves(&self) -> usize {
+        if self.left.is_some() || self.right.is_some() {
+            return self.left.as_ref().map_or(0, |left| left.count_all_leaves())
+                + self
+                    .right
+                    .as_ref()
+                    .map_or(0, |right| right.count_all_leaves());
+        }
+        1
+    }
+
+    // every subtree of a balanced tree is full, so the leaf count follows from
+    // the depth of the left edge without visiting every node.
+    fn count_balanced_leaves(&self) -> usize {
+        match &self.left {
+            Some(left) => 2 * left.count_balanced_leaves(),
+            None => 1,
+        }
+    }
+
+    pub fn compute_all_leaves_for_balanced_tree(non_null_leaves: usize) -> usize {
+        if non_null_leaves == 0 {
+            return 0;
+        }
+        non_null_leaves.next_power_of_two()
+    }
+
+    pub fn height(&self) -> usize {
+        self.count_all_leaves().ilog2() as usize + 1
+    }
+
+    pub fn concat(left: Option<&[u8; 32]>, right: Option<&[u8; 32]>) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(64);
+        if let Some(left) = left {
+            buf.extend_from_slice(left);
+        }
+        if let Some(right) = right {
+            buf.extend_from_slice(right);
+        }
+        buf
+    }
+
+    pub fn compute_hash(&self) -> Option<[u8; 32]> {
+        if self.left.is_some() || self.right.is_some() {
+            let left_hash = self.left.as_ref().and_then(|left| left.compute_hash());
+            let right_hash = self.right.as_ref().and_then(|right| right.compute_hash());
+            return Some(double_blake3_hash(&Self::concat(
+                left_hash.as_ref(),
+                right_hash.as_ref(),
+            )));
+        }
+        self.hash
+    }
+
+    pub fn compute_merkle_root_id(&self) -> [u8; 32] {
+        self.compute_hash().unwrap_or([0; 32])
+    }
+
+    pub fn left_height(&self) -> usize {
+        match &self.left {
+            Some(left) => left.left_height() + 1,
+            None => self.hash.is_some() as usize,
+        }
+    }
+
+    pub fn right_height(&self) -> usize {
+        match &self.right {
+            Some(right) => right.right_height() + 1,
+            None => self.hash.is_some() as usize,
+        }
+    }
+
+    pub fn is_null_balanced(&self) -> bool {
+        match (&self.left, &self.right) {
+            (None, None) => true,
+            (Some(left), Some(right)) => {
+                left.is_null_balanced()
+                    && right.is_null_balanced()
+                    && left.left_height().abs_diff(right.left_height()) <= 1
+                    && left.right_height().abs_diff(right.right_height()) <= 1
+            }
+            _ => false,
+        }
+    }
+
+    pub fn from_leaf_hashes(hashes: &[[u8; 32]]) -> Self {
+        let hashes: Vec<Option<[u8; 32]>> = hashes.iter().map(|hash| Some(*hash)).collect();
+        Self::from_optional_leaf_hashes(hashes)
+    }
+
+    fn from_optional_leaf_hashes(mut hashes: Vec<Option<[u8; 32]>>) -> Self {
+        match hashes.len() {
+            0 => Self::default(),
+            1 => Self::from_leaf_hash(hashes[0]),
+            2 => Self::from_children(
+                Some(Arc::new(Self::from_leaf_hash(hashes[0]))),
+                Some(Arc::new(Self::from_leaf_hash(hashes[1]))),
+            ),
+            _ => {
+                // ensure balance by filling with nulls
+                hashes.resize(hashes.len().next_power_of_two(), None);
+                let right_hashes = hashes.split_off(hashes.len() / 2);
+                let left = Self::from_optional_leaf_hashes(hashes);
+                let right = Self::from_optional_leaf_hashes(right_hashes);
+                Self::from_children(Some(Arc::new(left)), Some(Arc::new(right)))
+            }
+        }
+    }
+
+    // a tree of all-null leaves. both halves are identical, so they share one
+    // allocation and the tree is built in O(log n).
+    fn from_null_leaves(count: usize) -> Self {
+        if count <= 1 {
+            return Self::default();
+        }
+        let half = Arc::new(Self::from_null_leaves(count / 2));
+        Self::from_children(Some(half.clone()), Some(half))
+    }
+
+    pub fn double_with_nulls(&self) -> Result<Self, EbxError> {
+        let count = self.count_balanced_leaves();
+        if !count.is_power_of_two() {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "cannot double a tree that is not a power of 2".to_string(),
+            });
+        }
+        let null_tree = Self::from_null_leaves(count);
+        let hash = double_blake3_hash(&Self::concat(self.hash.as_ref(), null_tree.hash.as_ref()));
+        Ok(Self::new(
+            Some(Arc::new(self.clone())),
+            Some(Arc::new(null_tree)),
+            Some(hash),
+        ))
+    }
+
+    pub fn update_balanced_leaf_hash(&self, pos: usize, hash: [u8; 32]) -> Result<Self, EbxError> {
+        let count_all = self.count_balanced_leaves();
+        if pos >= count_all {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "position must be less than the number of leaves".to_string(),
+            });
+        }
+        Ok(self.update_leaf_hash(pos, hash, count_all))
+    }
+
+    fn update_leaf_hash(&self, pos: usize, hash: [u8; 32], count_all: usize) -> Self {
+        if count_all == 1 {
+            return Self::from_leaf_hash(Some(hash));
+        }
+        let count_left = count_all / 2;
+        if pos < count_left {
+            let left = self
+                .left
+                .as_ref()
+                .map(|left| Arc::new(left.update_leaf_hash(pos, hash, count_left)));
+            Self::from_children(left, self.right.clone())
+        } else {
+            let right = self
+                .right
+                .as_ref()
+                .map(|right| Arc::new(right.update_leaf_hash(pos - count_left, hash, count_left)));
+            Self::from_children(self.left.clone(), right)
+        }
+    }
+
+    // add a leaf hash to the tree, which is assumed to be balanced with nulls.
+    // non_null_leaves is the number of leaves already in use, typically the
+    // number of txs. passing it avoids counting them, so that appending a leaf
+    // is O(log n).
+    pub fn add_leaf_hash(
+        &self,
+        hash: [u8; 32],
+        non_null_leaves: Option<usize>,
+    ) -> Result<Self, EbxError> {
+        let count_non_null = non_null_leaves.unwrap_or_else(|| self.count_non_null_leaves());
+        let count_all = Self::compute_all_leaves_for_balanced_tree(count_non_null);
+        if count_non_null == count_all && count_all > 0 {
+            return self
+                .double_with_nulls()?
+                .update_balanced_leaf_hash(count_non_null, hash);
+        }
+        self.update_balanced_leaf_hash(count_non_null, hash)
+    }
+
+    pub fn update_balanced_leaf_hashes(
+        &self,
+        start_pos: usize,
+        hashes: &[[u8; 32]],
+    ) -> Result<Self, EbxError> {
+        let mut tree = self.clone();
+        for (i, hash) in hashes.iter().enumerate() {
+            tree = tree.update_balanced_leaf_hash(start_pos + i, *hash)?;
+        }
+        Ok(tree)
+    }
+
+    pub fn add_leaf_hashes(
+        &self,
+        hashes: &[[u8; 32]],
+        non_null_leaves: Option<usize>,
+    ) -> Result<Self, EbxError> {
+        let mut tree = self.clone();
+        for (i, hash) in hashes.iter().enumerate() {
+            tree = tree.add_leaf_hash(*hash, non_null_leaves.map(|n| n + i))?;
+        }
+        Ok(tree)
+    }
+
+    pub fn get_merkle_proof(&self, pos: usize) -> Result<MerkleProof, EbxError> {
+        // null hashes are written as zeros, the same as the typescript proof
+        let proof = self
+            .get_proof(pos)?
+            .into_iter()
+            .map(|(sibling, is_left)| (sibling.unwrap_or([0; 32]), is_left))
+            .collect();
+        Ok(MerkleProof::new(self.hash.unwrap_or([0; 32]), proof))
+    }
+
+    // the proof runs from the leaf up to the root. is_left is true when the
+    // node being proven is the left child, i.e. the sibling is on the right.
+    pub fn get_proof(&self, pos: usize) -> Result<MerkleTreeProof, EbxError> {
+        let count_all = self.count_balanced_leaves();
+        if pos >= count_all {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "position must be less than the number of leaves".to_string(),
+            });
+        }
+        let mut proof = Vec::new();
+        self.collect_proof(pos, count_all, &mut proof);
+        Ok(proof)
+    }
+
+    fn collect_proof(&self, pos: usize, count_all: usize, proof: &mut MerkleTreeProof) {
+        if count_all == 1 {
+            return;
+        }
+        let count_left = count_all / 2;
+        let left_hash = self.left.as_ref().and_then(|left| left.hash);
+        let right_hash = self.right.as_ref().and_then(|right| right.hash);
+        if pos < count_left {
+            if let Some(left) = &self.left {
+                left.collect_proof(pos, count_left, proof);
+            }
+            proof.push((right_hash, true));
+        } else {
+            if let Some(right) = &self.right {
+                right.collect_proof(pos - count_left, count_left, proof);
+            }
+            proof.push((left_hash, false));
+        }
+    }
+
+    pub fn verify_proof(
+        merkle_root: Option<&[u8; 32]>,
+        proof: &[(Option<[u8; 32]>, bool)],
+        hash: &[u8; 32],
+    ) -> bool {
+        let merkle_root = match merkle_root {
+            Some(merkle_root) => merkle_root,
+            None => return false,
+        };
+        let mut hash = *hash;
+        for (sibling, is_left) in proof {
+            hash = if *is_left {
+                double_blake3_hash(&Self::concat(Some(&hash), sibling.as_ref()))
+            } else {
+                double_blake3_hash(&Self::concat(sibling.as_ref(), Some(&hash)))
+            };
+        }
+        hash == *merkle_root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::fs;
+
+    fn data(name: &str) -> [u8; 32] {
+        double_blake3_hash(name.as_bytes())
+    }
+
+    fn datas(names: &[String]) -> Vec<[u8; 32]> {
+        names.iter().map(|name| data(name)).collect()
+    }
+
+    #[derive(Deserialize)]
+    struct MerkleTreeData {
+        from_leaf_hashes: Vec<FromLeafHashesVector>,
+        update_balanced_leaf_hash: Vec<UpdateVector>,
+        add_leaf_hash: Vec<AddVector>,
+        get_merkle_proof: Vec<ProofVector>,
+        known_leaf_hashes: Vec<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct FromLeafHashesVector {
+        leaves: Vec<String>,
+        root: String,
+    }
+
+    #[derive(Deserialize)]
+    struct UpdateVector {
+        leaves: Vec<String>,
+        pos: usize,
+        leaf: String,
+        root: String,
+    }
+
+    #[derive(Deserialize)]
+    struct AddVector {
+        leaves: Vec<String>,
+        leaf: String,
+        root: String,
+    }
+
+    #[derive(Deserialize)]
+    struct ProofVector {
+        leaves: Vec<String>,
+        pos: usize,
+        proof: String,
+    }
+
+    fn test_vectors() -> MerkleTreeData {
+        let data =
+            fs::read_to_string("./test_vectors/merkle_tree.json").expect("Unable to read file");
+        serde_json::from_str(&data).expect("Unable to parse JSON")
+    }
+
+    #[test]
+    fn test_compute_all_leaves_for_balanced_tree() {
+        let expected = [0, 1, 2, 4, 4, 8, 8, 8, 8, 16];
+        for (n, count) in expected.iter().enumerate() {
+            assert_eq!(MerkleTree::compute_all_leaves_for_balanced_tree(n), *count);
+        }
+    }
+
+    #[test]
+    fn test_from_leaf_hashes_vectors() {
+        assert_eq!(MerkleTree::from_leaf_hashes(&[]).hash, None);
+        for vector in test_vectors().from_leaf_hashes {
+            let tree = MerkleTree::from_leaf_hashes(&datas(&vector.leaves));
+            assert_eq!(hex::encode(tree.hash.unwrap()), vector.root);
+            assert_eq!(tree.compute_hash(), tree.hash);
+            assert_eq!(tree.count_non_null_leaves(), vector.leaves.len());
+            assert_eq!(
+                tree.count_all_leaves(),
+                vector.leaves.len().next_power_of_two()
+            );
+            assert!(tree.is_null_balanced());
+        }
+    }
+
+    #[test]
+    fn test_update_balanced_leaf_hash_vectors() {
+        for vector in test_vectors().update_balanced_leaf_hash {
+            let tree = MerkleTree::from_leaf_hashes(&datas(&vector.leaves));
+            let updated = tree
+                .update_balanced_leaf_hash(vector.pos, data(&vector.leaf))
+                .unwrap();
+            assert_eq!(hex::encode(updated.hash.unwrap()), vector.root);
+            assert_eq!(updated.compute_hash(), updated.hash);
+            assert!(tree
+                .update_balanced_leaf_hash(tree.count_all_leaves(), data(&vector.leaf))
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_add_leaf_hash_vectors() {
+        for vector in test_vectors().add_leaf_hash {
+            let tree = MerkleTree::from_leaf_hashes(&datas(&vector.leaves));
+            let updated = tree.add_leaf_hash(data(&vector.leaf), None).unwrap();
+            assert_eq!(hex::encode(updated.hash.unwrap()), vector.root);
+            let updated = tree
+                .add_leaf_hash(data(&vector.leaf), Some(vector.leaves.len()))
+                .unwrap();
+            assert_eq!(hex::encode(updated.hash.unwrap()), vector.root);
+        }
+    }
+
+    #[test]
+    fn test_get_merkle_proof_vectors() {
+        for vector in test_vectors().get_merkle_proof {
+            let leaves = datas(&vector.leaves);
+            let tree = MerkleTree::from_leaf_hashes(&leaves);
+            let proof = tree.get_merkle_proof(vector.pos).unwrap();
+            assert_eq!(proof.to_strict_str(), vector.proof);
+            assert!(proof.verify(&leaves[vector.pos]));
+        }
+    }
+
+    #[test]
+    fn test_add_leaf_hashes_matches_from_leaf_hashes() {
+        let hashes: Vec<[u8; 32]> = test_vectors()
+            .known_leaf_hashes
+            .iter()
+            .map(|hash| hex::decode(hash).unwrap().try_into().unwrap())
+            .collect();
+        for len in 1..=hashes.len() {
+            let tree1 = MerkleTree::from_leaf_hashes(&hashes[..len]);
+            let tree2 = MerkleTree::default()
+                .add_leaf_hashes(&hashes[..len], None)
+                .unwrap();
+            let tree3 = MerkleTree::default()
+                .add_leaf_hashes(&hashes[..len], Some(0))
+                .unwrap();
+            assert_eq!(tree1.hash, tree2.hash);
+            assert_eq!(tree1.hash, tree3.hash);
+            assert_eq!(tree2.count_all_leaves(), len.next_power_of_two());
+            assert!(tree2.is_null_balanced());
+        }
+    }
+
+    #[test]
+    fn test_get_proof_and_verify() {
+        for len in 1..=9 {
+            let names: Vec<String> = (1..=len).map(|i| format!("data{}", i)).collect();
+            let leaves = datas(&names);
+            let tree = MerkleTree::from_leaf_hashes(&leaves);
+            for (pos, leaf) in leaves.iter().enumerate() {
+                let proof = tree.get_proof(pos).unwrap();
+                assert_eq!(proof.len(), tree.height() - 1);
+                assert!(MerkleTree::verify_proof(tree.hash.as_ref(), &proof, leaf));
+                assert!(!MerkleTree::verify_proof(
+                    tree.hash.as_ref(),
+                    &proof,
+                    &data("other")
+                ));
+                let merkle_proof = tree.get_merkle_proof(pos).unwrap();
+                assert!(merkle_proof.verify(leaf));
+                let merkle_proof = MerkleProof::from_buf(&merkle_proof.to_buf()).unwrap();
+                assert!(merkle_proof.verify(leaf));
+            }
+        }
+    }
+
+    #[test]
+    fn test_double_with_nulls() {
+        let tree = MerkleTree::from_leaf_hashes(&[data("data1"), data("data2")]);
+        let doubled = tree.double_with_nulls().unwrap();
+        assert_eq!(doubled.count_all_leaves(), 4);
+        assert_eq!(doubled.count_non_null_leaves(), 2);
+        assert_eq!(doubled.hash, doubled.compute_hash());
+    }
+}
@@ -1,16 +1,21 @@
 use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::MerkleTree;
 use crate::tx::Tx;
 
-pub struct MerkleTxs {
-    pub txs: Vec<Tx>,
+pub struct MerkleTxs<'a> {
+    pub txs: &'a [Tx],
     pub root: [u8; 32],
     pub proofs: Vec<MerkleProof>,
 }
 
-impl MerkleTxs {
-    pub fn new(txs: Vec<Tx>) -> Self {
-        let hashed_datas: Vec<[u8; 32]> = txs.iter().map(|tx| tx.id()).collect::<Vec<_>>();
-        let (root, proofs) = MerkleProof::generate_proofs_and_root(hashed_datas);
+impl<'a> MerkleTxs<'a> {
+    pub fn new(txs: &'a [Tx]) -> Self {
+        let tx_ids: Vec<[u8; 32]> = txs.iter().map(|tx| tx.id()).collect::<Vec<_>>();
+        let merkle_tree = MerkleTree::from_leaf_hashes(&tx_ids);
+        let root = merkle_tree.compute_merkle_root_id();
+        let proofs = (0..txs.len())
+            .map(|pos| merkle_tree.get_merkle_proof(pos).unwrap())
+            .collect();
         Self { txs, root, proofs }
     }
 
@@ -22,7 +27,7 @@ impl MerkleTxs {
         for i in 0..self.txs.len() {
             let tx = &self.txs[i];
             let proof = &self.proofs[i];
-            if !MerkleProof::verify_proof(&tx.id(), proof, &self.root) {
+            if proof.root != self.root || !proof.verify(&tx.id()) {
                 return false;
             }
         }
@@ -37,7 +42,8 @@ mod tests {
     #[test]
     fn verify_with_1_tx() {
         let tx1 = Tx::new(0, vec![], vec![], 0);
-        let merkle_txs = MerkleTxs::new(vec![tx1]);
+        let txs = [tx1];
+        let merkle_txs = MerkleTxs::new(&txs);
         let verified = merkle_txs.verify();
         assert!(verified);
     }
@@ -46,7 +52,8 @@ mod tests {
     fn verify_with_2_txs() {
         let tx1 = Tx::new(0, vec![], vec![], 0);
         let tx2 = Tx::new(0, vec![], vec![], 0);
-        let merkle_txs = MerkleTxs::new(vec![tx1, tx2]);
+        let txs = [tx1, tx2];
+        let merkle_txs = MerkleTxs::new(&txs);
         let verified = merkle_txs.verify();
         assert!(verified);
     }
@@ -56,7 +63,8 @@ mod tests {
         let tx1 = Tx::new(0, vec![], vec![], 0);
         let tx2 = Tx::new(0, vec![], vec![], 0);
         let tx3 = Tx::new(0, vec![], vec![], 0);
-        let merkle_txs = MerkleTxs::new(vec![tx1, tx2, tx3]);
+        let txs = [tx1, tx2, tx3];
+        let merkle_txs = MerkleTxs::new(&txs);
         let verified = merkle_txs.verify();
         assert!(verified);
     }
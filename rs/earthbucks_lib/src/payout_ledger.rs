@@ -0,0 +1,144 @@
+use crate::ebx_value::EbxValue;
+use crate::error::EbxError;
+use crate::pkh::Pkh;
+use crate::pub_key::PubKey;
+use crate::script::Script;
+use crate::tx_out::TxOut;
+
+#[derive(Debug, Clone)]
+pub struct Payout {
+    pub pub_key: PubKey,
+    pub shares: u64,
+    pub value: EbxValue,
+}
+
+// splits an amount between miners in proportion to the shares they were
+// credited. every adam is paid out: the adams left over from rounding down go
+// one each to the miners with the most shares. payouts are ordered by pubkey,
+// so that the same shares always give the same ledger.
+#[derive(Debug, Clone)]
+pub struct PayoutLedger {
+    pub amount: EbxValue,
+    pub payouts: Vec<Payout>,
+}
+
+impl PayoutLedger {
+    pub fn new(amount: EbxValue, shares: Vec<(PubKey, u64)>) -> Result<Self, EbxError> {
+        let err = |message: &str| EbxError::GenericError {
+            source: None,
+            message: message.to_string(),
+        };
+        if !amount.is_valid() {
+            return Err(err("payout amount is more than the max money"));
+        }
+        let mut shares: Vec<(PubKey, u64)> = shares.into_iter().filter(|s| s.1 > 0).collect();
+        shares.sort_by_key(|s| s.0.buf);
+        if shares.windows(2).any(|w| w[0].0.buf == w[1].0.buf) {
+            return Err(err("duplicate pubkey in shares"));
+        }
+        let total_shares: u128 = shares.iter().map(|s| s.1 as u128).sum();
+        if total_shares == 0 {
+            return Err(err("no shares to pay out"));
+        }
+
+        let mut payouts: Vec<Payout> = shares
+            .into_iter()
+            .map(|(pub_key, shares)| {
+                let value = amount.value as u128 * shares as u128 / total_shares;
+                Payout {
+                    pub_key,
+                    shares,
+                    value: EbxValue::new(value as u64),
+                }
+            })
+            .collect();
+        let paid: u64 = payouts.iter().map(|payout| payout.value.value).sum();
+        let mut by_shares: Vec<usize> = (0..payouts.len()).collect();
+        by_shares.sort_by(|&a, &b| payouts[b].shares.cmp(&payouts[a].shares));
+        // there are fewer leftover adams than payouts
+        for &i in by_shares.iter().take((amount.value - paid) as usize) {
+            payouts[i].value.value += 1;
+        }
+        Ok(Self { amount, payouts })
+    }
+
+    // pay-to-pkh outputs, ready for HeaderChain::get_next_coinbase_tx_from_tx_outs
+    pub fn to_tx_outs(&self) -> Vec<TxOut> {
+        self.payouts
+            .iter()
+            .filter(|payout| payout.value.value > 0)
+            .map(|payout| {
+                let pkh = Pkh::from_pub_key_buffer(payout.pub_key.buf.to_vec());
+                TxOut::new(payout.value.value, Script::from_pkh_output(pkh.to_buf()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_pair::KeyPair;
+
+    fn pub_keys(n: usize) -> Vec<PubKey> {
+        let mut pub_keys: Vec<PubKey> = (0..n).map(|_| KeyPair::from_random().pub_key).collect();
+        pub_keys.sort_by_key(|pub_key| pub_key.buf);
+        pub_keys
+    }
+
+    #[test]
+    fn test_new() {
+        let pub_keys = pub_keys(3);
+        let shares = vec![
+            (pub_keys[2].clone(), 1),
+            (pub_keys[0].clone(), 3),
+            (pub_keys[1].clone(), 0),
+        ];
+        let ledger = PayoutLedger::new(EbxValue::new(10), shares).unwrap();
+        let payouts: Vec<([u8; 33], u64, u64)> = ledger
+            .payouts
+            .iter()
+            .map(|p| (p.pub_key.buf, p.shares, p.value.value))
+            .collect();
+        // 7.5 and 2.5 adams, and the leftover adam goes to the most shares
+        assert_eq!(
+            payouts,
+            vec![(pub_keys[0].buf, 3, 8), (pub_keys[2].buf, 1, 2)]
+        );
+
+        // every adam is paid out
+        let shares = pub_keys
+            .iter()
+            .map(|pub_key| (pub_key.clone(), 1))
+            .collect();
+        let ledger = PayoutLedger::new(EbxValue::new(100), shares).unwrap();
+        let values: Vec<u64> = ledger.payouts.iter().map(|p| p.value.value).collect();
+        assert_eq!(values, vec![34, 33, 33]);
+
+        let max_money = EbxValue::new(EbxValue::MAX_MONEY);
+        let shares = vec![(pub_keys[0].clone(), u64::MAX), (pub_keys[1].clone(), 1)];
+        let ledger = PayoutLedger::new(max_money, shares).unwrap();
+        let paid = EbxValue::checked_sum(ledger.payouts.iter().map(|p| p.value.value));
+        assert_eq!(paid, Some(max_money));
+
+        assert!(PayoutLedger::new(EbxValue::new(10), vec![]).is_err());
+        let shares = vec![(pub_keys[0].clone(), 1), (pub_keys[0].clone(), 1)];
+        assert!(PayoutLedger::new(EbxValue::new(10), shares).is_err());
+    }
+
+    #[test]
+    fn test_to_tx_outs() {
+        let pub_keys = pub_keys(2);
+        let shares = vec![(pub_keys[0].clone(), 1), (pub_keys[1].clone(), 1_000)];
+        let ledger = PayoutLedger::new(EbxValue::new(100), shares).unwrap();
+        let tx_outs = ledger.to_tx_outs();
+        // nothing is paid to the first miner, so it gets no output
+        assert_eq!(tx_outs.len(), 1);
+        assert_eq!(tx_outs[0].value, 100);
+        let pkh = Pkh::from_pub_key_buffer(pub_keys[1].buf.to_vec());
+        assert_eq!(
+            tx_outs[0].script.to_buf(),
+            Script::from_pkh_output(pkh.to_buf()).to_buf()
+        );
+    }
+}
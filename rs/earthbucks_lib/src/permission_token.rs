@@ -0,0 +1,84 @@
+use crate::buf::EbxBuf;
+use crate::buf_reader::BufReader;
+use crate::buf_writer::BufWriter;
+use crate::clock::Clock;
+use crate::error::EbxError;
+
+// a random value and the time it was made. signed by a mine, it lets a user
+// sign in for a limited time. the same as PermissionToken in the typescript
+// library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionToken {
+    pub rand_value: [u8; 32],
+    pub timestamp: u64, // milliseconds
+}
+
+impl PermissionToken {
+    pub const SIZE: usize = 32 + 8;
+    pub const VALIDITY: u64 = 15 * 60 * 1000; // 15 minutes
+
+    pub fn new(rand_value: [u8; 32], timestamp: u64) -> Self {
+        Self {
+            rand_value,
+            timestamp,
+        }
+    }
+
+    pub fn from_random(clock: &dyn Clock) -> Self {
+        Self::new(EbxBuf::from_random(), clock.now())
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut writer = BufWriter::new();
+        writer.write(self.rand_value.to_vec());
+        writer.write_u64_be(self.timestamp);
+        writer.to_buf()
+    }
+
+    pub fn from_buf(buf: Vec<u8>) -> Result<Self, EbxError> {
+        if buf.len() != Self::SIZE {
+            return Err(EbxError::InvalidSizeError { source: None });
+        }
+        let mut reader = BufReader::new(buf);
+        let rand_value: [u8; 32] = reader.read(32)?.try_into().unwrap();
+        let timestamp = reader.read_u64_be()?;
+        Ok(Self::new(rand_value, timestamp))
+    }
+
+    pub fn is_valid_at(&self, timestamp: u64) -> bool {
+        timestamp.saturating_sub(self.timestamp) < Self::VALIDITY
+    }
+
+    pub fn is_valid_now(&self, clock: &dyn Clock) -> bool {
+        self.is_valid_at(clock.now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn test_to_from_buf() {
+        let clock = ManualClock::new(1_700_000_000_000);
+        let permission_token = PermissionToken::from_random(&clock);
+        let buf = permission_token.to_buf();
+        assert_eq!(buf.len(), PermissionToken::SIZE);
+        assert_eq!(&buf[32..], &1_700_000_000_000u64.to_be_bytes());
+        assert_eq!(PermissionToken::from_buf(buf).unwrap(), permission_token);
+        assert!(PermissionToken::from_buf(vec![0; PermissionToken::SIZE - 1]).is_err());
+        assert!(PermissionToken::from_buf(vec![0; PermissionToken::SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn test_is_valid_now() {
+        let clock = ManualClock::new(1_700_000_000_000);
+        let permission_token = PermissionToken::from_random(&clock);
+        assert!(permission_token.is_valid_now(&clock));
+        clock.advance(PermissionToken::VALIDITY - 1);
+        assert!(permission_token.is_valid_now(&clock));
+        clock.advance(1);
+        assert!(!permission_token.is_valid_now(&clock));
+    }
+}
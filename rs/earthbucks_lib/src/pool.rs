@@ -0,0 +1,434 @@
+use crate::ebx_value::EbxValue;
+use crate::error::EbxError;
+use crate::header::Header;
+use crate::header_chain::HeaderChain;
+use crate::numbers::u256;
+use crate::payout_ledger::PayoutLedger;
+use crate::pub_key::PubKey;
+use crate::work_pack::WorkPack;
+use crate::work_ser_algo::WorkSerAlgo;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+// a job handed to the miners of a pool: the work pack for a block template,
+// and the easier target that a share must meet
+#[derive(Debug, Clone)]
+pub struct PoolJob {
+    pub job_id: u64,
+    pub work_pack: WorkPack,
+    pub share_target: u256,
+}
+
+// a nonce that met the share target of its job. if the header also meets the
+// block target, it is a new block.
+#[derive(Debug, Clone)]
+pub struct Share {
+    pub job_id: u64,
+    pub pub_key: PubKey,
+    pub header: Header,
+    pub shares: u64,
+    pub is_block: bool,
+}
+
+struct PoolState {
+    next_job_id: u64,
+    jobs: HashMap<u64, PoolJob>,
+    // the nonces submitted for each job, so that a share is credited once
+    nonces: HashSet<(u64, u256)>,
+    // the shares credited to each miner, by pubkey, since the last payout
+    shares: HashMap<[u8; PubKey::SIZE], (PubKey, u64)>,
+}
+
+// hands out jobs to miners and credits them for the shares they find. miners
+// find shares far more often than blocks, so each miner's shares measure the
+// work it did towards the blocks the pool finds, and the coinbase of those
+// blocks is split between the miners by their shares.
+//
+// a share is worth the difficulty of its share target, so that jobs with
+// different share difficulties are credited fairly. jobs are only good while
+// they build on the tip of the chain: when the tip changes, the jobs built on
+// the old tip are stale, and shares for them are rejected.
+pub struct Pool {
+    pub share_difficulty: u64,
+    state: Mutex<PoolState>,
+}
+
+impl Pool {
+    // the number of the pow5 algorithm in the header's work_par_algo field,
+    // the same as in the typescript library
+    pub const WORK_PAR_ALGO_POW5: u16 = 2;
+
+    pub fn new(share_difficulty: u64) -> Self {
+        Self {
+            share_difficulty,
+            state: Mutex::new(PoolState {
+                next_job_id: 0,
+                jobs: HashMap::new(),
+                nonces: HashSet::new(),
+                shares: HashMap::new(),
+            }),
+        }
+    }
+
+    // the target of a share with the given difficulty, which is never harder
+    // than the block target
+    pub fn share_target(target: u256, share_difficulty: u64) -> u256 {
+        let share_target = Header::target_from_difficulty(u256::from(share_difficulty.max(1)));
+        share_target.max(target)
+    }
+
+    // what a share with the given target is worth
+    pub fn share_value(share_target: u256) -> u64 {
+        let difficulty = Header::difficulty_from_target(share_target.max(u256::from(1u8)));
+        u64::try_from(difficulty).unwrap_or(u64::MAX)
+    }
+
+    // pow5 of a header in the typescript layout, which has the number of
+    // transactions in the block
+    pub fn work_par_hash(header: &Header, n_transactions: u64) -> [u8; 32] {
+        let buf = header.to_wire_buf(n_transactions).to_vec();
+        earthbucks_pow5::get_work_par(buf)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    // the header of a job with the nonce and both work hashes filled in. the
+    // parallel work is pow5 of the working header.
+    pub fn header_with_nonce(
+        header: &Header,
+        n_transactions: u64,
+        nonce: u256,
+    ) -> Result<Header, EbxError> {
+        let mut header = header.to_working_header();
+        header.nonce = nonce;
+        header.work_par_algo = Self::WORK_PAR_ALGO_POW5;
+        let work_ser_algo = WorkSerAlgo::from_u16(header.work_ser_algo)?;
+        let work_ser_hash = work_ser_algo.work(&header.id());
+        let work_par_hash = Self::work_par_hash(&header, n_transactions);
+        header.work_ser_hash = work_ser_hash;
+        header.work_par_hash = work_par_hash;
+        Ok(header)
+    }
+
+    // a job for a block template, which must be the next header of the chain
+    pub fn new_job(
+        &self,
+        header: Header,
+        n_transactions: u64,
+        lch: &HeaderChain,
+    ) -> Result<PoolJob, EbxError> {
+        let mut header = header.to_working_header();
+        header.work_par_algo = Self::WORK_PAR_ALGO_POW5;
+        let work_pack = WorkPack::from_header_chain(header, n_transactions, lch);
+        if !work_pack.is_valid_in(lch) {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "block template does not build on the tip".to_string(),
+            });
+        }
+        let share_target = Self::share_target(work_pack.header.target, self.share_difficulty);
+
+        let mut state = self.state.lock().unwrap();
+        Self::remove_stale_jobs_locked(&mut state, lch);
+        let job = PoolJob {
+            job_id: state.next_job_id,
+            work_pack,
+            share_target,
+        };
+        state.next_job_id += 1;
+        state.jobs.insert(job.job_id, job.clone());
+        Ok(job)
+    }
+
+    pub fn job(&self, job_id: u64) -> Option<PoolJob> {
+        self.state.lock().unwrap().jobs.get(&job_id).cloned()
+    }
+
+    pub fn n_jobs(&self) -> usize {
+        self.state.lock().unwrap().jobs.len()
+    }
+
+    // forgets the jobs that do not build on the tip of the chain, along with
+    // their nonces. returns the number of jobs removed.
+    pub fn remove_stale_jobs(&self, lch: &HeaderChain) -> usize {
+        let mut state = self.state.lock().unwrap();
+        Self::remove_stale_jobs_locked(&mut state, lch)
+    }
+
+    fn remove_stale_jobs_locked(state: &mut PoolState, lch: &HeaderChain) -> usize {
+        let n_jobs = state.jobs.len();
+        state.jobs.retain(|_, job| job.work_pack.is_valid_in(lch));
+        let jobs = &state.jobs;
+        state.nonces.retain(|(job_id, _)| jobs.contains_key(job_id));
+        n_jobs - state.jobs.len()
+    }
+
+    // checks a nonce found by a miner and credits the miner with the share
+    pub fn submit_share(
+        &self,
+        lch: &HeaderChain,
+        job_id: u64,
+        pub_key: &PubKey,
+        nonce: u256,
+    ) -> Result<Share, EbxError> {
+        let err = |message: &str| EbxError::GenericError {
+            source: None,
+            message: message.to_string(),
+        };
+        let job = match self.job(job_id) {
+            Some(job) => job,
+            None => return Err(err("unknown job")),
+        };
+        if !job.work_pack.is_valid_in(lch) {
+            self.remove_stale_jobs(lch);
+            return Err(err("stale job"));
+        }
+        // the work is done before taking the lock, so that miners can be
+        // checked in parallel
+        let work_pack = &job.work_pack;
+        let header = Self::header_with_nonce(&work_pack.header, work_pack.n_transactions, nonce)?;
+        let id_num = u256::from_be_slice(&header.id()).unwrap();
+        if id_num >= job.share_target {
+            return Err(err("share does not meet the share target"));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if !state.jobs.contains_key(&job_id) {
+            return Err(err("stale job"));
+        }
+        if !state.nonces.insert((job_id, nonce)) {
+            return Err(err("duplicate share"));
+        }
+        let shares = Self::share_value(job.share_target);
+        let credit = state
+            .shares
+            .entry(pub_key.buf)
+            .or_insert_with(|| (pub_key.clone(), 0));
+        credit.1 = credit.1.saturating_add(shares);
+        Ok(Share {
+            job_id,
+            pub_key: pub_key.clone(),
+            is_block: header.is_id_valid(),
+            header,
+            shares,
+        })
+    }
+
+    pub fn shares(&self, pub_key: &PubKey) -> u64 {
+        let state = self.state.lock().unwrap();
+        state.shares.get(&pub_key.buf).map_or(0, |credit| credit.1)
+    }
+
+    // splits the amount between the miners by the shares credited since the
+    // last payout, and starts counting shares again from zero
+    pub fn take_payout_ledger(&self, amount: EbxValue) -> Result<PayoutLedger, EbxError> {
+        let mut state = self.state.lock().unwrap();
+        let shares = state.shares.values().cloned().collect();
+        let payout_ledger = PayoutLedger::new(amount, shares)?;
+        state.shares.clear();
+        Ok(payout_ledger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buf::EbxBuf;
+    use crate::buf_reader::BufReader;
+    use crate::chain_params::ChainParams;
+    use crate::key_pair::KeyPair;
+
+    fn regtest_chain(n_headers: u32) -> HeaderChain {
+        let mut lch = HeaderChain::from_params(ChainParams::regtest());
+        for _ in 0..n_headers {
+            let header = next_header(&lch);
+            lch.add(header);
+        }
+        lch
+    }
+
+    // a template with a block target 256 times harder than the max
+    fn next_header(lch: &HeaderChain) -> Header {
+        let mut header = Header::from_genesis(0);
+        header.block_num = lch.headers.len() as u32;
+        if let Some(tip) = lch.get_tip() {
+            header.prev_block_id = tip.id();
+        }
+        header.target = Header::target_from_difficulty(u256::from(256u32));
+        header
+    }
+
+    // the first nonce from the given one whose header is a share but not a
+    // block, or is not a share at all
+    fn find_nonce(job: &PoolJob, start: u32, is_share: bool) -> u32 {
+        (start..)
+            .find(|&nonce| {
+                let work_pack = &job.work_pack;
+                let header = Pool::header_with_nonce(
+                    &work_pack.header,
+                    work_pack.n_transactions,
+                    u256::from(nonce),
+                )
+                .unwrap();
+                let id_num = u256::from_be_slice(&header.id()).unwrap();
+                match is_share {
+                    true => id_num < job.share_target && !header.is_id_valid(),
+                    false => id_num >= job.share_target,
+                }
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_share_target() {
+        let max_target = Header::target_from_difficulty(u256::from(1u8));
+        let target = Header::target_from_difficulty(u256::from(256u32));
+        assert_eq!(
+            Pool::share_target(target, 4),
+            Header::target_from_difficulty(u256::from(4u8))
+        );
+        assert_eq!(Pool::share_target(target, 0), max_target);
+        // a share is never harder than a block
+        assert_eq!(Pool::share_target(target, 1_000), target);
+        assert_eq!(Pool::share_value(Pool::share_target(target, 4)), 4);
+        assert_eq!(Pool::share_value(max_target), 1);
+    }
+
+    #[test]
+    fn test_work_par_hash() {
+        // the same vectors as the pow5 tests, for headers of all zeroes and all
+        // 0x11 bytes
+        let mut br = BufReader::new(vec![0; Header::WIRE_SIZE]);
+        let (header, n_transactions) = Header::from_wire_buf_reader(&mut br).unwrap();
+        assert_eq!(
+            Pool::work_par_hash(&header, n_transactions).to_strict_hex(),
+            "6fe9eddc39bb4183c44853c41876801be94a138ea9adea89f40a08442d2f79b8"
+        );
+        let mut br = BufReader::new(vec![0x11; Header::WIRE_SIZE]);
+        let (header, n_transactions) = Header::from_wire_buf_reader(&mut br).unwrap();
+        assert_eq!(
+            Pool::work_par_hash(&header, n_transactions).to_strict_hex(),
+            "09d125453a1a5e9f75c770e3580e8b8035069b39816036b38207e8e152fa6871"
+        );
+
+        // a u32 nonce goes where pow5 inserts it
+        let mut br = BufReader::new(vec![0; Header::WIRE_SIZE]);
+        let (mut header, n_transactions) = Header::from_wire_buf_reader(&mut br).unwrap();
+        header.nonce = u256::from(376413u32) << 224;
+        let buf = earthbucks_pow5::insert_nonce(vec![0; Header::WIRE_SIZE], 376413).unwrap();
+        assert_eq!(header.to_wire_buf(n_transactions).to_vec(), buf);
+    }
+
+    #[test]
+    fn test_header_with_nonce() {
+        let lch = regtest_chain(1);
+        let mut header = next_header(&lch);
+        header.work_ser_algo = WorkSerAlgo::Blake3_3.to_u16();
+        let header = Pool::header_with_nonce(&header, 3, u256::from(7u8)).unwrap();
+        assert_eq!(header.nonce, u256::from(7u8));
+        assert!(header.is_work_ser_hash_valid());
+        let work_par_hash = Pool::work_par_hash(&header.to_working_header(), 3);
+        assert_eq!(header.work_par_hash, work_par_hash);
+        assert_ne!(
+            header.work_par_hash,
+            Pool::work_par_hash(&header.to_working_header(), 4)
+        );
+    }
+
+    #[test]
+    fn test_submit_share() {
+        let lch = regtest_chain(2);
+        let pool = Pool::new(4);
+        let job = pool.new_job(next_header(&lch), 1, &lch).unwrap();
+        assert_eq!(job.work_pack.header.work_par_algo, Pool::WORK_PAR_ALGO_POW5);
+        let pub_key = KeyPair::from_random().pub_key;
+
+        let nonce = u256::from(find_nonce(&job, 0, true));
+        let share = pool
+            .submit_share(&lch, job.job_id, &pub_key, nonce)
+            .unwrap();
+        assert_eq!(share.shares, 4);
+        assert!(!share.is_block);
+        assert_eq!(pool.shares(&pub_key), 4);
+
+        let err = pool
+            .submit_share(&lch, job.job_id, &pub_key, nonce)
+            .unwrap_err();
+        assert!(err.to_string().contains("duplicate share"));
+
+        let nonce = u256::from(find_nonce(&job, 0, false));
+        let err = pool
+            .submit_share(&lch, job.job_id, &pub_key, nonce)
+            .unwrap_err();
+        assert!(err.to_string().contains("share target"));
+
+        let err = pool
+            .submit_share(&lch, job.job_id + 1, &pub_key, nonce)
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown job"));
+        assert_eq!(pool.shares(&pub_key), 4);
+    }
+
+    #[test]
+    fn test_stale_jobs() {
+        let mut lch = regtest_chain(2);
+        let pool = Pool::new(4);
+        let template = next_header(&lch);
+        let job = pool.new_job(template.clone(), 1, &lch).unwrap();
+        let pub_key = KeyPair::from_random().pub_key;
+        let nonce = u256::from(find_nonce(&job, 0, true));
+
+        // the tip changes before the share is submitted
+        lch.add(next_header(&lch));
+        let err = pool
+            .submit_share(&lch, job.job_id, &pub_key, nonce)
+            .unwrap_err();
+        assert!(err.to_string().contains("stale job"));
+        assert_eq!(pool.n_jobs(), 0);
+        assert!(pool.new_job(template, 1, &lch).is_err());
+
+        let job1 = pool.new_job(next_header(&lch), 1, &lch).unwrap();
+        let job2 = pool.new_job(next_header(&lch), 1, &lch).unwrap();
+        assert_ne!(job1.job_id, job2.job_id);
+        assert_eq!(pool.n_jobs(), 2);
+        assert_eq!(pool.remove_stale_jobs(&lch), 0);
+        lch.add(next_header(&lch));
+        assert_eq!(pool.remove_stale_jobs(&lch), 2);
+        assert!(pool.job(job1.job_id).is_none());
+    }
+
+    #[test]
+    fn test_take_payout_ledger() {
+        let lch = regtest_chain(1);
+        let pool = Pool::new(4);
+        let job = pool.new_job(next_header(&lch), 1, &lch).unwrap();
+        let pub_key1 = KeyPair::from_random().pub_key;
+        let pub_key2 = KeyPair::from_random().pub_key;
+        let mut nonce = 0;
+        for pub_key in [&pub_key1, &pub_key1, &pub_key1, &pub_key2] {
+            nonce = find_nonce(&job, nonce, true);
+            let share = pool.submit_share(&lch, job.job_id, pub_key, u256::from(nonce));
+            assert!(share.is_ok());
+            nonce += 1;
+        }
+        assert_eq!(pool.shares(&pub_key1), 12);
+        assert_eq!(pool.shares(&pub_key2), 4);
+
+        let amount = EbxValue::new(lch.params.coinbase_amount(1));
+        let payout_ledger = pool.take_payout_ledger(amount).unwrap();
+        assert_eq!(payout_ledger.payouts.len(), 2);
+        for payout in &payout_ledger.payouts {
+            let expected = amount.value / 16 * payout.shares;
+            assert_eq!(payout.value.value, expected);
+        }
+        let tx_outs = payout_ledger.to_tx_outs();
+        assert!(lch
+            .get_next_coinbase_tx_from_tx_outs(tx_outs, "example.com")
+            .is_ok());
+
+        // the shares start again from zero
+        assert_eq!(pool.shares(&pub_key1), 0);
+        assert!(pool.take_payout_ledger(amount).is_err());
+    }
+}
@@ -16,6 +16,8 @@ impl Script {
         Self { chunks }
     }
 
+    pub const MAX_MULTI_SIG_KEYS: usize = 16;
+
     pub fn from_empty() -> Self {
         Self::new(Vec::new())
     }
@@ -516,6 +518,29 @@ impl Script {
         true
     }
 
+    // the number of signature checks this script can perform. a multisig
+    // counts its number of keys if that is pushed right before it, and the
+    // maximum number of keys otherwise.
+    pub fn sig_op_count(&self) -> usize {
+        let mut count = 0;
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            match chunk.opcode {
+                Opcode::OP_CHECKSIG | Opcode::OP_CHECKSIGVERIFY => count += 1,
+                Opcode::OP_CHECKMULTISIG | Opcode::OP_CHECKMULTISIGVERIFY => {
+                    count += match i.checked_sub(1).map(|j| self.chunks[j].opcode) {
+                        Some(Opcode::OP_0) => 0,
+                        Some(opcode @ Opcode::OP_1..=Opcode::OP_16) => {
+                            (opcode - Opcode::OP_1 + 1) as usize
+                        }
+                        _ => Script::MAX_MULTI_SIG_KEYS,
+                    }
+                }
+                _ => {}
+            }
+        }
+        count
+    }
+
     pub fn is_coinbase_input(&self) -> bool {
         // TODO: Add more checks
         self.is_push_only()
@@ -626,6 +651,18 @@ mod tests {
         assert_eq!(script.unwrap().to_strict_str().unwrap(), new_string);
     }
 
+    #[test]
+    fn test_sig_op_count() {
+        assert_eq!(Script::from_empty().sig_op_count(), 0);
+        assert_eq!(Script::from_pkh_output(&[0; 32]).sig_op_count(), 1);
+        let pub_keys = vec![vec![0; PubKey::SIZE]; 3];
+        assert_eq!(Script::from_multi_sig_output(2, pub_keys).sig_op_count(), 3);
+        let script = Script::from_strict_str("CHECKMULTISIG CHECKSIGVERIFY").unwrap();
+        assert_eq!(script.sig_op_count(), Script::MAX_MULTI_SIG_KEYS + 1);
+        let script = Script::from_strict_str("0 CHECKMULTISIGVERIFY").unwrap();
+        assert_eq!(script.sig_op_count(), 0);
+    }
+
     #[test]
     fn test_is_pkh_output() {
         let mut script = Script::from_empty();
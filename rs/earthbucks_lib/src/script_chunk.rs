@@ -78,27 +78,24 @@ impl ScriptChunk {
     pub fn to_buf(&self) -> Vec<u8> {
         let mut result = Vec::new();
         result.push(self.opcode);
-        match &self.buffer {
-            Some(buffer) => {
-                let len = buffer.len();
-                if self.opcode == Opcode::OP_PUSHDATA1 {
-                    let mut writer = BufWriter::new();
-                    writer.write_u8(len as u8);
-                    result.extend_from_slice(&writer.to_buf());
-                    result.extend_from_slice(buffer);
-                } else if self.opcode == Opcode::OP_PUSHDATA2 {
-                    let mut writer = BufWriter::new();
-                    writer.write_u16_be(len as u16);
-                    result.extend_from_slice(&writer.to_buf());
-                    result.extend_from_slice(buffer);
-                } else if self.opcode == Opcode::OP_PUSHDATA4 {
-                    let mut writer = BufWriter::new();
-                    writer.write_u32_be(len as u32);
-                    result.extend_from_slice(&writer.to_buf());
-                    result.extend_from_slice(buffer);
-                }
+        if let Some(buffer) = &self.buffer {
+            let len = buffer.len();
+            if self.opcode == Opcode::OP_PUSHDATA1 {
+                let mut writer = BufWriter::new();
+                writer.write_u8(len as u8);
+                result.extend_from_slice(&writer.to_buf());
+                result.extend_from_slice(buffer);
+            } else if self.opcode == Opcode::OP_PUSHDATA2 {
+                let mut writer = BufWriter::new();
+                writer.write_u16_be(len as u16);
+                result.extend_from_slice(&writer.to_buf());
+                result.extend_from_slice(buffer);
+            } else if self.opcode == Opcode::OP_PUSHDATA4 {
+                let mut writer = BufWriter::new();
+                writer.write_u32_be(len as u32);
+                result.extend_from_slice(&writer.to_buf());
+                result.extend_from_slice(buffer);
             }
-            None => (),
         }
         result
     }
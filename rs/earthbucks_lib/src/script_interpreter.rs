@@ -3,6 +3,7 @@ use crate::opcode::{Opcode, OP};
 use crate::pub_key::PubKey;
 use crate::script::Script;
 use crate::script_num::ScriptNum;
+use crate::sig_cache::SigCache;
 use crate::tx::{HashCache, Tx};
 use crate::tx_signature::TxSignature;
 use num_bigint::{BigInt, ToBigInt};
@@ -10,7 +11,7 @@ use num_traits::ToPrimitive;
 
 pub struct ScriptInterpreter<'a> {
     pub script: Script,
-    pub tx: Tx,
+    pub tx: &'a Tx,
     pub n_in: usize,
     pub stack: Vec<Vec<u8>>,
     pub alt_stack: Vec<Vec<u8>>,
@@ -22,12 +23,13 @@ pub struct ScriptInterpreter<'a> {
     pub err_str: String,
     pub value: u64,
     pub hash_cache: &'a mut HashCache,
+    pub sig_cache: Option<&'a SigCache>,
 }
 
 impl<'a> ScriptInterpreter<'a> {
     pub fn from_script_tx(
         script: Script,
-        tx: Tx,
+        tx: &'a Tx,
         n_in: usize,
         hash_cache: &'a mut HashCache,
     ) -> Self {
@@ -45,12 +47,13 @@ impl<'a> ScriptInterpreter<'a> {
             err_str: "".to_string(),
             value: 0,
             hash_cache,
+            sig_cache: None,
         }
     }
 
     pub fn from_output_script_tx(
         script: Script,
-        tx: Tx,
+        tx: &'a Tx,
         n_in: usize,
         stack: Vec<Vec<u8>>,
         value: u64,
@@ -70,9 +73,26 @@ impl<'a> ScriptInterpreter<'a> {
             err_str: "".to_string(),
             value,
             hash_cache,
+            sig_cache: None,
         }
     }
 
+    fn verify_sig(
+        &mut self,
+        public_key: [u8; PubKey::SIZE],
+        signature: TxSignature,
+        script: Vec<u8>,
+    ) -> bool {
+        let digest = self.tx.sighash_with_cache(
+            self.n_in,
+            script,
+            self.value,
+            signature.hash_type,
+            self.hash_cache,
+        );
+        Tx::verify_sighash(digest, public_key, &signature, self.sig_cache)
+    }
+
     pub fn cast_to_bool(buf: &[u8]) -> bool {
         !buf.iter().all(|&x| x == 0)
     }
@@ -949,14 +969,7 @@ impl<'a> ScriptInterpreter<'a> {
                             )
                         });
 
-                    let success = self.tx.verify_with_cache(
-                        self.n_in,
-                        pub_key_arr,
-                        signature.unwrap(),
-                        exec_script_buf,
-                        self.value,
-                        self.hash_cache,
-                    );
+                    let success = self.verify_sig(pub_key_arr, signature.unwrap(), exec_script_buf);
 
                     self.stack.push(if success { vec![1] } else { vec![] });
                     if opcode == OP["CHECKSIGVERIFY"] && !success {
@@ -1010,13 +1023,10 @@ impl<'a> ScriptInterpreter<'a> {
                     let mut matched_sigs = 0;
                     for sig in sigs {
                         for j in 0..pub_keys.len() {
-                            let success = self.tx.verify_with_cache(
-                                self.n_in,
+                            let success = self.verify_sig(
                                 pub_keys[j][..PubKey::SIZE].try_into().unwrap(),
                                 TxSignature::from_buf(sig.clone()).unwrap(),
                                 exec_script_buf.clone(),
-                                self.value,
-                                self.hash_cache,
                             );
                             if success {
                                 matched_sigs += 1;
@@ -1043,6 +1053,15 @@ impl<'a> ScriptInterpreter<'a> {
                         self.err_str = "negative lockabs".to_string();
                         break;
                     }
+                    // a lock by block number is not met by a lock by time, nor
+                    // the other way around
+                    let threshold = Tx::LOCK_ABS_TIME_THRESHOLD.to_bigint().unwrap();
+                    if (self.tx.lock_abs >= Tx::LOCK_ABS_TIME_THRESHOLD)
+                        != (script_num.num >= threshold)
+                    {
+                        self.err_str = "lockabs type mismatch".to_string();
+                        break;
+                    }
                     if self.tx.lock_abs.to_bigint().unwrap() < script_num.num {
                         self.err_str = "lockabs requirement not met".to_string();
                         break;
@@ -1113,7 +1132,7 @@ mod tests {
             let script = Script::from_strict_str("0").unwrap();
             let mut hash_cache = HashCache::new();
             let mut script_interpreter =
-                ScriptInterpreter::from_script_tx(script, tx, 0, &mut hash_cache);
+                ScriptInterpreter::from_script_tx(script, &tx, 0, &mut hash_cache);
             script_interpreter.eval_script();
             assert_eq!(script_interpreter.return_success, Some(false));
             assert_eq!(hex::encode(script_interpreter.return_value.unwrap()), "");
@@ -1125,7 +1144,7 @@ mod tests {
             let script = Script::from_strict_str("0xff").unwrap();
             let mut hash_cache = HashCache::new();
             let mut script_interpreter =
-                ScriptInterpreter::from_script_tx(script, tx, 0, &mut hash_cache);
+                ScriptInterpreter::from_script_tx(script, &tx, 0, &mut hash_cache);
             script_interpreter.eval_script();
             assert_eq!(script_interpreter.return_success, Some(true));
             assert!(script_interpreter.return_value.is_some());
@@ -1139,7 +1158,7 @@ mod tests {
 
             let mut hash_cache = HashCache::new();
             let mut script_interpreter =
-                ScriptInterpreter::from_script_tx(script, tx, 0, &mut hash_cache);
+                ScriptInterpreter::from_script_tx(script, &tx, 0, &mut hash_cache);
             script_interpreter.eval_script();
             assert_eq!(script_interpreter.return_success, Some(true));
             assert!(script_interpreter.return_value.is_some());
@@ -1155,7 +1174,7 @@ mod tests {
             let script = Script::from_strict_str(&("0x".to_owned() + &"ff".repeat(65536))).unwrap();
             let mut hash_cache = HashCache::new();
             let mut script_interpreter =
-                ScriptInterpreter::from_script_tx(script, tx, 0, &mut hash_cache);
+                ScriptInterpreter::from_script_tx(script, &tx, 0, &mut hash_cache);
             script_interpreter.eval_script();
             assert_eq!(script_interpreter.return_success, Some(true));
             assert!(script_interpreter.return_value.is_some());
@@ -1171,13 +1190,36 @@ mod tests {
             let script = Script::from_strict_str("1NEGATE").unwrap();
             let mut hash_cache = HashCache::new();
             let mut script_interpreter =
-                ScriptInterpreter::from_script_tx(script, tx, 0, &mut hash_cache);
+                ScriptInterpreter::from_script_tx(script, &tx, 0, &mut hash_cache);
             script_interpreter.eval_script();
             assert_eq!(script_interpreter.return_success, Some(true));
             assert!(script_interpreter.return_value.is_some());
             assert_eq!(hex::encode(script_interpreter.return_value.unwrap()), "ff");
         }
 
+        #[test]
+        fn test_checklockabsverify_type() {
+            let eval = |tx_lock_abs: u32, script_lock_abs: u32| {
+                let tx = Tx::new(0, Vec::new(), Vec::new(), tx_lock_abs);
+                let num = ScriptNum::from_u32(script_lock_abs).to_buf();
+                let script =
+                    Script::from_strict_str(&format!("0x{} CHECKLOCKABSVERIFY", hex::encode(num)))
+                        .unwrap();
+                let mut hash_cache = HashCache::new();
+                let mut script_interpreter =
+                    ScriptInterpreter::from_script_tx(script, &tx, 0, &mut hash_cache);
+                script_interpreter.eval_script();
+                script_interpreter.err_str
+            };
+            let time = Tx::LOCK_ABS_TIME_THRESHOLD;
+            assert_eq!(eval(100, 100), "");
+            assert_eq!(eval(time + 1, time), "");
+            // a lock by time never meets a lock by block number, or the reverse
+            assert_eq!(eval(time, 100), "lockabs type mismatch");
+            assert_eq!(eval(100, time), "lockabs type mismatch");
+            assert_eq!(eval(time, time + 1), "lockabs requirement not met");
+        }
+
         #[test]
         fn test_checksig() {
             let output_priv_key_hex =
@@ -1198,7 +1240,7 @@ mod tests {
             let output_tx_id = [0; 32];
             let output_tx_index = 0;
 
-            let mut tx = Tx::new(
+            let tx = Tx::new(
                 1,
                 vec![TxIn::new(
                     output_tx_id,
@@ -1223,7 +1265,7 @@ mod tests {
 
             let mut script_interpreter = ScriptInterpreter::from_output_script_tx(
                 output_script,
-                tx,
+                &tx,
                 0,
                 stack,
                 output_amount,
@@ -1273,7 +1315,7 @@ mod tests {
             let output_tx_index = 0;
 
             // Create a tx
-            let mut tx = Tx::new(
+            let tx = Tx::new(
                 1,
                 vec![TxIn::new(
                     output_tx_id,
@@ -1308,7 +1350,7 @@ mod tests {
             // Create a script interpreter
             let mut script_interpreter = ScriptInterpreter::from_output_script_tx(
                 output_script,
-                tx,
+                &tx,
                 0,
                 stack,
                 output_amount,
@@ -1363,7 +1405,7 @@ mod tests {
                 );
                 let mut hash_cache = HashCache::new();
                 let mut script_interpreter =
-                    ScriptInterpreter::from_script_tx(script, tx, 0, &mut hash_cache);
+                    ScriptInterpreter::from_script_tx(script, &tx, 0, &mut hash_cache);
                 script_interpreter.eval_script();
                 assert_eq!(
                     script_interpreter.err_str, test_script.expected_error,
@@ -1377,7 +1419,7 @@ mod tests {
                     test_script.name
                 );
                 assert_eq!(
-                    hex::encode(&script_interpreter.return_value.unwrap()),
+                    hex::encode(script_interpreter.return_value.unwrap()),
                     test_script.expected_return_value,
                     "Test '{}' failed on return value",
                     test_script.name
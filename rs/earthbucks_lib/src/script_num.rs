@@ -63,7 +63,7 @@ impl ScriptNum {
             }
             std::cmp::Ordering::Less => {
                 let bit_length = self.num.bits();
-                let byte_length = (bit_length + 7) / 8;
+                let byte_length = bit_length.div_ceil(8);
                 (BigInt::from(2).pow((byte_length * 8) as u32) + &self.num)
                     .to_bytes_be()
                     .1
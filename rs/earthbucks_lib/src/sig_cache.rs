@@ -0,0 +1,125 @@
+use crate::hash::blake3_hash;
+use crate::pub_key::PubKey;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+
+// cache of signatures that have already been verified, so that a tx accepted
+// into the mempool does not pay the full secp256k1 cost again when the block
+// containing it arrives. only successful verifications are cached. the cache
+// is bounded: once full, the oldest entries are evicted first.
+#[derive(Debug)]
+pub struct SigCache {
+    max_size: usize,
+    entries: Mutex<SigCacheEntries>,
+}
+
+#[derive(Debug, Default)]
+struct SigCacheEntries {
+    set: HashSet<[u8; 32]>,
+    order: VecDeque<[u8; 32]>,
+}
+
+impl SigCache {
+    pub const DEFAULT_MAX_SIZE: usize = 100_000;
+
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            entries: Mutex::new(SigCacheEntries::default()),
+        }
+    }
+
+    pub fn key(sighash: &[u8; 32], pub_key: &[u8; PubKey::SIZE], sig: &[u8]) -> [u8; 32] {
+        let mut data = Vec::with_capacity(32 + PubKey::SIZE + sig.len());
+        data.extend_from_slice(sighash);
+        data.extend_from_slice(pub_key);
+        data.extend_from_slice(sig);
+        blake3_hash(&data)
+    }
+
+    pub fn contains(&self, sighash: &[u8; 32], pub_key: &[u8; PubKey::SIZE], sig: &[u8]) -> bool {
+        let key = Self::key(sighash, pub_key, sig);
+        self.entries.lock().unwrap().set.contains(&key)
+    }
+
+    pub fn insert(&self, sighash: &[u8; 32], pub_key: &[u8; PubKey::SIZE], sig: &[u8]) {
+        if self.max_size == 0 {
+            return;
+        }
+        let key = Self::key(sighash, pub_key, sig);
+        let mut entries = self.entries.lock().unwrap();
+        if !entries.set.insert(key) {
+            return;
+        }
+        entries.order.push_back(key);
+        while entries.order.len() > self.max_size {
+            let oldest = entries.order.pop_front().unwrap();
+            entries.set.remove(&oldest);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.set.clear();
+        entries.order.clear();
+    }
+}
+
+impl Default for SigCache {
+    fn default() -> Self {
+        Self::new(SigCache::DEFAULT_MAX_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_contains() {
+        let sig_cache = SigCache::new(10);
+        let sighash = [1; 32];
+        let pub_key = [2; PubKey::SIZE];
+        let sig = [3; 65];
+        assert!(!sig_cache.contains(&sighash, &pub_key, &sig));
+        sig_cache.insert(&sighash, &pub_key, &sig);
+        assert!(sig_cache.contains(&sighash, &pub_key, &sig));
+        assert!(!sig_cache.contains(&[0; 32], &pub_key, &sig));
+        assert!(!sig_cache.contains(&sighash, &[0; PubKey::SIZE], &sig));
+        assert!(!sig_cache.contains(&sighash, &pub_key, &[0; 65]));
+        sig_cache.insert(&sighash, &pub_key, &sig);
+        assert_eq!(sig_cache.len(), 1);
+    }
+
+    #[test]
+    fn test_evicts_oldest() {
+        let sig_cache = SigCache::new(3);
+        let pub_key = [2; PubKey::SIZE];
+        let sig = [3; 65];
+        for i in 0..5u8 {
+            sig_cache.insert(&[i; 32], &pub_key, &sig);
+        }
+        assert_eq!(sig_cache.len(), 3);
+        assert!(!sig_cache.contains(&[0; 32], &pub_key, &sig));
+        assert!(!sig_cache.contains(&[1; 32], &pub_key, &sig));
+        assert!(sig_cache.contains(&[2; 32], &pub_key, &sig));
+        assert!(sig_cache.contains(&[4; 32], &pub_key, &sig));
+    }
+
+    #[test]
+    fn test_clear() {
+        let sig_cache = SigCache::default();
+        sig_cache.insert(&[1; 32], &[2; PubKey::SIZE], &[3; 65]);
+        assert!(!sig_cache.is_empty());
+        sig_cache.clear();
+        assert!(sig_cache.is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::buf::EbxBuf;
+use crate::buf_reader::BufReader;
+use crate::buf_writer::BufWriter;
+use crate::ecdsa;
+use crate::error::EbxError;
+use crate::hash::{blake3_hash, blake3_mac};
+use crate::priv_key::PrivKey;
+use crate::pub_key::PubKey;
+
+// a message signed for one purpose only. the signature covers a blake3 mac of
+// the message keyed by the hash of the "key string", which names the purpose,
+// e.g. "signin challenge", so that a signature made for one purpose can never
+// be replayed for another. the key string is not serialized: the verifier
+// must already know what it expects. the same as SignedMessage in the
+// typescript library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedMessage {
+    pub sig: [u8; 64],
+    pub pub_key: [u8; PubKey::SIZE],
+    pub mac: [u8; 32],
+    pub message: Vec<u8>,
+    pub key_str: String,
+}
+
+impl SignedMessage {
+    pub fn new(
+        sig: [u8; 64],
+        pub_key: [u8; PubKey::SIZE],
+        mac: [u8; 32],
+        message: Vec<u8>,
+        key_str: String,
+    ) -> Self {
+        Self {
+            sig,
+            pub_key,
+            mac,
+            message,
+            key_str,
+        }
+    }
+
+    pub fn create_mac(message: &[u8], key_str: &str) -> [u8; 32] {
+        let key = blake3_hash(key_str.as_bytes());
+        blake3_mac(&key, message)
+    }
+
+    pub fn from_sign_message(
+        priv_key: &PrivKey,
+        message: Vec<u8>,
+        key_str: &str,
+    ) -> Result<Self, EbxError> {
+        let pub_key = priv_key.to_pub_key_buffer()?;
+        let mac = Self::create_mac(&message, key_str);
+        let sig = ecdsa::sign(&mac, priv_key.clone());
+        Ok(Self::new(sig, pub_key, mac, message, key_str.to_string()))
+    }
+
+    pub fn is_valid(&self, pub_key: &PubKey, key_str: &str) -> bool {
+        if key_str != self.key_str {
+            return false;
+        }
+        let mac = Self::create_mac(&self.message, &self.key_str);
+        if mac != self.mac {
+            return false;
+        }
+        if pub_key.buf != self.pub_key {
+            return false;
+        }
+        ecdsa::verify(&mac, &self.sig, &self.pub_key)
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut writer = BufWriter::new();
+        writer.write(self.sig.to_vec());
+        writer.write(self.pub_key.to_vec());
+        writer.write(self.mac.to_vec());
+        writer.write(self.message.clone());
+        writer.to_buf()
+    }
+
+    pub fn from_buf(buf: Vec<u8>, key_str: &str) -> Result<Self, EbxError> {
+        let mut reader = BufReader::new(buf);
+        let sig: [u8; 64] = reader.read(64)?.try_into().unwrap();
+        let pub_key: [u8; PubKey::SIZE] = reader.read(PubKey::SIZE)?.try_into().unwrap();
+        let mac: [u8; 32] = reader.read(32)?.try_into().unwrap();
+        let message = reader.read_remainder();
+        Ok(Self::new(sig, pub_key, mac, message, key_str.to_string()))
+    }
+
+    pub fn to_strict_hex(&self) -> String {
+        self.to_buf().to_strict_hex()
+    }
+
+    pub fn from_strict_hex(hex: &str, key_str: &str) -> Result<Self, EbxError> {
+        Self::from_buf(Vec::<u8>::from_strict_hex(hex)?, key_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::fs;
+
+    #[derive(Deserialize)]
+    struct SignedMessageVectors {
+        signed_message: Vec<SignedMessageVector>,
+    }
+
+    #[derive(Deserialize)]
+    struct SignedMessageVector {
+        priv_key: String,
+        message: String,
+        key_str: String,
+        signed_message: String,
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let priv_key = PrivKey::from_random();
+        let pub_key = PubKey::from_priv_key(&priv_key).unwrap();
+        let message = b"message".to_vec();
+        let key_str = "signed message";
+        let signed_message = SignedMessage::from_sign_message(&priv_key, message, key_str).unwrap();
+        assert!(signed_message.is_valid(&pub_key, key_str));
+
+        // the wrong purpose, or the wrong signer
+        assert!(!signed_message.is_valid(&pub_key, "other purpose"));
+        let other_pub_key = PubKey::from_priv_key(&PrivKey::from_random()).unwrap();
+        assert!(!signed_message.is_valid(&other_pub_key, key_str));
+
+        // a tampered message no longer matches its mac
+        let mut tampered = signed_message.clone();
+        tampered.message = b"massage".to_vec();
+        assert!(!tampered.is_valid(&pub_key, key_str));
+
+        // a message read with a different key string is not valid
+        let buf = signed_message.to_buf();
+        let signed_message2 = SignedMessage::from_buf(buf.clone(), key_str).unwrap();
+        assert_eq!(signed_message2, signed_message);
+        let signed_message3 = SignedMessage::from_buf(buf, "other purpose").unwrap();
+        assert!(!signed_message3.is_valid(&pub_key, "other purpose"));
+    }
+
+    #[test]
+    fn test_from_buf_not_enough_data() {
+        assert!(SignedMessage::from_buf(vec![0; 64 + 33 + 31], "key").is_err());
+        let signed_message = SignedMessage::from_buf(vec![0; 64 + 33 + 32], "key").unwrap();
+        assert!(signed_message.message.is_empty());
+    }
+
+    #[test]
+    fn test_vectors_signed_message() {
+        let data =
+            fs::read_to_string("./test_vectors/signed_message.json").expect("Unable to read file");
+        let test_vectors: SignedMessageVectors =
+            serde_json::from_str(&data).expect("Unable to parse JSON");
+        for test_vector in test_vectors.signed_message {
+            let priv_key = PrivKey::from_strict_str(&test_vector.priv_key).unwrap();
+            let pub_key = PubKey::from_priv_key(&priv_key).unwrap();
+            let message = Vec::<u8>::from_strict_hex(&test_vector.message).unwrap();
+            let key_str = &test_vector.key_str;
+            let signed_message =
+                SignedMessage::from_sign_message(&priv_key, message, key_str).unwrap();
+            assert_eq!(signed_message.to_strict_hex(), test_vector.signed_message);
+            let signed_message =
+                SignedMessage::from_strict_hex(&test_vector.signed_message, key_str).unwrap();
+            assert!(signed_message.is_valid(&pub_key, key_str));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::buf::EbxBuf;
+use crate::clock::Clock;
+use crate::domain::Domain;
+use crate::domain_priv_key::DomainPrivKey;
+use crate::domain_pub_key::DomainPubKey;
+use crate::error::EbxError;
+use crate::signed_message::SignedMessage;
+use crate::work_data::WorkData;
+
+// work data signed by a mine's domain key, so that a pool miner knows the work
+// came from the mine. the same as SignedWorkData in the typescript library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedWorkData {
+    pub signed_message: SignedMessage,
+}
+
+impl SignedWorkData {
+    pub fn new(signed_message: SignedMessage) -> Self {
+        Self { signed_message }
+    }
+
+    pub fn signed_work_data_key_string(domain: &Domain) -> String {
+        format!("signed work data for {}", domain)
+    }
+
+    pub fn from_work_data(
+        domain_priv_key: &DomainPrivKey,
+        work_data: &WorkData,
+    ) -> Result<Self, EbxError> {
+        let key_str = Self::signed_work_data_key_string(&domain_priv_key.domain);
+        let message = work_data.to_buf();
+        let signed_message =
+            SignedMessage::from_sign_message(&domain_priv_key.priv_key, message, &key_str)?;
+        Ok(Self::new(signed_message))
+    }
+
+    pub fn to_work_data(&self) -> Result<WorkData, EbxError> {
+        WorkData::from_buf(self.signed_message.message.clone())
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        self.signed_message.to_buf()
+    }
+
+    pub fn from_buf(buf: Vec<u8>, domain: &Domain) -> Result<Self, EbxError> {
+        let key_str = Self::signed_work_data_key_string(domain);
+        let signed_message = SignedMessage::from_buf(buf, &key_str)?;
+        Ok(Self::new(signed_message))
+    }
+
+    pub fn to_strict_hex(&self) -> String {
+        self.to_buf().to_strict_hex()
+    }
+
+    pub fn from_strict_hex(hex: &str, domain: &Domain) -> Result<Self, EbxError> {
+        Self::from_buf(Vec::<u8>::from_strict_hex(hex)?, domain)
+    }
+
+    pub fn is_valid_at(&self, domain_pub_key: &DomainPubKey, timestamp: u64) -> bool {
+        let work_data = match self.to_work_data() {
+            Ok(work_data) => work_data,
+            Err(_) => return false,
+        };
+        if work_data.is_expired_at(timestamp) {
+            return false;
+        }
+        let key_str = Self::signed_work_data_key_string(&domain_pub_key.domain);
+        self.signed_message
+            .is_valid(&domain_pub_key.pub_key, &key_str)
+    }
+
+    pub fn is_valid_now(&self, domain_pub_key: &DomainPubKey, clock: &dyn Clock) -> bool {
+        self.is_valid_at(domain_pub_key, clock.now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buf_reader::BufReader;
+    use crate::clock::ManualClock;
+    use crate::header::Header;
+    use crate::merkle_proof::MerkleProof;
+    use crate::tx::Tx;
+    use serde::Deserialize;
+    use std::fs;
+
+    #[derive(Deserialize)]
+    struct SignedWorkDataVectors {
+        signed_work_data: Vec<SignedWorkDataVector>,
+    }
+
+    #[derive(Deserialize)]
+    struct SignedWorkDataVector {
+        domain_priv_key: String,
+        work_data: String,
+        signed_work_data: String,
+    }
+
+    fn make_work_data(expires_at: u64) -> WorkData {
+        let header = Header::from_genesis(0);
+        let coinbase_tx = Tx::new(1, vec![], vec![], 0);
+        let merkle_proof = MerkleProof::new(coinbase_tx.id(), vec![]);
+        let share_target = BufReader::new(vec![0xff; 32]).read_u256_be().unwrap();
+        WorkData::new(
+            header,
+            1,
+            1,
+            coinbase_tx,
+            None,
+            merkle_proof.root,
+            merkle_proof,
+            share_target,
+            expires_at,
+        )
+    }
+
+    #[test]
+    fn test_from_work_data() {
+        let clock = ManualClock::new(1_700_000_000_000);
+        let domain = Domain::from_strict_str("earthbucks.com").unwrap();
+        let domain_priv_key = DomainPrivKey::from_random(domain.clone());
+        let domain_pub_key = DomainPubKey::from_domain_priv_key(&domain_priv_key).unwrap();
+        let work_data = make_work_data(clock.now() + WorkData::VALIDITY);
+        let signed_work_data =
+            SignedWorkData::from_work_data(&domain_priv_key, &work_data).unwrap();
+        assert!(signed_work_data.is_valid_now(&domain_pub_key, &clock));
+        assert_eq!(
+            signed_work_data.to_work_data().unwrap().to_buf(),
+            work_data.to_buf()
+        );
+
+        let hex = signed_work_data.to_strict_hex();
+        let signed_work_data2 = SignedWorkData::from_strict_hex(&hex, &domain).unwrap();
+        assert_eq!(signed_work_data2, signed_work_data);
+
+        // signed by another key, or for another domain
+        let other_domain_priv_key = DomainPrivKey::from_random(domain);
+        let other_domain_pub_key =
+            DomainPubKey::from_domain_priv_key(&other_domain_priv_key).unwrap();
+        assert!(!signed_work_data.is_valid_now(&other_domain_pub_key, &clock));
+        let other_domain = Domain::from_strict_str("other.com").unwrap();
+        let other_domain_pub_key = DomainPubKey::new(other_domain, domain_pub_key.pub_key.clone());
+        assert!(!signed_work_data.is_valid_now(&other_domain_pub_key, &clock));
+
+        clock.advance(WorkData::VALIDITY);
+        assert!(signed_work_data.is_valid_now(&domain_pub_key, &clock));
+        clock.advance(1);
+        assert!(!signed_work_data.is_valid_now(&domain_pub_key, &clock));
+    }
+
+    #[test]
+    fn test_vectors_signed_work_data() {
+        let data =
+            fs::read_to_string("./test_vectors/work_data.json").expect("Unable to read file");
+        let test_vectors: SignedWorkDataVectors =
+            serde_json::from_str(&data).expect("Unable to parse JSON");
+        for test_vector in test_vectors.signed_work_data {
+            let domain_priv_key =
+                DomainPrivKey::from_strict_str(&test_vector.domain_priv_key).unwrap();
+            let domain_pub_key = DomainPubKey::from_domain_priv_key(&domain_priv_key).unwrap();
+            let work_data = WorkData::from_strict_hex(&test_vector.work_data).unwrap();
+            let signed_work_data =
+                SignedWorkData::from_work_data(&domain_priv_key, &work_data).unwrap();
+            assert_eq!(
+                signed_work_data.to_strict_hex(),
+                test_vector.signed_work_data
+            );
+            let signed_work_data = SignedWorkData::from_strict_hex(
+                &test_vector.signed_work_data,
+                &domain_priv_key.domain,
+            )
+            .unwrap();
+            assert!(signed_work_data.is_valid_at(&domain_pub_key, work_data.expires_at));
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::buf::EbxBuf;
+use crate::clock::Clock;
+use crate::domain::Domain;
+use crate::domain_priv_key::DomainPrivKey;
+use crate::domain_pub_key::DomainPubKey;
+use crate::error::EbxError;
+use crate::permission_token::PermissionToken;
+use crate::signed_message::SignedMessage;
+
+// a permission token signed by a mine's domain key. the user signs it back in
+// a SigninResponse to prove that they hold their key. the same as
+// SigninChallenge in the typescript library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigninChallenge {
+    pub signed_message: SignedMessage,
+}
+
+impl SigninChallenge {
+    pub fn new(signed_message: SignedMessage) -> Self {
+        Self { signed_message }
+    }
+
+    pub fn signin_challenge_key_string(domain: &Domain) -> String {
+        format!("signin challenge for {}", domain)
+    }
+
+    pub fn from_random(
+        domain_priv_key: &DomainPrivKey,
+        clock: &dyn Clock,
+    ) -> Result<Self, EbxError> {
+        let key_str = Self::signin_challenge_key_string(&domain_priv_key.domain);
+        let permission_token = PermissionToken::from_random(clock);
+        let message = permission_token.to_buf();
+        let signed_message =
+            SignedMessage::from_sign_message(&domain_priv_key.priv_key, message, &key_str)?;
+        Ok(Self::new(signed_message))
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        self.signed_message.to_buf()
+    }
+
+    pub fn from_buf(buf: Vec<u8>, domain: &Domain) -> Result<Self, EbxError> {
+        let key_str = Self::signin_challenge_key_string(domain);
+        let signed_message = SignedMessage::from_buf(buf, &key_str)?;
+        Ok(Self::new(signed_message))
+    }
+
+    pub fn to_strict_hex(&self) -> String {
+        self.to_buf().to_strict_hex()
+    }
+
+    pub fn from_strict_hex(hex: &str, domain: &Domain) -> Result<Self, EbxError> {
+        Self::from_buf(Vec::<u8>::from_strict_hex(hex)?, domain)
+    }
+
+    pub fn permission_token(&self) -> Result<PermissionToken, EbxError> {
+        PermissionToken::from_buf(self.signed_message.message.clone())
+    }
+
+    pub fn is_valid_at(&self, domain_pub_key: &DomainPubKey, timestamp: u64) -> bool {
+        let permission_token = match self.permission_token() {
+            Ok(permission_token) => permission_token,
+            Err(_) => return false,
+        };
+        if !permission_token.is_valid_at(timestamp) {
+            return false;
+        }
+        let key_str = Self::signin_challenge_key_string(&domain_pub_key.domain);
+        self.signed_message
+            .is_valid(&domain_pub_key.pub_key, &key_str)
+    }
+
+    pub fn is_valid_now(&self, domain_pub_key: &DomainPubKey, clock: &dyn Clock) -> bool {
+        self.is_valid_at(domain_pub_key, clock.now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn test_from_random() {
+        let clock = ManualClock::new(1_700_000_000_000);
+        let domain = Domain::from_strict_str("earthbucks.com").unwrap();
+        let domain_priv_key = DomainPrivKey::from_random(domain.clone());
+        let domain_pub_key = DomainPubKey::from_domain_priv_key(&domain_priv_key).unwrap();
+        let signin_challenge = SigninChallenge::from_random(&domain_priv_key, &clock).unwrap();
+        assert!(signin_challenge.is_valid_now(&domain_pub_key, &clock));
+
+        let hex = signin_challenge.to_strict_hex();
+        let signin_challenge2 = SigninChallenge::from_strict_hex(&hex, &domain).unwrap();
+        assert_eq!(signin_challenge2, signin_challenge);
+
+        // read for another domain, the key string does not match
+        let other_domain = Domain::from_strict_str("other.com").unwrap();
+        let signin_challenge3 = SigninChallenge::from_strict_hex(&hex, &other_domain).unwrap();
+        let other_domain_pub_key = DomainPubKey::new(other_domain, domain_pub_key.pub_key.clone());
+        assert!(!signin_challenge3.is_valid_now(&other_domain_pub_key, &clock));
+
+        clock.advance(PermissionToken::VALIDITY);
+        assert!(!signin_challenge.is_valid_now(&domain_pub_key, &clock));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::buf::EbxBuf;
+use crate::clock::Clock;
+use crate::domain::Domain;
+use crate::domain_pub_key::DomainPubKey;
+use crate::error::EbxError;
+use crate::priv_key::PrivKey;
+use crate::pub_key::PubKey;
+use crate::signed_message::SignedMessage;
+use crate::signin_challenge::SigninChallenge;
+
+// a signin challenge signed back by the user. the same as SigninResponse in
+// the typescript library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigninResponse {
+    pub signed_message: SignedMessage,
+}
+
+impl SigninResponse {
+    pub fn new(signed_message: SignedMessage) -> Self {
+        Self { signed_message }
+    }
+
+    pub fn signin_response_key_string(domain: &Domain) -> String {
+        format!("signin response for {}", domain)
+    }
+
+    pub fn from_signin_challenge(
+        user_priv_key: &PrivKey,
+        domain_pub_key: &DomainPubKey,
+        signin_challenge: &SigninChallenge,
+        clock: &dyn Clock,
+    ) -> Result<Self, EbxError> {
+        if !signin_challenge.is_valid_now(domain_pub_key, clock) {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "invalid signin challenge".to_string(),
+            });
+        }
+        let key_str = Self::signin_response_key_string(&domain_pub_key.domain);
+        let message = signin_challenge.to_buf();
+        let signed_message = SignedMessage::from_sign_message(user_priv_key, message, &key_str)?;
+        Ok(Self::new(signed_message))
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        self.signed_message.to_buf()
+    }
+
+    pub fn from_buf(buf: Vec<u8>, domain: &Domain) -> Result<Self, EbxError> {
+        let key_str = Self::signin_response_key_string(domain);
+        let signed_message = SignedMessage::from_buf(buf, &key_str)?;
+        Ok(Self::new(signed_message))
+    }
+
+    pub fn to_strict_hex(&self) -> String {
+        self.to_buf().to_strict_hex()
+    }
+
+    pub fn from_strict_hex(hex: &str, domain: &Domain) -> Result<Self, EbxError> {
+        Self::from_buf(Vec::<u8>::from_strict_hex(hex)?, domain)
+    }
+
+    // the challenge that was signed
+    pub fn signin_challenge(&self, domain: &Domain) -> Result<SigninChallenge, EbxError> {
+        SigninChallenge::from_buf(self.signed_message.message.clone(), domain)
+    }
+
+    pub fn is_valid(&self, user_pub_key: &PubKey, domain: &Domain) -> bool {
+        let key_str = Self::signin_response_key_string(domain);
+        self.signed_message.is_valid(user_pub_key, &key_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::domain_priv_key::DomainPrivKey;
+    use crate::permission_token::PermissionToken;
+
+    #[test]
+    fn test_from_signin_challenge() {
+        let clock = ManualClock::new(1_700_000_000_000);
+        let domain = Domain::from_strict_str("earthbucks.com").unwrap();
+        let domain_priv_key = DomainPrivKey::from_random(domain.clone());
+        let domain_pub_key = DomainPubKey::from_domain_priv_key(&domain_priv_key).unwrap();
+        let signin_challenge = SigninChallenge::from_random(&domain_priv_key, &clock).unwrap();
+
+        let user_priv_key = PrivKey::from_random();
+        let user_pub_key = PubKey::from_priv_key(&user_priv_key).unwrap();
+        let signin_response = SigninResponse::from_signin_challenge(
+            &user_priv_key,
+            &domain_pub_key,
+            &signin_challenge,
+            &clock,
+        )
+        .unwrap();
+        assert!(signin_response.is_valid(&user_pub_key, &domain));
+        let hex = signin_response.to_strict_hex();
+        let signin_response2 = SigninResponse::from_strict_hex(&hex, &domain).unwrap();
+        assert_eq!(signin_response2, signin_response);
+        assert_eq!(
+            signin_response.signin_challenge(&domain).unwrap(),
+            signin_challenge
+        );
+
+        // a challenge that has expired, or was not signed by the domain
+        clock.advance(PermissionToken::VALIDITY);
+        assert!(SigninResponse::from_signin_challenge(
+            &user_priv_key,
+            &domain_pub_key,
+            &signin_challenge,
+            &clock,
+        )
+        .is_err());
+        clock.set(1_700_000_000_000);
+        let other_domain_priv_key = DomainPrivKey::from_random(domain);
+        let other_challenge = SigninChallenge::from_random(&other_domain_priv_key, &clock).unwrap();
+        assert!(SigninResponse::from_signin_challenge(
+            &user_priv_key,
+            &domain_pub_key,
+            &other_challenge,
+            &clock,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use crate::clock::Clock;
+use crate::domain_priv_key::DomainPrivKey;
+use crate::domain_pub_key::DomainPubKey;
+use crate::error::EbxError;
+use crate::permission_token::PermissionToken;
+use crate::pub_key::PubKey;
+use crate::signin_challenge::SigninChallenge;
+use crate::signin_response::SigninResponse;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// the server side of signing in to a mine. the mine issues challenges signed
+// by its domain key, and a user signs in by signing a challenge back before it
+// expires. each challenge can be used to sign in only once.
+pub struct SigninVerifier<'a> {
+    domain_priv_key: DomainPrivKey,
+    domain_pub_key: DomainPubKey,
+    clock: &'a dyn Clock,
+    // the tokens of used challenges, by their random values, until they expire
+    used_challenges: Mutex<HashMap<[u8; 32], PermissionToken>>,
+}
+
+impl<'a> SigninVerifier<'a> {
+    pub fn new(domain_priv_key: DomainPrivKey, clock: &'a dyn Clock) -> Result<Self, EbxError> {
+        let domain_pub_key = DomainPubKey::from_domain_priv_key(&domain_priv_key)?;
+        Ok(Self {
+            domain_priv_key,
+            domain_pub_key,
+            clock,
+            used_challenges: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn domain_pub_key(&self) -> &DomainPubKey {
+        &self.domain_pub_key
+    }
+
+    pub fn new_signin_challenge(&self) -> Result<SigninChallenge, EbxError> {
+        SigninChallenge::from_random(&self.domain_priv_key, self.clock)
+    }
+
+    // checks a response and returns the pubkey of the user who signed in
+    pub fn verify_signin_response(
+        &self,
+        signin_response: &SigninResponse,
+    ) -> Result<PubKey, EbxError> {
+        let err = |message: &str| EbxError::GenericError {
+            source: None,
+            message: message.to_string(),
+        };
+        let domain = &self.domain_pub_key.domain;
+        let user_pub_key = PubKey::from_buf(signin_response.signed_message.pub_key.to_vec())?;
+        if !signin_response.is_valid(&user_pub_key, domain) {
+            return Err(err("invalid signin response"));
+        }
+        let signin_challenge = signin_response.signin_challenge(domain)?;
+        let permission_token = signin_challenge.permission_token()?;
+        let now = self.clock.now();
+        if !permission_token.is_valid_at(now) {
+            return Err(err("signin challenge expired"));
+        }
+        if !signin_challenge.is_valid_at(&self.domain_pub_key, now) {
+            return Err(err("invalid signin challenge"));
+        }
+
+        let mut used_challenges = self.used_challenges.lock().unwrap();
+        used_challenges.retain(|_, permission_token| permission_token.is_valid_at(now));
+        let rand_value = permission_token.rand_value;
+        if used_challenges
+            .insert(rand_value, permission_token)
+            .is_some()
+        {
+            return Err(err("signin challenge already used"));
+        }
+        Ok(user_pub_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::domain::Domain;
+    use crate::priv_key::PrivKey;
+
+    #[test]
+    fn test_verify_signin_response() {
+        let clock = ManualClock::new(1_700_000_000_000);
+        let domain = Domain::from_strict_str("earthbucks.com").unwrap();
+        let domain_priv_key = DomainPrivKey::from_random(domain.clone());
+        let signin_verifier = SigninVerifier::new(domain_priv_key, &clock).unwrap();
+        let domain_pub_key = signin_verifier.domain_pub_key().clone();
+
+        let user_priv_key = PrivKey::from_random();
+        let user_pub_key = PubKey::from_priv_key(&user_priv_key).unwrap();
+        let signin_challenge = signin_verifier.new_signin_challenge().unwrap();
+        clock.advance(1_000);
+        let signin_response = SigninResponse::from_signin_challenge(
+            &user_priv_key,
+            &domain_pub_key,
+            &signin_challenge,
+            &clock,
+        )
+        .unwrap();
+        let pub_key = signin_verifier
+            .verify_signin_response(&signin_response)
+            .unwrap();
+        assert_eq!(pub_key.buf, user_pub_key.buf);
+
+        // a challenge can only be used once
+        let err = signin_verifier
+            .verify_signin_response(&signin_response)
+            .unwrap_err();
+        assert!(err.to_string().contains("already used"));
+
+        // a response after the challenge expired
+        let signin_challenge = signin_verifier.new_signin_challenge().unwrap();
+        let signin_response = SigninResponse::from_signin_challenge(
+            &user_priv_key,
+            &domain_pub_key,
+            &signin_challenge,
+            &clock,
+        )
+        .unwrap();
+        clock.advance(PermissionToken::VALIDITY);
+        let err = signin_verifier
+            .verify_signin_response(&signin_response)
+            .unwrap_err();
+        assert!(err.to_string().contains("expired"));
+    }
+
+    #[test]
+    fn test_verify_signin_response_from_other_domain_key() {
+        let clock = ManualClock::new(1_700_000_000_000);
+        let domain = Domain::from_strict_str("earthbucks.com").unwrap();
+        let signin_verifier =
+            SigninVerifier::new(DomainPrivKey::from_random(domain.clone()), &clock).unwrap();
+
+        // a challenge issued with another key for the same domain
+        let other_domain_priv_key = DomainPrivKey::from_random(domain);
+        let other_domain_pub_key =
+            DomainPubKey::from_domain_priv_key(&other_domain_priv_key).unwrap();
+        let signin_challenge =
+            SigninChallenge::from_random(&other_domain_priv_key, &clock).unwrap();
+        let signin_response = SigninResponse::from_signin_challenge(
+            &PrivKey::from_random(),
+            &other_domain_pub_key,
+            &signin_challenge,
+            &clock,
+        )
+        .unwrap();
+        let err = signin_verifier
+            .verify_signin_response(&signin_response)
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid signin challenge"));
+
+        // a response whose signature does not match
+        let mut signin_response = signin_response;
+        signin_response.signed_message.sig[0] ^= 1;
+        let err = signin_verifier
+            .verify_signin_response(&signin_response)
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid signin response"));
+    }
+}
@@ -6,6 +6,7 @@ use crate::hash::blake3_hash;
 use crate::hash::double_blake3_hash;
 use crate::pub_key::PubKey;
 use crate::script::Script;
+use crate::sig_cache::SigCache;
 use crate::tx_in::TxIn;
 use crate::tx_out::TxOut;
 use crate::tx_signature::TxSignature;
@@ -27,6 +28,14 @@ impl HashCache {
             outputs_hash: None,
         }
     }
+
+    pub fn from_tx(tx: &Tx) -> Self {
+        Self {
+            prevouts_hash: Some(tx.hash_prevouts()),
+            lock_rel_hash: Some(tx.hash_lock_rel()),
+            outputs_hash: Some(tx.hash_outputs()),
+        }
+    }
 }
 
 // add clone support
@@ -39,6 +48,13 @@ pub struct Tx {
 }
 
 impl Tx {
+    // bytes
+    pub const MAX_SIZE: usize = 1_000_000;
+
+    // a lock_abs below this is a block number. at or above it, it is a unix
+    // timestamp in seconds, which is met once the median time past reaches it.
+    pub const LOCK_ABS_TIME_THRESHOLD: u32 = 500_000_000;
+
     pub fn new(version: u8, inputs: Vec<TxIn>, outputs: Vec<TxOut>, lock_abs: u32) -> Self {
         Self {
             version,
@@ -58,6 +74,7 @@ impl Tx {
     }
 
     pub fn from_buf_reader(reader: &mut BufReader) -> Result<Self, EbxError> {
+        let start_len = reader.remainder_len();
         let version = reader.read_u8()?;
         let input_count = reader.read_var_int()? as usize;
         let mut inputs = Vec::new();
@@ -70,6 +87,10 @@ impl Tx {
             outputs.push(TxOut::from_buf_reader(reader)?);
         }
         let lock_num = reader.read_u32_be()?;
+        let size = start_len - reader.remainder_len();
+        if size > Tx::MAX_SIZE {
+            return Err(Tx::size_limit_error(size));
+        }
         Ok(Self::new(version, inputs, outputs, lock_num))
     }
 
@@ -88,6 +109,13 @@ impl Tx {
         writer
     }
 
+    pub fn size_limit_error(size: usize) -> EbxError {
+        EbxError::GenericError {
+            source: None,
+            message: format!("tx size {} exceeds limit of {}", size, Tx::MAX_SIZE),
+        }
+    }
+
     pub fn to_strict_hex(&self) -> String {
         hex::encode(self.to_buf())
     }
@@ -110,15 +138,42 @@ impl Tx {
         output_amount: u64,
         block_num: u32,
     ) -> Self {
-        let version = 1;
-        let inputs = vec![TxIn::from_coinbase(input_script)];
         let outputs = vec![TxOut::new(output_amount, output_script)];
+        Self::from_coinbase_tx_outs(input_script, outputs, block_num)
+    }
+
+    // a coinbase that splits the block reward across several outputs. this is
+    // called a "mint tx" in the typescript library.
+    pub fn from_coinbase_tx_outs(
+        input_script: Script,
+        outputs: Vec<TxOut>,
+        block_num: u32,
+    ) -> Self {
+        Self::from_coinbase_tx_ins(input_script, vec![], outputs, block_num)
+    }
+
+    // a coinbase that also sweeps expired outputs. the outputs must add up to
+    // the coinbase amount plus the value of the swept outputs.
+    pub fn from_coinbase_tx_ins(
+        input_script: Script,
+        expired_inputs: Vec<TxIn>,
+        outputs: Vec<TxOut>,
+        block_num: u32,
+    ) -> Self {
+        let version = 1;
+        let mut inputs = vec![TxIn::from_coinbase(input_script)];
+        inputs.extend(expired_inputs);
         let lock_num = block_num;
         Self::new(version, inputs, outputs, lock_num)
     }
 
+    // the first input of a coinbase is the null input, which spends nothing.
+    // every other input must sweep an expired output.
     pub fn is_coinbase(&self) -> bool {
-        self.inputs.len() == 1 && self.inputs[0].is_coinbase()
+        match self.inputs.split_first() {
+            None => false,
+            Some((first, rest)) => first.is_coinbase() && rest.iter().all(TxIn::is_expired),
+        }
     }
 
     pub fn blake3_hash(&self) -> [u8; 32] {
@@ -222,7 +277,7 @@ impl Tx {
     }
 
     pub fn sighash_no_cache(
-        &mut self,
+        &self,
         input_index: usize,
         script_iso_buf: Vec<u8>,
         amount: u64,
@@ -244,7 +299,7 @@ impl Tx {
     }
 
     pub fn sighash_with_cache(
-        &mut self,
+        &self,
         input_index: usize,
         script_iso_buf: Vec<u8>,
         amount: u64,
@@ -257,7 +312,7 @@ impl Tx {
     }
 
     pub fn sign_no_cache(
-        &mut self,
+        &self,
         input_index: usize,
         private_key: [u8; 32],
         script: Vec<u8>,
@@ -280,7 +335,7 @@ impl Tx {
     }
 
     pub fn sign_with_cache(
-        &mut self,
+        &self,
         input_index: usize,
         private_key: [u8; 32],
         script: Vec<u8>,
@@ -305,7 +360,7 @@ impl Tx {
     }
 
     pub fn verify_no_cache(
-        &mut self,
+        &self,
         input_index: usize,
         public_key: [u8; PubKey::SIZE],
         signature: TxSignature,
@@ -327,7 +382,7 @@ impl Tx {
     }
 
     pub fn verify_with_cache(
-        &mut self,
+        &self,
         input_index: usize,
         public_key: [u8; PubKey::SIZE],
         signature: TxSignature,
@@ -343,11 +398,36 @@ impl Tx {
             hash_type,
             hash_cache,
         );
+        Tx::verify_sighash(digest, public_key, &signature, None)
+    }
+
+    // checks a signature against a sighash. signatures found in the sig cache
+    // are not checked again, and valid ones are added to it.
+    pub fn verify_sighash(
+        digest: [u8; 32],
+        public_key: [u8; PubKey::SIZE],
+        signature: &TxSignature,
+        sig_cache: Option<&SigCache>,
+    ) -> bool {
+        let sig_buf = signature.to_buf();
+        if let Some(sig_cache) = sig_cache {
+            if sig_cache.contains(&digest, &public_key, &sig_buf) {
+                return true;
+            }
+        }
         let secp = secp256k1::Secp256k1::new();
         let message = secp256k1::Message::from_digest(digest);
-        let public_key = secp256k1::PublicKey::from_slice(&public_key).unwrap();
-        let signature = secp256k1::ecdsa::Signature::from_compact(&signature.sig_buf).unwrap();
-        secp.verify_ecdsa(&message, &signature, &public_key).is_ok()
+        let secp_public_key = secp256k1::PublicKey::from_slice(&public_key).unwrap();
+        let secp_signature = secp256k1::ecdsa::Signature::from_compact(&signature.sig_buf).unwrap();
+        let success = secp
+            .verify_ecdsa(&message, &secp_signature, &secp_public_key)
+            .is_ok();
+        if success {
+            if let Some(sig_cache) = sig_cache {
+                sig_cache.insert(&digest, &public_key, &sig_buf);
+            }
+        }
+        success
     }
 }
 
@@ -356,6 +436,7 @@ mod tests {
     use super::*;
     use crate::priv_key::PrivKey;
     use crate::script::Script;
+    use crate::script_chunk::ScriptChunk;
 
     #[test]
     fn test_tx() -> Result<(), String> {
@@ -439,6 +520,19 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_from_buf_size_limit() {
+        let script = Script::new(vec![ScriptChunk::from_data(vec![0; Tx::MAX_SIZE - 100])]);
+        let mut tx = Tx::new(1, vec![], vec![TxOut::new(0, script)], 0);
+        assert!(Tx::from_buf(tx.to_buf()).is_ok());
+        tx.outputs[0]
+            .script
+            .chunks
+            .push(ScriptChunk::from_data(vec![0; 100]));
+        let err = Tx::from_buf(tx.to_buf()).unwrap_err();
+        assert!(err.to_string().contains("tx size"));
+    }
+
     #[test]
     fn test_from_coinbase() {
         let input_script =
@@ -479,6 +573,19 @@ mod tests {
         let output_amount = 100;
         let tx = Tx::from_coinbase(input_script, output_script, output_amount, 0);
         assert!(tx.is_coinbase());
+
+        // a coinbase may sweep expired outputs, but spend nothing else
+        let mut tx = tx;
+        tx.inputs
+            .push(TxIn::new([1; 32], 0, Script::from_expired_pkhx_input(), 0));
+        tx.inputs
+            .push(TxIn::new([1; 32], 1, Script::from_expired_pkhxr_input(), 0));
+        assert!(tx.is_coinbase());
+        tx.inputs.push(TxIn::new([1; 32], 2, Script::from_empty(), 0));
+        assert!(!tx.is_coinbase());
+        tx.inputs.pop();
+        tx.inputs.swap(0, 1);
+        assert!(!tx.is_coinbase());
     }
 
     #[test]
@@ -586,7 +693,7 @@ mod tests {
         let inputs = vec![TxIn::new([0; 32], 0, Script::from_empty(), 0)];
         let outputs = vec![TxOut::new(100, Script::from_empty())];
 
-        let mut tx = Tx::new(version, inputs, outputs, 0);
+        let tx = Tx::new(version, inputs, outputs, 0);
 
         let script = Script::from_empty();
         let amount = 1;
@@ -606,7 +713,7 @@ mod tests {
         let inputs = vec![TxIn::new([0; 32], 0, Script::from_empty(), 0)];
         let outputs = vec![TxOut::new(100, Script::from_empty())];
 
-        let mut tx = Tx::new(version, inputs, outputs, 0);
+        let tx = Tx::new(version, inputs, outputs, 0);
 
         let script = Script::from_empty();
         let amount = 1;
@@ -637,7 +744,7 @@ mod tests {
         );
         let outputs = vec![TxOut::new(100, Script::from_empty())];
         assert_eq!(hex::encode(outputs[0].to_buf()), "000000000000006400");
-        let mut tx = Tx::new(0, inputs, outputs, 0);
+        let tx = Tx::new(0, inputs, outputs, 0);
         assert_eq!(hex::encode(tx.to_buf()), "000100000000000000000000000000000000000000000000000000000000000000000000000000000000000100000000000000640000000000");
 
         let signature = tx.sign_no_cache(
@@ -677,7 +784,7 @@ mod tests {
         );
         let outputs = vec![TxOut::new(100, Script::from_empty())];
         assert_eq!(hex::encode(outputs[0].to_buf()), "000000000000006400");
-        let mut tx = Tx::new(0, inputs, outputs, 0);
+        let tx = Tx::new(0, inputs, outputs, 0);
         assert_eq!(hex::encode(tx.to_buf()), "000100000000000000000000000000000000000000000000000000000000000000000000000000000000000100000000000000640000000000");
         let hash_cache_1 = &mut HashCache::new();
 
@@ -1,3 +1,5 @@
+use crate::chain_params::ChainParams;
+use crate::ebx_value::EbxValue;
 use crate::error::EbxError;
 use crate::script::Script;
 use crate::tx::Tx;
@@ -11,16 +13,27 @@ pub struct TxBuilder {
     change_script: Script,
     input_amount: u64,
     lock_abs: u32,
+    block_num: u32,
+    coinbase_maturity: u32,
 }
 
 impl TxBuilder {
-    pub fn new(input_tx_out_bn_map: &TxOutBnMap, change_script: Script, lock_abs: u32) -> Self {
+    // only coinbase outputs that are mature in block block_num are spent
+    pub fn new(
+        input_tx_out_bn_map: &TxOutBnMap,
+        change_script: Script,
+        lock_abs: u32,
+        block_num: u32,
+        params: &ChainParams,
+    ) -> Self {
         Self {
             tx: Tx::new(0, vec![], vec![], 0),
             input_tx_out_bn_map: input_tx_out_bn_map.clone(),
             change_script,
             input_amount: 0,
             lock_abs,
+            block_num,
+            coinbase_maturity: params.coinbase_maturity,
         }
     }
 
@@ -30,7 +43,7 @@ impl TxBuilder {
 
     pub fn add_input(&mut self, tx_in: TxIn, amount: u64) {
         self.tx.inputs.push(tx_in);
-        self.input_amount += amount;
+        self.input_amount = self.input_amount.saturating_add(amount);
     }
 
     // "tx fees", also called "change fees", are zero on earthbucks. this
@@ -39,8 +52,13 @@ impl TxBuilder {
     // transaction fees are paid by making a separate transaction to a mine.
     pub fn build(&mut self) -> Result<Tx, EbxError> {
         self.tx.lock_abs = self.lock_abs;
-        let total_spend_amount: u64 = self.tx.outputs.iter().map(|output| output.value).sum();
-        let mut change_amount = 0;
+        let output_values = self.tx.outputs.iter().map(|output| output.value);
+        let total_spend_amount = EbxValue::checked_sum(output_values)
+            .ok_or_else(|| EbxError::GenericError {
+                source: None,
+                message: "output values exceed max money".to_string(),
+            })?
+            .value;
         let mut input_amount = self.input_amount;
 
         // sort by block number first, but if those are the same, sort by the id
@@ -48,13 +66,17 @@ impl TxBuilder {
         // this logic means we use the "most confirmed" outputs first, which is
         // what we want, and then we have a deterministic way to sort the UTXOs
         // in the same block.
-        let mut sorted_tx_out_bns: Vec<_> = self.input_tx_out_bn_map.map.iter().collect();
+        let mut sorted_tx_out_bns: Vec<_> = self
+            .input_tx_out_bn_map
+            .map
+            .iter()
+            .filter(|(_, tx_out_bn)| tx_out_bn.is_mature_at(self.block_num, self.coinbase_maturity))
+            .collect();
         sorted_tx_out_bns
             .sort_by(|a, b| a.1.block_num.cmp(&b.1.block_num).then_with(|| a.0.cmp(b.0)));
 
         for (tx_out_id, tx_out_bn) in sorted_tx_out_bns {
             if input_amount >= total_spend_amount {
-                change_amount = input_amount - total_spend_amount;
                 break;
             }
             let tx_out = &tx_out_bn.tx_out;
@@ -78,10 +100,11 @@ impl TxBuilder {
 
             let tx_input = TxIn::new(tx_id, tx_out_num, input_script, 0);
             self.tx.inputs.push(tx_input);
-            input_amount += tx_out.value;
+            input_amount = input_amount.saturating_add(tx_out.value);
         }
         self.input_amount = input_amount;
-        if change_amount > 0 {
+        if input_amount > total_spend_amount {
+            let change_amount = input_amount - total_spend_amount;
             let tx_out = TxOut::new(change_amount, self.change_script.clone());
             self.add_output(tx_out);
         }
@@ -109,7 +132,13 @@ mod tests {
             tx_out_bn_map.add(&[0; 32], i, tx_out, block_num);
         }
 
-        TxBuilder::new(&tx_out_bn_map, change_script.unwrap(), 0)
+        TxBuilder::new(
+            &tx_out_bn_map,
+            change_script.unwrap(),
+            0,
+            0,
+            &ChainParams::mainnet(),
+        )
     }
 
     #[test]
@@ -138,4 +167,50 @@ mod tests {
         assert_eq!(tx_builder.input_amount, 500);
         assert_eq!(tx.outputs[0].value, 10000);
     }
+
+    #[test]
+    fn test_build_valid_tx_with_change_when_all_inputs_are_used() {
+        let mut tx_builder = setup();
+        let tx_out = TxOut::new(450, Script::from_empty());
+        tx_builder.add_output(tx_out);
+
+        let tx = tx_builder.build().unwrap();
+
+        assert_eq!(tx.inputs.len(), 5);
+        assert_eq!(tx.outputs.len(), 2);
+        assert_eq!(tx.outputs[0].value, 450);
+        assert_eq!(tx.outputs[1].value, 50);
+    }
+
+    #[test]
+    fn test_build_skips_immature_coinbase_outputs() {
+        let mut tx_out_bn_map = TxOutBnMap::new();
+        let coinbase_tx = Tx::from_coinbase(
+            Script::from_empty(),
+            Script::from_pkh_output(&[1; 32]),
+            1000,
+            10,
+        );
+        tx_out_bn_map.add_tx_outputs(&coinbase_tx, 10);
+        tx_out_bn_map.add(
+            &[0; 32],
+            0,
+            TxOut::new(100, Script::from_pkh_output(&[2; 32])),
+            10,
+        );
+        let params = ChainParams::mainnet();
+
+        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, Script::from_empty(), 0, 109, &params);
+        tx_builder.add_output(TxOut::new(500, Script::from_empty()));
+        let tx = tx_builder.build().unwrap();
+        assert_eq!(tx.inputs.len(), 1);
+        assert_eq!(tx.inputs[0].input_tx_id, [0; 32]);
+        assert_eq!(tx_builder.input_amount, 100);
+
+        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, Script::from_empty(), 0, 110, &params);
+        tx_builder.add_output(TxOut::new(500, Script::from_empty()));
+        let tx = tx_builder.build().unwrap();
+        assert_eq!(tx.inputs.len(), 2);
+        assert_eq!(tx_builder.input_amount, 1100);
+    }
 }
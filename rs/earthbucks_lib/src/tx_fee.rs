@@ -0,0 +1,360 @@
+use crate::chain_params::ChainParams;
+use crate::error::EbxError;
+use crate::pkh::Pkh;
+use crate::pkh_key_map::PkhKeyMap;
+use crate::script::Script;
+use crate::tx::Tx;
+use crate::tx_builder::TxBuilder;
+use crate::tx_out::TxOut;
+use crate::tx_out_bn_map::TxOutBnMap;
+use crate::tx_signer::TxSigner;
+use crate::tx_verifier::TxVerifier;
+
+// txs on earthbucks have no implicit fee: input must exactly equal output. a
+// mine is instead paid by an explicit output to its pkh, either in a separate
+// "fee tx" submitted alongside the user's tx, or as an extra output in the
+// user's tx itself.
+pub struct TxFeeBuilder {
+    input_tx_out_bn_map: TxOutBnMap,
+    change_script: Script,
+    mine_pkh: Pkh,
+    fee_amount: u64,
+    lock_abs: u32,
+    block_num: u32,
+    params: ChainParams,
+}
+
+impl TxFeeBuilder {
+    // the fee tx only spends coinbase outputs that are mature in block
+    // block_num, like TxBuilder
+    pub fn new(
+        input_tx_out_bn_map: &TxOutBnMap,
+        change_script: Script,
+        mine_pkh: Pkh,
+        fee_amount: u64,
+        lock_abs: u32,
+        block_num: u32,
+        params: &ChainParams,
+    ) -> Self {
+        Self {
+            input_tx_out_bn_map: input_tx_out_bn_map.clone(),
+            change_script,
+            mine_pkh,
+            fee_amount,
+            lock_abs,
+            block_num,
+            params: params.clone(),
+        }
+    }
+
+    pub fn fee_tx_out(&self) -> TxOut {
+        TxOut::new(self.fee_amount, Script::from_pkh_output(&self.mine_pkh.buf))
+    }
+
+    // the outputs spent by the user's tx are excluded so that the fee tx never
+    // conflicts with it.
+    pub fn build_fee_tx(&self, user_tx: &Tx) -> Result<Tx, EbxError> {
+        let mut tx_out_bn_map = self.input_tx_out_bn_map.clone();
+        for tx_in in &user_tx.inputs {
+            tx_out_bn_map.remove(&tx_in.input_tx_id, tx_in.input_tx_out_num);
+        }
+        let mut tx_builder = TxBuilder::new(
+            &tx_out_bn_map,
+            self.change_script.clone(),
+            self.lock_abs,
+            self.block_num,
+            &self.params,
+        );
+        tx_builder.add_output(self.fee_tx_out());
+        let tx = tx_builder.build()?;
+        let mut input_amount: u64 = 0;
+        for tx_in in &tx.inputs {
+            let tx_out_bn = tx_out_bn_map
+                .get(&tx_in.input_tx_id, tx_in.input_tx_out_num)
+                .unwrap();
+            input_amount = input_amount.saturating_add(tx_out_bn.tx_out.value);
+        }
+        if input_amount < self.fee_amount {
+            return Err(EbxError::GenericError {
+                source: None,
+                message: "insufficient funds to pay fee".to_string(),
+            });
+        }
+        Ok(tx)
+    }
+
+    pub fn build_with_fee_output(&self, tx_builder: &mut TxBuilder) -> Result<Tx, EbxError> {
+        tx_builder.add_output(self.fee_tx_out());
+        tx_builder.build()
+    }
+
+    pub fn sign(
+        &self,
+        tx: Tx,
+        pkh_key_map: &PkhKeyMap,
+        working_block_num: u32,
+    ) -> Result<Tx, EbxError> {
+        let mut tx_signer = TxSigner::new(
+            tx,
+            &self.input_tx_out_bn_map,
+            pkh_key_map,
+            working_block_num,
+        );
+        tx_signer.sign()
+    }
+
+    pub fn build_bundle(
+        &self,
+        user_tx: Tx,
+        pkh_key_map: &PkhKeyMap,
+        working_block_num: u32,
+    ) -> Result<TxFeeBundle, EbxError> {
+        let fee_tx = self.build_fee_tx(&user_tx)?;
+        let user_tx = self.sign(user_tx, pkh_key_map, working_block_num)?;
+        let fee_tx = self.sign(fee_tx, pkh_key_map, working_block_num)?;
+        Ok(TxFeeBundle::new(vec![user_tx, fee_tx]))
+    }
+
+    pub fn build_bundle_with_fee_output(
+        &self,
+        tx_builder: &mut TxBuilder,
+        pkh_key_map: &PkhKeyMap,
+        working_block_num: u32,
+    ) -> Result<TxFeeBundle, EbxError> {
+        let tx = self.build_with_fee_output(tx_builder)?;
+        let tx = self.sign(tx, pkh_key_map, working_block_num)?;
+        Ok(TxFeeBundle::new(vec![tx]))
+    }
+}
+
+// a set of txs submitted to a mine together, at least one of which pays the
+// mine's fee.
+#[derive(Clone, Debug)]
+pub struct TxFeeBundle {
+    pub txs: Vec<Tx>,
+}
+
+impl TxFeeBundle {
+    pub fn new(txs: Vec<Tx>) -> Self {
+        Self { txs }
+    }
+
+    pub fn fee_paid_to(&self, mine_pkh: &Pkh) -> u64 {
+        let fee_script = Script::from_pkh_output(&mine_pkh.buf);
+        self.txs
+            .iter()
+            .flat_map(|tx| tx.outputs.iter())
+            .filter(|tx_out| tx_out.script == fee_script)
+            .fold(0u64, |sum, tx_out| sum.saturating_add(tx_out.value))
+    }
+
+    pub fn has_no_conflicts(&self) -> bool {
+        let mut spent_outputs = Vec::new();
+        for tx in &self.txs {
+            if tx.is_coinbase() {
+                return false;
+            }
+            for tx_in in &tx.inputs {
+                let name = TxOutBnMap::name_from_output(&tx_in.input_tx_id, tx_in.input_tx_out_num);
+                if spent_outputs.contains(&name) {
+                    return false;
+                }
+                spent_outputs.push(name);
+            }
+        }
+        true
+    }
+
+    pub fn pays_fee(&self, mine_pkh: &Pkh, required_fee: u64) -> bool {
+        self.has_no_conflicts() && self.fee_paid_to(mine_pkh) >= required_fee
+    }
+
+    pub fn is_valid(
+        &self,
+        tx_out_bn_map: &TxOutBnMap,
+        block_num: u32,
+        median_time_past: u64,
+        params: &ChainParams,
+        mine_pkh: &Pkh,
+        required_fee: u64,
+    ) -> bool {
+        if !self.pays_fee(mine_pkh, required_fee) {
+            return false;
+        }
+        for tx in &self.txs {
+            let mut tx_verifier =
+                TxVerifier::new(tx, tx_out_bn_map, block_num, median_time_past, params);
+            if !tx_verifier.verify() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_pair::KeyPair;
+
+    fn setup() -> (TxOutBnMap, PkhKeyMap) {
+        let mut tx_out_bn_map = TxOutBnMap::new();
+        let mut pkh_key_map = PkhKeyMap::new();
+        for i in 0..5 {
+            let key = KeyPair::from_random();
+            let pkh = Pkh::from_pub_key_buffer(key.pub_key.buf.to_vec());
+            pkh_key_map.add(key, &pkh.buf);
+            let script = Script::from_pkh_output(&pkh.buf);
+            let output = TxOut::new(100, script);
+            tx_out_bn_map.add(&[0; 32], i, output, 0);
+        }
+        (tx_out_bn_map, pkh_key_map)
+    }
+
+    fn mine_pkh() -> Pkh {
+        let key = KeyPair::from_random();
+        Pkh::from_pub_key_buffer(key.pub_key.buf.to_vec())
+    }
+
+    #[test]
+    fn test_build_bundle_with_separate_fee_tx() {
+        let (tx_out_bn_map, pkh_key_map) = setup();
+        let mine_pkh = mine_pkh();
+
+        let mut tx_builder = TxBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
+        tx_builder.add_output(TxOut::new(150, Script::from_empty()));
+        let user_tx = tx_builder.build().unwrap();
+        assert_eq!(user_tx.inputs.len(), 2);
+
+        let fee_builder = TxFeeBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            mine_pkh.clone(),
+            10,
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
+        let bundle = fee_builder.build_bundle(user_tx, &pkh_key_map, 0).unwrap();
+
+        assert_eq!(bundle.txs.len(), 2);
+        let fee_tx = &bundle.txs[1];
+        assert_eq!(fee_tx.inputs.len(), 1);
+        assert_eq!(fee_tx.outputs.len(), 2);
+        assert_eq!(fee_tx.outputs[0].value, 10);
+        assert_eq!(fee_tx.outputs[1].value, 90);
+
+        assert!(bundle.has_no_conflicts());
+        assert_eq!(bundle.fee_paid_to(&mine_pkh), 10);
+        assert!(bundle.pays_fee(&mine_pkh, 10));
+        assert!(!bundle.pays_fee(&mine_pkh, 11));
+        assert!(!bundle.pays_fee(&self::mine_pkh(), 10));
+        assert!(bundle.is_valid(&tx_out_bn_map, 0, 0, &ChainParams::mainnet(), &mine_pkh, 10));
+    }
+
+    #[test]
+    fn test_build_bundle_with_fee_output() {
+        let (tx_out_bn_map, pkh_key_map) = setup();
+        let mine_pkh = mine_pkh();
+
+        let mut tx_builder = TxBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
+        tx_builder.add_output(TxOut::new(150, Script::from_empty()));
+
+        let fee_builder = TxFeeBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            mine_pkh.clone(),
+            10,
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
+        let bundle = fee_builder
+            .build_bundle_with_fee_output(&mut tx_builder, &pkh_key_map, 0)
+            .unwrap();
+
+        assert_eq!(bundle.txs.len(), 1);
+        let tx = &bundle.txs[0];
+        assert_eq!(tx.outputs.len(), 3);
+        assert_eq!(tx.outputs[1].value, 10);
+        assert_eq!(tx.outputs[2].value, 40);
+        assert!(bundle.is_valid(&tx_out_bn_map, 0, 0, &ChainParams::mainnet(), &mine_pkh, 10));
+    }
+
+    #[test]
+    fn test_build_fee_tx_with_insufficient_funds() {
+        let (tx_out_bn_map, _) = setup();
+        let mut tx_builder = TxBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
+        tx_builder.add_output(TxOut::new(450, Script::from_empty()));
+        let user_tx = tx_builder.build().unwrap();
+
+        let fee_builder = TxFeeBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            mine_pkh(),
+            10,
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
+        assert!(fee_builder.build_fee_tx(&user_tx).is_err());
+    }
+
+    #[test]
+    fn test_bundle_with_conflicting_txs() {
+        let (tx_out_bn_map, pkh_key_map) = setup();
+        let mine_pkh = mine_pkh();
+
+        let mut tx_builder = TxBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
+        tx_builder.add_output(TxOut::new(50, Script::from_empty()));
+        let user_tx = tx_builder.build().unwrap();
+
+        let fee_builder = TxFeeBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            mine_pkh.clone(),
+            10,
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
+        let mut tx_builder = TxBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
+        let fee_tx = fee_builder.build_with_fee_output(&mut tx_builder).unwrap();
+        let fee_tx = fee_builder.sign(fee_tx, &pkh_key_map, 0).unwrap();
+
+        // both txs spend the same first output
+        let bundle = TxFeeBundle::new(vec![user_tx, fee_tx]);
+        assert!(!bundle.has_no_conflicts());
+        assert!(!bundle.pays_fee(&mine_pkh, 10));
+    }
+}
@@ -70,6 +70,13 @@ impl TxIn {
         self.is_null() && self.is_minimal_lock()
     }
 
+    // spends an expired pkhx or pkhxr output. anyone can spend these once they
+    // have expired, and the coinbase sweeps them into the block reward.
+    pub fn is_expired(&self) -> bool {
+        !self.is_null()
+            && (self.script.is_expired_pkhx_input() || self.script.is_expired_pkhxr_input())
+    }
+
     pub fn from_coinbase(script: Script) -> Self {
         Self {
             input_tx_id: [0; 32],
@@ -20,10 +20,7 @@ impl TxOut {
         let value = reader.read_u64_be()?;
         let script_len = reader.read_var_int()? as usize;
         let script_arr = reader.read(script_len)?;
-        let script = match Script::from_buf(&script_arr[..]) {
-            Ok(script) => script,
-            Err(e) => return Err(e),
-        };
+        let script = Script::from_buf(&script_arr[..])?;
         Ok(Self::new(value, script))
     }
 
@@ -31,10 +28,7 @@ impl TxOut {
         let value = reader.read_u64_be()?;
         let script_len = reader.read_var_int()? as usize;
         let script_arr = reader.read(script_len)?;
-        let script = match Script::from_buf(&script_arr[..]) {
-            Ok(script) => script,
-            Err(e) => return Err(e),
-        };
+        let script = Script::from_buf(&script_arr[..])?;
         Ok(Self::new(value, script))
     }
 
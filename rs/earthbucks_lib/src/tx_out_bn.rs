@@ -4,4 +4,34 @@ use crate::tx_out::TxOut;
 pub struct TxOutBn {
     pub tx_out: TxOut,
     pub block_num: u32,
+    pub is_coinbase: bool,
+}
+
+impl TxOutBn {
+    // coinbase outputs cannot be spent until they are coinbase_maturity blocks
+    // deep, so that a reorg that drops the coinbase cannot also invalidate txs
+    // that spend it. other outputs can be spent in the next block.
+    pub fn is_mature_at(&self, block_num: u32, coinbase_maturity: u32) -> bool {
+        !self.is_coinbase || block_num >= self.block_num.saturating_add(coinbase_maturity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_mature_at() {
+        let mut tx_out_bn = TxOutBn {
+            tx_out: TxOut::default(),
+            block_num: 10,
+            is_coinbase: false,
+        };
+        assert!(tx_out_bn.is_mature_at(11, 100));
+
+        tx_out_bn.is_coinbase = true;
+        assert!(!tx_out_bn.is_mature_at(11, 100));
+        assert!(!tx_out_bn.is_mature_at(109, 100));
+        assert!(tx_out_bn.is_mature_at(110, 100));
+    }
 }
@@ -29,11 +29,16 @@ impl TxOutBnMap {
     }
 
     pub fn add(&mut self, tx_id: &[u8; 32], tx_out_num: u32, tx_out: TxOut, block_num: u32) {
-        let name = Self::name_from_output(tx_id, tx_out_num);
         let tx_out_bn = TxOutBn {
             tx_out: tx_out.clone(),
             block_num,
+            is_coinbase: false,
         };
+        self.add_tx_out_bn(tx_id, tx_out_num, tx_out_bn);
+    }
+
+    pub fn add_tx_out_bn(&mut self, tx_id: &[u8; 32], tx_out_num: u32, tx_out_bn: TxOutBn) {
+        let name = Self::name_from_output(tx_id, tx_out_num);
         self.map.insert(name, tx_out_bn);
     }
 
@@ -52,10 +57,37 @@ impl TxOutBnMap {
     }
 
     pub fn add_tx_outputs(&mut self, tx: &Tx, block_num: u32) {
+        let tx_id = tx.id();
+        let is_coinbase = tx.is_coinbase();
         for (output_index, output) in tx.outputs.iter().enumerate() {
-            self.add(&tx.id(), output_index as u32, output.clone(), block_num);
+            let tx_out_bn = TxOutBn {
+                tx_out: output.clone(),
+                block_num,
+                is_coinbase,
+            };
+            self.add_tx_out_bn(&tx_id, output_index as u32, tx_out_bn);
         }
     }
+
+    // the value that can be spent in a tx in the given block
+    pub fn balance_at(&self, block_num: u32, coinbase_maturity: u32) -> u64 {
+        self.map
+            .values()
+            .filter(|tx_out_bn| tx_out_bn.is_mature_at(block_num, coinbase_maturity))
+            .fold(0u64, |sum, tx_out_bn| {
+                sum.saturating_add(tx_out_bn.tx_out.value)
+            })
+    }
+
+    // the value of coinbase outputs that cannot be spent yet in the given block
+    pub fn immature_balance_at(&self, block_num: u32, coinbase_maturity: u32) -> u64 {
+        self.map
+            .values()
+            .filter(|tx_out_bn| !tx_out_bn.is_mature_at(block_num, coinbase_maturity))
+            .fold(0u64, |sum, tx_out_bn| {
+                sum.saturating_add(tx_out_bn.tx_out.value)
+            })
+    }
 }
 
 #[cfg(test)]
@@ -90,6 +122,7 @@ mod tests {
         let tx_out_bn = TxOutBn {
             tx_out: tx_output.clone(),
             block_num,
+            is_coinbase: false,
         };
         tx_out_map.add(&tx_id_hash, output_index, tx_output.clone(), block_num);
         assert_eq!(
@@ -126,6 +159,7 @@ mod tests {
         let tx_out_bn = TxOutBn {
             tx_out: tx_output.clone(),
             block_num,
+            is_coinbase: false,
         };
         tx_out_map.add(&tx_id_hash, output_index, tx_output.clone(), block_num);
         let retrieved = tx_out_map.get(&tx_id_hash, output_index);
@@ -150,10 +184,12 @@ mod tests {
         let tx_out_bn1 = TxOutBn {
             tx_out: tx_out1.clone(),
             block_num,
+            is_coinbase: false,
         };
         let tx_out_bn2 = TxOutBn {
             tx_out: tx_out2.clone(),
             block_num,
+            is_coinbase: false,
         };
         tx_out_map.add(&tx_id_hash1, output_index, tx_out1.clone(), block_num);
         tx_out_map.add(&tx_id_hash2, output_index, tx_out2.clone(), block_num);
@@ -162,4 +198,21 @@ mod tests {
         assert!(values.contains(&&tx_out_bn1));
         assert!(values.contains(&&tx_out_bn2));
     }
+
+    #[test]
+    fn test_add_tx_outputs_and_balance() {
+        let mut tx_out_map = TxOutBnMap::new();
+        let coinbase_tx = Tx::from_coinbase(Script::from_empty(), Script::from_empty(), 1000, 5);
+        tx_out_map.add_tx_outputs(&coinbase_tx, 5);
+        assert!(tx_out_map.get(&coinbase_tx.id(), 0).unwrap().is_coinbase);
+
+        let tx = Tx::new(1, vec![], vec![TxOut::new(100, Script::from_empty())], 0);
+        tx_out_map.add_tx_outputs(&tx, 6);
+        assert!(!tx_out_map.get(&tx.id(), 0).unwrap().is_coinbase);
+
+        assert_eq!(tx_out_map.balance_at(7, 10), 100);
+        assert_eq!(tx_out_map.immature_balance_at(7, 10), 1000);
+        assert_eq!(tx_out_map.balance_at(15, 10), 1100);
+        assert_eq!(tx_out_map.immature_balance_at(15, 10), 0);
+    }
 }
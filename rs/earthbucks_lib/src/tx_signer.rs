@@ -28,7 +28,7 @@ impl TxSigner {
     }
 
     pub fn sign_input(&mut self, n_in: usize) -> Result<Tx, EbxError> {
-        let mut tx_clone = self.tx.clone();
+        let tx_clone = self.tx.clone();
 
         let tx_input = &mut self.tx.inputs[n_in];
         let tx_out_hash: &[u8; 32] = &tx_input.input_tx_id.clone();
@@ -358,6 +358,7 @@ impl TxSigner {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chain_params::ChainParams;
     use crate::key_pair::KeyPair;
     use crate::pkh::Pkh;
     use crate::pkh_key_map::PkhKeyMap;
@@ -385,7 +386,13 @@ mod tests {
             tx_out_bn_map.add(&[0; 32], i, output, block_num);
         }
 
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, Script::from_empty(), 0);
+        let mut tx_builder = TxBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
         let tx_out = TxOut::new(50, Script::from_empty());
         tx_builder.add_output(tx_out);
 
@@ -415,7 +422,7 @@ mod tests {
         let mut hash_cache = HashCache::new();
         let mut script_interpreter = ScriptInterpreter::from_output_script_tx(
             exec_script,
-            signed_tx,
+            &signed_tx,
             0,
             stack,
             100,
@@ -442,7 +449,13 @@ mod tests {
             tx_out_bn_map.add(&[0; 32], i, output, block_num);
         }
 
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, Script::from_empty(), 0);
+        let mut tx_builder = TxBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
         let tx_out = TxOut::new(100, Script::from_empty());
         tx_builder.add_output(tx_out.clone());
         tx_builder.add_output(tx_out.clone());
@@ -477,7 +490,7 @@ mod tests {
         let mut hash_cache = HashCache::new();
         let mut script_interpreter_1 = ScriptInterpreter::from_output_script_tx(
             exec_script_1,
-            signed_tx.clone(),
+            &signed_tx,
             0,
             stack_1,
             100,
@@ -503,7 +516,7 @@ mod tests {
 
         let mut script_interpreter_2 = ScriptInterpreter::from_output_script_tx(
             exec_script_2,
-            signed_tx.clone(),
+            &signed_tx,
             1,
             stack_2,
             100,
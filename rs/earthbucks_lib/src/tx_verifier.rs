@@ -1,27 +1,58 @@
+use crate::chain_params::ChainParams;
+use crate::ebx_value::EbxValue;
 use crate::script_interpreter::ScriptInterpreter;
+use crate::sig_cache::SigCache;
 use crate::tx::{HashCache, Tx};
 use crate::tx_out_bn_map::TxOutBnMap;
+use std::collections::HashSet;
 
 pub struct TxVerifier<'a> {
-    tx: Tx,
+    tx: &'a Tx,
     tx_out_bn_map: &'a TxOutBnMap,
     hash_cache: HashCache,
     block_num: u32,
+    median_time_past: u64,
+    sig_cache: Option<&'a SigCache>,
+    coinbase_maturity: u32,
 }
 
 impl<'a> TxVerifier<'a> {
-    pub fn new(tx: Tx, tx_out_bn_map: &'a TxOutBnMap, block_num: u32) -> Self {
-        let hash_cache = HashCache::new();
+    pub fn new(
+        tx: &'a Tx,
+        tx_out_bn_map: &'a TxOutBnMap,
+        block_num: u32,
+        median_time_past: u64,
+        params: &ChainParams,
+    ) -> Self {
+        // the tx-wide hashes are shared by every input, so compute them once
+        let hash_cache = HashCache::from_tx(tx);
         Self {
             tx,
             tx_out_bn_map,
             hash_cache,
             block_num,
+            median_time_past,
+            sig_cache: None,
+            coinbase_maturity: params.coinbase_maturity,
         }
     }
 
+    pub fn new_with_sig_cache(
+        tx: &'a Tx,
+        tx_out_bn_map: &'a TxOutBnMap,
+        block_num: u32,
+        median_time_past: u64,
+        params: &ChainParams,
+        sig_cache: &'a SigCache,
+    ) -> Self {
+        let mut tx_verifier = Self::new(tx, tx_out_bn_map, block_num, median_time_past, params);
+        tx_verifier.sig_cache = Some(sig_cache);
+        tx_verifier
+    }
+
     pub fn verify_input_script(&mut self, n_in: usize) -> bool {
-        let tx_input = &self.tx.inputs[n_in];
+        let tx = self.tx;
+        let tx_input = &tx.inputs[n_in];
         let tx_out_hash: &[u8; 32] = &tx_input.input_tx_id.clone();
         let output_index = tx_input.input_tx_out_num;
         let tx_out = self.tx_out_bn_map.get(tx_out_hash, output_index);
@@ -40,12 +71,13 @@ impl<'a> TxVerifier<'a> {
                     .collect();
                 let mut script_interpreter = ScriptInterpreter::from_output_script_tx(
                     output_script.clone(),
-                    self.tx.clone(),
+                    tx,
                     n_in,
                     stack,
                     tx_out_bn.tx_out.value,
                     &mut self.hash_cache,
                 );
+                script_interpreter.sig_cache = self.sig_cache;
                 script_interpreter.eval_script()
             }
         }
@@ -66,6 +98,17 @@ impl<'a> TxVerifier<'a> {
         }
     }
 
+    pub fn verify_input_maturity(&self, n_in: usize) -> bool {
+        let tx_input = &self.tx.inputs[n_in];
+        let tx_out = self
+            .tx_out_bn_map
+            .get(&tx_input.input_tx_id, tx_input.input_tx_out_num);
+        match tx_out {
+            None => false,
+            Some(tx_out_bn) => tx_out_bn.is_mature_at(self.block_num, self.coinbase_maturity),
+        }
+    }
+
     pub fn verify_inputs(&mut self) -> bool {
         for i in 0..self.tx.inputs.len() {
             if !self.verify_input_script(i) {
@@ -74,60 +117,101 @@ impl<'a> TxVerifier<'a> {
             if !self.verify_input_lock_rel(i) {
                 return false;
             }
+            if !self.verify_input_maturity(i) {
+                return false;
+            }
         }
         true
     }
 
     pub fn verify_no_double_spend(&self) -> bool {
-        let mut spent_outputs = Vec::new();
+        let mut spent_outputs = HashSet::new();
         for input in &self.tx.inputs {
-            let tx_out = self
+            if self
                 .tx_out_bn_map
-                .get(&input.input_tx_id.clone(), input.input_tx_out_num);
-            match tx_out {
-                None => return false,
-                Some(tx_out) => {
-                    if spent_outputs.contains(&tx_out) {
-                        return false;
-                    }
-                    spent_outputs.push(tx_out);
-                }
+                .get(&input.input_tx_id, input.input_tx_out_num)
+                .is_none()
+            {
+                return false;
+            }
+            if !spent_outputs.insert((input.input_tx_id, input.input_tx_out_num)) {
+                return false;
             }
         }
         true
     }
 
     pub fn verify_output_values(&self) -> bool {
-        let mut total_output_value = 0;
-        for output in &self.tx.outputs {
-            total_output_value += output.value;
-        }
-        let mut total_input_value = 0;
+        let output_values = self.tx.outputs.iter().map(|output| output.value);
+        let total_output_value = match EbxValue::checked_sum(output_values) {
+            None => return false,
+            Some(total_output_value) => total_output_value,
+        };
+        let mut total_input_value = EbxValue::zero();
         for input in &self.tx.inputs {
             let tx_out_bn = self
                 .tx_out_bn_map
                 .get(&input.input_tx_id.clone(), input.input_tx_out_num);
-            match tx_out_bn {
+            let input_value = match tx_out_bn {
                 None => return false,
-                Some(tx_out_bn) => total_input_value += tx_out_bn.tx_out.value,
-            }
+                Some(tx_out_bn) => EbxValue::new(tx_out_bn.tx_out.value),
+            };
+            total_input_value = match total_input_value.checked_add(input_value) {
+                None => return false,
+                Some(total_input_value) => total_input_value,
+            };
         }
         total_input_value == total_output_value
     }
 
+    // only the first input of the coinbase may be a null input
     pub fn verify_is_not_coinbase(&self) -> bool {
-        // TODO: Allow coinbases to have multiple inputs
-        if self.tx.inputs.len() == 1 && self.tx.inputs[0].is_coinbase() {
-            return false;
+        !self.tx.inputs.iter().any(|input| input.is_coinbase())
+    }
+
+    // the null input of a coinbase spends nothing, but the rest of its inputs
+    // sweep expired outputs, and are verified like the inputs of any other tx
+    pub fn verify_coinbase_inputs(&mut self) -> bool {
+        let mut spent_outputs = HashSet::new();
+        for i in 1..self.tx.inputs.len() {
+            let input = &self.tx.inputs[i];
+            if !spent_outputs.insert((input.input_tx_id, input.input_tx_out_num)) {
+                return false;
+            }
+            if !self.verify_input_script(i) {
+                return false;
+            }
+            if !self.verify_input_lock_rel(i) {
+                return false;
+            }
+            if !self.verify_input_maturity(i) {
+                return false;
+            }
         }
         true
     }
 
+    // the total value of the expired outputs swept by a coinbase
+    pub fn coinbase_input_value(&self) -> Option<EbxValue> {
+        let mut total_input_value = EbxValue::zero();
+        for input in self.tx.inputs.iter().skip(1) {
+            let tx_out_bn = self
+                .tx_out_bn_map
+                .get(&input.input_tx_id, input.input_tx_out_num)?;
+            total_input_value =
+                total_input_value.checked_add(EbxValue::new(tx_out_bn.tx_out.value))?;
+        }
+        Some(total_input_value)
+    }
+
+    // a lock by time is checked against the median time past of the chain,
+    // which is in milliseconds, rather than the timestamp of the block
     pub fn verify_lock_abs(&self) -> bool {
-        if self.tx.lock_abs > self.block_num {
-            return false;
+        let lock_abs = self.tx.lock_abs;
+        if lock_abs >= Tx::LOCK_ABS_TIME_THRESHOLD {
+            return u64::from(lock_abs) * 1000 <= self.median_time_past;
         }
-        true
+        lock_abs <= self.block_num
     }
 
     pub fn verify(&mut self) -> bool {
@@ -180,7 +264,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let tx_out = TxOut::new(50, Script::from_empty());
         tx_builder.add_output(tx_out);
@@ -197,7 +282,8 @@ mod tests {
         let signed_tx = tx_signer.tx;
         assert!(tx_res.is_ok());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, 0);
+        let mut tx_verifier =
+            TxVerifier::new(&signed_tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet());
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -215,6 +301,80 @@ mod tests {
         assert!(verified);
     }
 
+    fn signed_pkh_tx() -> (Tx, TxOutBnMap) {
+        let mut tx_out_bn_map = TxOutBnMap::new();
+        let mut pkh_key_map = PkhKeyMap::new();
+        for i in 0..5 {
+            let key = KeyPair::from_random();
+            let pkh = Pkh::from_pub_key_buffer(key.clone().pub_key.buf.to_vec());
+            pkh_key_map.add(key, &pkh.buf);
+            let script = Script::from_pkh_output(&pkh.buf);
+            let output = TxOut::new(100, script);
+            tx_out_bn_map.add(&[0; 32], i, output, 0);
+        }
+        let mut tx_builder = TxBuilder::new(
+            &tx_out_bn_map,
+            Script::from_empty(),
+            0,
+            0,
+            &ChainParams::mainnet(),
+        );
+        tx_builder.add_output(TxOut::new(150, Script::from_empty()));
+        let tx = tx_builder.build().unwrap();
+        let mut tx_signer = TxSigner::new(tx, &tx_out_bn_map, &pkh_key_map, 0);
+        let signed_tx = tx_signer.sign().unwrap();
+        (signed_tx, tx_out_bn_map)
+    }
+
+    #[test]
+    fn should_verify_a_tx_with_sig_cache() {
+        let (signed_tx, tx_out_bn_map) = signed_pkh_tx();
+        let sig_cache = SigCache::default();
+
+        let mut tx_verifier = TxVerifier::new_with_sig_cache(
+            &signed_tx,
+            &tx_out_bn_map,
+            0,
+            0,
+            &ChainParams::mainnet(),
+            &sig_cache,
+        );
+        assert!(tx_verifier.verify());
+        assert_eq!(sig_cache.len(), 2);
+
+        // second verification is served from the cache
+        let mut tx_verifier = TxVerifier::new_with_sig_cache(
+            &signed_tx,
+            &tx_out_bn_map,
+            0,
+            0,
+            &ChainParams::mainnet(),
+            &sig_cache,
+        );
+        assert!(tx_verifier.verify());
+        assert_eq!(sig_cache.len(), 2);
+    }
+
+    #[test]
+    fn should_not_cache_an_invalid_signature() {
+        let (mut signed_tx, tx_out_bn_map) = signed_pkh_tx();
+        let mut sig = signed_tx.inputs[0].script.chunks[0].buffer.clone().unwrap();
+        sig[10] ^= 0xff;
+        signed_tx.inputs[0].script.chunks[0].buffer = Some(sig);
+        let sig_cache = SigCache::default();
+
+        let mut tx_verifier = TxVerifier::new_with_sig_cache(
+            &signed_tx,
+            &tx_out_bn_map,
+            0,
+            0,
+            &ChainParams::mainnet(),
+            &sig_cache,
+        );
+        assert!(!tx_verifier.verify());
+        assert!(sig_cache.is_empty());
+    }
+
     #[test]
     fn should_sign_and_not_verify_a_tx_with_wrong_lock_num() {
         let mut tx_out_bn_map = TxOutBnMap::new();
@@ -231,7 +391,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 1);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 1, 1, &ChainParams::mainnet());
 
         let tx_out = TxOut::new(50, Script::from_empty());
         tx_builder.add_output(tx_out);
@@ -248,7 +409,8 @@ mod tests {
         let signed_tx = tx_signer.tx;
         assert!(tx_res.is_ok());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, 0);
+        let mut tx_verifier =
+            TxVerifier::new(&signed_tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet());
         let verified_input = tx_verifier.verify_input_script(0);
         assert!(verified_input);
 
@@ -278,7 +440,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let tx_out = TxOut::new(100, Script::from_empty());
         tx_builder.add_output(tx_out.clone());
@@ -298,7 +461,8 @@ mod tests {
         assert!(tx_res2.is_ok());
         let signed_tx = tx_signer.tx;
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, 0);
+        let mut tx_verifier =
+            TxVerifier::new(&signed_tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet());
         let verified_input1 = tx_verifier.verify_input_script(0);
         assert!(verified_input1);
         let verified_input2 = tx_verifier.verify_input_script(1);
@@ -330,7 +494,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let tx_out = TxOut::new(50, Script::from_empty());
         tx_builder.add_output(tx_out);
@@ -347,7 +512,8 @@ mod tests {
         let signed_tx = tx_signer.tx;
         assert!(tx_res.is_ok());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, 0);
+        let mut tx_verifier =
+            TxVerifier::new(&signed_tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet());
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -382,7 +548,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let expired_input_script = Script::from_expired_pkhx_input();
         let tx_in = TxIn::new([0; 32], 0, expired_input_script, Script::PKHX_1H_LOCK_REL);
@@ -406,7 +573,13 @@ mod tests {
 
         assert!(tx.inputs[0].script.is_expired_pkhx_input());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, working_block_num);
+        let mut tx_verifier = TxVerifier::new(
+            &signed_tx,
+            &tx_out_bn_map,
+            working_block_num,
+            0,
+            &ChainParams::mainnet(),
+        );
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -440,7 +613,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let tx_out = TxOut::new(50, Script::from_empty());
         tx_builder.add_output(tx_out);
@@ -457,7 +631,8 @@ mod tests {
         let signed_tx = tx_signer.tx;
         assert!(tx_res.is_ok());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, 0);
+        let mut tx_verifier =
+            TxVerifier::new(&signed_tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet());
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -492,7 +667,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let expired_input_script = Script::from_expired_pkhx_input();
         let tx_in = TxIn::new([0; 32], 0, expired_input_script, Script::PKHX_90D_LOCK_REL);
@@ -516,7 +692,13 @@ mod tests {
 
         assert!(tx.inputs[0].script.is_expired_pkhx_input());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, working_block_num);
+        let mut tx_verifier = TxVerifier::new(
+            &signed_tx,
+            &tx_out_bn_map,
+            working_block_num,
+            0,
+            &ChainParams::mainnet(),
+        );
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -550,7 +732,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let tx_out = TxOut::new(50, Script::from_empty());
         tx_builder.add_output(tx_out);
@@ -567,7 +750,8 @@ mod tests {
         let signed_tx = tx_signer.tx;
         assert!(tx_res.is_ok());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, 0);
+        let mut tx_verifier =
+            TxVerifier::new(&signed_tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet());
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -602,7 +786,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let recovery_input_script = Script::from_recovery_pkhxr_input_placeholder();
         let tx_in = TxIn::new(
@@ -629,7 +814,13 @@ mod tests {
         let signed_tx = tx_signer.tx;
         assert!(tx_res.is_ok());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, working_block_num);
+        let mut tx_verifier = TxVerifier::new(
+            &signed_tx,
+            &tx_out_bn_map,
+            working_block_num,
+            0,
+            &ChainParams::mainnet(),
+        );
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -664,7 +855,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let recovery_input_script = Script::from_expired_pkhxr_input();
         let tx_in = TxIn::new(
@@ -691,7 +883,13 @@ mod tests {
         let signed_tx = tx_signer.tx;
         assert!(tx_res.is_ok());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, working_block_num);
+        let mut tx_verifier = TxVerifier::new(
+            &signed_tx,
+            &tx_out_bn_map,
+            working_block_num,
+            0,
+            &ChainParams::mainnet(),
+        );
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -725,7 +923,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let tx_out = TxOut::new(50, Script::from_empty());
         tx_builder.add_output(tx_out);
@@ -742,7 +941,8 @@ mod tests {
         let signed_tx = tx_signer.tx;
         assert!(tx_res.is_ok());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, 0);
+        let mut tx_verifier =
+            TxVerifier::new(&signed_tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet());
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -777,7 +977,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let recovery_input_script = Script::from_recovery_pkhxr_input_placeholder();
         let tx_in = TxIn::new(
@@ -804,7 +1005,13 @@ mod tests {
         let signed_tx = tx_signer.tx;
         assert!(tx_res.is_ok());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, working_block_num);
+        let mut tx_verifier = TxVerifier::new(
+            &signed_tx,
+            &tx_out_bn_map,
+            working_block_num,
+            0,
+            &ChainParams::mainnet(),
+        );
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -839,7 +1046,8 @@ mod tests {
         }
 
         let change_script = Script::from_empty();
-        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, change_script, 0);
+        let mut tx_builder =
+            TxBuilder::new(&tx_out_bn_map, change_script, 0, 0, &ChainParams::mainnet());
 
         let recovery_input_script = Script::from_expired_pkhxr_input();
         let tx_in = TxIn::new(
@@ -866,7 +1074,13 @@ mod tests {
         let signed_tx = tx_signer.tx;
         assert!(tx_res.is_ok());
 
-        let mut tx_verifier = TxVerifier::new(signed_tx, &tx_out_bn_map, working_block_num);
+        let mut tx_verifier = TxVerifier::new(
+            &signed_tx,
+            &tx_out_bn_map,
+            working_block_num,
+            0,
+            &ChainParams::mainnet(),
+        );
 
         let verified_input_script = tx_verifier.verify_input_script(0);
         assert!(verified_input_script);
@@ -883,4 +1097,125 @@ mod tests {
         let verified = tx_verifier.verify();
         assert!(verified);
     }
+
+    #[test]
+    fn test_coinbase_maturity() {
+        let key = KeyPair::from_random();
+        let pkh = Pkh::from_pub_key_buffer(key.pub_key.buf.to_vec());
+        let mut pkh_key_map = PkhKeyMap::new();
+        pkh_key_map.add(key, &pkh.buf);
+
+        let coinbase_tx = Tx::from_coinbase(
+            Script::from_empty(),
+            Script::from_pkh_output(&pkh.buf),
+            100,
+            5,
+        );
+        let mut tx_out_bn_map = TxOutBnMap::new();
+        tx_out_bn_map.add_tx_outputs(&coinbase_tx, 5);
+
+        let params = ChainParams::mainnet();
+        let mut tx_builder = TxBuilder::new(&tx_out_bn_map, Script::from_empty(), 0, 105, &params);
+        tx_builder.add_output(TxOut::new(100, Script::from_empty()));
+        let tx = tx_builder.build().unwrap();
+        let signed_tx = TxSigner::new(tx, &tx_out_bn_map, &pkh_key_map, 105)
+            .sign()
+            .unwrap();
+
+        let mut tx_verifier = TxVerifier::new(&signed_tx, &tx_out_bn_map, 104, 0, &params);
+        assert!(!tx_verifier.verify_input_maturity(0));
+        assert!(!tx_verifier.verify());
+
+        let mut tx_verifier = TxVerifier::new(&signed_tx, &tx_out_bn_map, 105, 0, &params);
+        assert!(tx_verifier.verify_input_maturity(0));
+        assert!(tx_verifier.verify());
+
+        // regtest coinbases mature sooner
+        let params = ChainParams::regtest();
+        let mut tx_verifier = TxVerifier::new(&signed_tx, &tx_out_bn_map, 15, 0, &params);
+        assert!(tx_verifier.verify());
+    }
+
+    #[test]
+    fn test_verify_output_values_overflow() {
+        let mut tx_out_bn_map = TxOutBnMap::new();
+        tx_out_bn_map.add(&[0; 32], 0, TxOut::new(1, Script::from_empty()), 0);
+        let tx_in = TxIn::new([0; 32], 0, Script::from_empty(), 0);
+
+        // the outputs wrap around to 1 with unchecked u64 addition
+        let outputs = vec![
+            TxOut::new(u64::MAX, Script::from_empty()),
+            TxOut::new(2, Script::from_empty()),
+        ];
+        let tx = Tx::new(1, vec![tx_in.clone()], outputs, 0);
+        assert!(
+            !TxVerifier::new(&tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet())
+                .verify_output_values()
+        );
+
+        let outputs = vec![TxOut::new(1, Script::from_empty())];
+        let tx = Tx::new(1, vec![tx_in.clone()], outputs, 0);
+        assert!(
+            TxVerifier::new(&tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet())
+                .verify_output_values()
+        );
+
+        // inputs above the max money are refused too
+        let max_money = EbxValue::MAX_MONEY + 1;
+        tx_out_bn_map.add(&[0; 32], 0, TxOut::new(max_money, Script::from_empty()), 0);
+        let outputs = vec![TxOut::new(max_money, Script::from_empty())];
+        let tx = Tx::new(1, vec![tx_in], outputs, 0);
+        assert!(
+            !TxVerifier::new(&tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet())
+                .verify_output_values()
+        );
+    }
+
+    #[test]
+    fn test_verify_lock_abs() {
+        let tx_out_bn_map = TxOutBnMap::new();
+        let params = ChainParams::mainnet();
+
+        // a lock by block number ignores the median time past
+        let tx = Tx::new(1, vec![], vec![], 5);
+        assert!(!TxVerifier::new(&tx, &tx_out_bn_map, 4, u64::MAX, &params).verify_lock_abs());
+        assert!(TxVerifier::new(&tx, &tx_out_bn_map, 5, 0, &params).verify_lock_abs());
+
+        // a lock by time is met once the median time past, in milliseconds,
+        // reaches it, whatever the block number
+        let lock_abs = Tx::LOCK_ABS_TIME_THRESHOLD + 1_000;
+        let median_time_past = u64::from(lock_abs) * 1000;
+        let tx = Tx::new(1, vec![], vec![], lock_abs);
+        assert!(
+            !TxVerifier::new(&tx, &tx_out_bn_map, u32::MAX, median_time_past - 1, &params)
+                .verify_lock_abs()
+        );
+        assert!(
+            TxVerifier::new(&tx, &tx_out_bn_map, 0, median_time_past, &params).verify_lock_abs()
+        );
+    }
+
+    #[test]
+    fn test_verify_is_not_coinbase() {
+        let mut tx_out_bn_map = TxOutBnMap::new();
+        tx_out_bn_map.add(&[0; 32], 0, TxOut::new(1, Script::from_empty()), 0);
+        let tx_in = TxIn::new([0; 32], 0, Script::from_empty(), 0);
+        let mut tx = Tx::new(1, vec![tx_in], vec![], 0);
+        assert!(
+            TxVerifier::new(&tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet())
+                .verify_is_not_coinbase()
+        );
+
+        // a null input anywhere in the tx makes it a coinbase, or invalid
+        tx.inputs.push(TxIn::from_coinbase(Script::from_empty()));
+        assert!(
+            !TxVerifier::new(&tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet())
+                .verify_is_not_coinbase()
+        );
+        tx.inputs.swap(0, 1);
+        assert!(
+            !TxVerifier::new(&tx, &tx_out_bn_map, 0, 0, &ChainParams::mainnet())
+                .verify_is_not_coinbase()
+        );
+    }
 }
@@ -0,0 +1,225 @@
+use crate::buf::EbxBuf;
+use crate::buf_reader::BufReader;
+use crate::buf_writer::BufWriter;
+use crate::error::EbxError;
+use crate::header::Header;
+use crate::merkle_proof::MerkleProof;
+use crate::numbers::u256;
+use crate::tx::Tx;
+
+// what a mine tells a pool miner about the block being mined: the header and
+// the number of transactions in the block, the coinbase and its merkle proof,
+// and the easier target a share must meet. the same as WorkData in the
+// typescript library, which calls the coinbase the "mint tx" and keeps the
+// number of transactions in the header.
+#[derive(Debug, Clone)]
+pub struct WorkData {
+    pub header: Header,
+    pub n_transactions: u64,
+    pub share_difficulty: u64,
+    pub coinbase_tx: Tx,
+    pub prev_merkle_root_id: Option<[u8; 32]>,
+    pub new_merkle_root_id: [u8; 32],
+    pub merkle_proof: MerkleProof,
+    pub share_target: u256,
+    pub expires_at: u64, // milliseconds
+}
+
+impl WorkData {
+    pub const VALIDITY: u64 = 40 * 1000; // 40 seconds
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        header: Header,
+        n_transactions: u64,
+        share_difficulty: u64,
+        coinbase_tx: Tx,
+        prev_merkle_root_id: Option<[u8; 32]>,
+        new_merkle_root_id: [u8; 32],
+        merkle_proof: MerkleProof,
+        share_target: u256,
+        expires_at: u64,
+    ) -> Self {
+        Self {
+            header,
+            n_transactions,
+            share_difficulty,
+            coinbase_tx,
+            prev_merkle_root_id,
+            new_merkle_root_id,
+            merkle_proof,
+            share_target,
+            expires_at,
+        }
+    }
+
+    pub fn is_expired_at(&self, timestamp: u64) -> bool {
+        self.expires_at < timestamp
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut writer = BufWriter::new();
+        writer.write(self.header.to_wire_buf(self.n_transactions).to_vec());
+        writer.write_u64_be(self.share_difficulty);
+        let coinbase_tx_buf = self.coinbase_tx.to_buf();
+        writer.write_u64_be(coinbase_tx_buf.len() as u64);
+        writer.write(coinbase_tx_buf);
+        match self.prev_merkle_root_id {
+            Some(prev_merkle_root_id) => {
+                writer.write_u8(1);
+                writer.write(prev_merkle_root_id.to_vec());
+            }
+            None => {
+                writer.write_u8(0);
+            }
+        }
+        writer.write(self.new_merkle_root_id.to_vec());
+        let merkle_proof_buf = self.merkle_proof.to_buf();
+        writer.write_u64_be(merkle_proof_buf.len() as u64);
+        writer.write(merkle_proof_buf);
+        writer.write_u256_be(self.share_target);
+        writer.write_u64_be(self.expires_at);
+        writer.to_buf()
+    }
+
+    pub fn from_buf(buf: Vec<u8>) -> Result<Self, EbxError> {
+        let mut br = BufReader::new(buf);
+        let (header, n_transactions) = Header::from_wire_buf_reader(&mut br)?;
+        let share_difficulty = br.read_u64_be()?;
+        let coinbase_tx_len = Self::read_len(&mut br)?;
+        let coinbase_tx = Tx::from_buf(br.read(coinbase_tx_len)?)?;
+        let prev_merkle_root_id = match br.read_u8()? {
+            0 => None,
+            1 => Some(br.read(32)?.try_into().unwrap()),
+            _ => {
+                return Err(EbxError::GenericError {
+                    source: None,
+                    message: "invalid prev merkle root id flag".to_string(),
+                })
+            }
+        };
+        let new_merkle_root_id: [u8; 32] = br.read(32)?.try_into().unwrap();
+        let merkle_proof_len = Self::read_len(&mut br)?;
+        let merkle_proof = MerkleProof::from_buf(&br.read(merkle_proof_len)?).map_err(|e| {
+            EbxError::GenericError {
+                source: None,
+                message: e,
+            }
+        })?;
+        let share_target = br.read_u256_be()?;
+        let expires_at = br.read_u64_be()?;
+        if !br.eof() {
+            return Err(EbxError::TooMuchDataError { source: None });
+        }
+        Ok(Self::new(
+            header,
+            n_transactions,
+            share_difficulty,
+            coinbase_tx,
+            prev_merkle_root_id,
+            new_merkle_root_id,
+            merkle_proof,
+            share_target,
+            expires_at,
+        ))
+    }
+
+    // a length that must fit in what is left of the buffer
+    fn read_len(br: &mut BufReader) -> Result<usize, EbxError> {
+        let len = br.read_u64_be()?;
+        if len > br.remainder_len() as u64 {
+            return Err(EbxError::NotEnoughDataError { source: None });
+        }
+        Ok(len as usize)
+    }
+
+    pub fn to_strict_hex(&self) -> String {
+        self.to_buf().to_strict_hex()
+    }
+
+    pub fn from_strict_hex(hex: &str) -> Result<Self, EbxError> {
+        Self::from_buf(Vec::<u8>::from_strict_hex(hex)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::Script;
+    use crate::script_chunk::ScriptChunk;
+    use serde::Deserialize;
+    use std::fs;
+
+    #[derive(Deserialize)]
+    struct WorkDataVectors {
+        work_data: Vec<WorkDataVector>,
+    }
+
+    #[derive(Deserialize)]
+    struct WorkDataVector {
+        description: String,
+        expires_at: u64,
+        work_data: String,
+    }
+
+    fn make_work_data(prev_merkle_root_id: Option<[u8; 32]>, expires_at: u64) -> WorkData {
+        let mut header = Header::from_genesis(1_700_000_000_000);
+        let input_script = Script::new(vec![ScriptChunk::from_data(b"example.com".to_vec())]);
+        let output_script = Script::from_pkh_output(&[1; 32]);
+        let coinbase_tx = Tx::from_coinbase(input_script, output_script, 100, 0);
+        let (root, proofs) = MerkleProof::generate_proofs_and_root(vec![coinbase_tx.id(), [2; 32]]);
+        header.merkle_root = root;
+        let share_target = BufReader::new(vec![0x0f; 32]).read_u256_be().unwrap();
+        WorkData::new(
+            header,
+            2,
+            1000,
+            coinbase_tx,
+            prev_merkle_root_id,
+            root,
+            proofs[0].clone(),
+            share_target,
+            expires_at,
+        )
+    }
+
+    #[test]
+    fn test_to_from_buf() {
+        for prev_merkle_root_id in [None, Some([3; 32])] {
+            let work_data = make_work_data(prev_merkle_root_id, 1_700_000_040_000);
+            let buf = work_data.to_buf();
+            let work_data2 = WorkData::from_buf(buf.clone()).unwrap();
+            assert_eq!(work_data2.to_buf(), buf);
+            assert_eq!(work_data2.n_transactions, 2);
+            assert_eq!(work_data2.prev_merkle_root_id, prev_merkle_root_id);
+            assert_eq!(work_data2.coinbase_tx.id(), work_data.coinbase_tx.id());
+            assert!(work_data2.merkle_proof.verify(&work_data.coinbase_tx.id()));
+
+            let mut too_long = buf.clone();
+            too_long.push(0);
+            assert!(WorkData::from_buf(too_long).is_err());
+            assert!(WorkData::from_buf(buf[..buf.len() - 1].to_vec()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_is_expired_at() {
+        let work_data = make_work_data(None, 1_700_000_040_000);
+        assert!(!work_data.is_expired_at(1_700_000_040_000));
+        assert!(work_data.is_expired_at(1_700_000_040_001));
+    }
+
+    #[test]
+    fn test_vectors_work_data() {
+        let data =
+            fs::read_to_string("./test_vectors/work_data.json").expect("Unable to read file");
+        let test_vectors: WorkDataVectors =
+            serde_json::from_str(&data).expect("Unable to parse JSON");
+        for test_vector in test_vectors.work_data {
+            let work_data =
+                WorkData::from_strict_hex(&test_vector.work_data).expect(&test_vector.description);
+            assert_eq!(work_data.expires_at, test_vector.expires_at);
+            assert_eq!(work_data.to_strict_hex(), test_vector.work_data);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::buf::EbxBuf;
+use crate::buf_reader::BufReader;
+use crate::error::EbxError;
+use crate::header::Header;
+use crate::header_chain::HeaderChain;
+use crate::lch10_ids::Lch10Ids;
+
+// the work a mine hands to a miner: a header to find the proof of work for,
+// the number of transactions in its block, and the ids of the headers it
+// builds on. the same as WorkPack in the typescript library, whose header
+// holds the number of transactions.
+#[derive(Debug, Clone)]
+pub struct WorkPack {
+    pub header: Header,
+    pub n_transactions: u64,
+    pub lch10_ids: Lch10Ids,
+}
+
+impl WorkPack {
+    pub fn new(header: Header, n_transactions: u64, lch10_ids: Lch10Ids) -> Self {
+        Self {
+            header,
+            n_transactions,
+            lch10_ids,
+        }
+    }
+
+    pub fn from_header_chain(header: Header, n_transactions: u64, lch: &HeaderChain) -> Self {
+        Self::new(header, n_transactions, Lch10Ids::from_header_chain(lch))
+    }
+
+    // the header is the next header of the chain, and the ids are those of
+    // the tip of the chain
+    pub fn is_valid_in(&self, lch: &HeaderChain) -> bool {
+        if !self.lch10_ids.is_valid_in(lch) {
+            return false;
+        }
+        if self.header.block_num as usize != lch.headers.len() {
+            return false;
+        }
+        match self.lch10_ids.ids.last() {
+            Some(tip_id) => self.header.prev_block_id == *tip_id,
+            None => self.header.is_genesis(),
+        }
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = self.header.to_wire_buf(self.n_transactions).to_vec();
+        buf.extend(self.lch10_ids.to_buf());
+        buf
+    }
+
+    pub fn from_buf_reader(br: &mut BufReader) -> Result<Self, EbxError> {
+        let (header, n_transactions) = Header::from_wire_buf_reader(br)?;
+        let lch10_ids = Lch10Ids::from_buf_reader(br)?;
+        Ok(Self::new(header, n_transactions, lch10_ids))
+    }
+
+    pub fn from_buf(buf: Vec<u8>) -> Result<Self, EbxError> {
+        let mut br = BufReader::new(buf);
+        Self::from_buf_reader(&mut br)
+    }
+
+    pub fn to_strict_hex(&self) -> String {
+        self.to_buf().to_strict_hex()
+    }
+
+    pub fn from_strict_hex(hex: &str) -> Result<Self, EbxError> {
+        Self::from_buf(Vec::<u8>::from_strict_hex(hex)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_params::ChainParams;
+
+    fn regtest_chain(n_headers: u32) -> HeaderChain {
+        let mut lch = HeaderChain::from_params(ChainParams::regtest());
+        for block_num in 0..n_headers {
+            let mut header = Header::from_genesis(0);
+            header.block_num = block_num;
+            if let Some(tip) = lch.get_tip() {
+                header.prev_block_id = tip.id();
+            }
+            lch.add(header);
+        }
+        lch
+    }
+
+    #[test]
+    fn test_to_from_buf() {
+        let lch = regtest_chain(3);
+        let mut header = Header::from_genesis(0);
+        header.block_num = 3;
+        header.prev_block_id = lch.get_tip().unwrap().id();
+        let work_pack = WorkPack::from_header_chain(header, 2, &lch);
+        let buf = work_pack.to_buf();
+        assert_eq!(buf.len(), Header::WIRE_SIZE + 1 + 3 * 32);
+        let work_pack2 = WorkPack::from_strict_hex(&work_pack.to_strict_hex()).unwrap();
+        assert_eq!(work_pack2.n_transactions, 2);
+        assert_eq!(work_pack2.to_buf(), buf);
+        assert!(WorkPack::from_buf(buf[..Header::WIRE_SIZE].to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_is_valid_in() {
+        let lch = regtest_chain(0);
+        let work_pack = WorkPack::from_header_chain(Header::from_genesis(0), 1, &lch);
+        assert!(work_pack.is_valid_in(&lch));
+
+        let lch = regtest_chain(12);
+        let mut header = Header::from_genesis(0);
+        header.block_num = 12;
+        header.prev_block_id = lch.get_tip().unwrap().id();
+        let work_pack = WorkPack::from_header_chain(header.clone(), 1, &lch);
+        assert!(work_pack.is_valid_in(&lch));
+
+        // a header that does not build on the tip
+        let mut work_pack2 = work_pack.clone();
+        work_pack2.header.prev_block_id = lch.headers[10].id();
+        assert!(!work_pack2.is_valid_in(&lch));
+        let mut work_pack2 = work_pack.clone();
+        work_pack2.header.block_num = 11;
+        assert!(!work_pack2.is_valid_in(&lch));
+
+        // ids of an older tip
+        let mut work_pack2 = work_pack;
+        work_pack2.lch10_ids = Lch10Ids::from_header_chain(&regtest_chain(11));
+        assert!(!work_pack2.is_valid_in(&lch));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::error::EbxError;
+use crate::hash::blake3_hash;
+
+// serial work is a hash of the working header id (the header id with both
+// work hashes zeroed) that cannot be parallelized. the number stored in the
+// header's work_ser_algo field selects the algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkSerAlgo {
+    Null = 0,
+    Blake3 = 1,
+    Blake3_2 = 2,
+    Blake3_3 = 3,
+}
+
+impl WorkSerAlgo {
+    pub fn from_u16(num: u16) -> Result<Self, EbxError> {
+        match num {
+            0 => Ok(WorkSerAlgo::Null),
+            1 => Ok(WorkSerAlgo::Blake3),
+            2 => Ok(WorkSerAlgo::Blake3_2),
+            3 => Ok(WorkSerAlgo::Blake3_3),
+            _ => Err(EbxError::GenericError {
+                source: None,
+                message: format!("unknown work ser algo: {}", num),
+            }),
+        }
+    }
+
+    pub fn to_u16(self) -> u16 {
+        self as u16
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, EbxError> {
+        match name {
+            "null" => Ok(WorkSerAlgo::Null),
+            "blake3" => Ok(WorkSerAlgo::Blake3),
+            "blake3_2" => Ok(WorkSerAlgo::Blake3_2),
+            "blake3_3" => Ok(WorkSerAlgo::Blake3_3),
+            _ => Err(EbxError::GenericError {
+                source: None,
+                message: format!("unknown work ser algo: {}", name),
+            }),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WorkSerAlgo::Null => "null",
+            WorkSerAlgo::Blake3 => "blake3",
+            WorkSerAlgo::Blake3_2 => "blake3_2",
+            WorkSerAlgo::Blake3_3 => "blake3_3",
+        }
+    }
+
+    // the null algorithm does no work and its hash is all zeros. the blake3
+    // algorithms hash the working header id one, two or three times.
+    pub fn work(self, working_header_id: &[u8; 32]) -> [u8; 32] {
+        let rounds = match self {
+            WorkSerAlgo::Null => return [0; 32],
+            WorkSerAlgo::Blake3 => 1,
+            WorkSerAlgo::Blake3_2 => 2,
+            WorkSerAlgo::Blake3_3 => 3,
+        };
+        let mut hash = *working_header_id;
+        for _ in 0..rounds {
+            hash = blake3_hash(&hash);
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_num_and_name_round_trip() {
+        for num in 0..4 {
+            let algo = WorkSerAlgo::from_u16(num).unwrap();
+            assert_eq!(algo.to_u16(), num);
+            assert_eq!(WorkSerAlgo::from_name(algo.name()).unwrap(), algo);
+        }
+        assert!(WorkSerAlgo::from_u16(4).is_err());
+        assert!(WorkSerAlgo::from_name("blake3_4").is_err());
+    }
+
+    #[test]
+    fn test_work() {
+        let id = blake3_hash(b"working header");
+        assert_eq!(WorkSerAlgo::Null.work(&id), [0; 32]);
+        assert_eq!(WorkSerAlgo::Blake3.work(&id), blake3_hash(&id));
+        assert_eq!(
+            WorkSerAlgo::Blake3_2.work(&id),
+            blake3_hash(&blake3_hash(&id))
+        );
+        assert_eq!(
+            WorkSerAlgo::Blake3_3.work(&id),
+            blake3_hash(&blake3_hash(&blake3_hash(&id)))
+        );
+    }
+}
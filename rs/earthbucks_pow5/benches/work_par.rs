@@ -0,0 +1,39 @@
+// compares the time to compute the header id (elementary iteration) with the
+// reference blake3 implementation and with the optimized blake3 crate.
+//
+// run with: cargo bench -p earthbucks_pow5 --features fast --bench work_par
+use earthbucks_pow5::{
+    elementary_iteration_with, fast_hash, insert_nonce, reference_hash, HASH_SIZE, HEADER_SIZE,
+};
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 10_000;
+
+fn time_iterations(hash: fn(&[u8]) -> [u8; HASH_SIZE]) -> Duration {
+    let header = vec![0x11; HEADER_SIZE];
+    let start = Instant::now();
+    for nonce in 0..ITERATIONS {
+        let header = insert_nonce(header.clone(), nonce).unwrap();
+        std::hint::black_box(elementary_iteration_with(&header, hash).unwrap());
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let reference = time_iterations(reference_hash);
+    let fast = time_iterations(fast_hash);
+    println!(
+        "reference: {:>10.3?} total, {:>8.3?} per iteration",
+        reference,
+        reference / ITERATIONS
+    );
+    println!(
+        "fast:      {:>10.3?} total, {:>8.3?} per iteration",
+        fast,
+        fast / ITERATIONS
+    );
+    println!(
+        "speedup:   {:.1}x",
+        reference.as_secs_f64() / fast.as_secs_f64()
+    );
+}
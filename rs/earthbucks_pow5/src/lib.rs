@@ -2,13 +2,54 @@ pub mod blake3_reference;
 use blake3_reference::blake3_reference_hash;
 use wasm_bindgen::prelude::*;
 
-const HEADER_SIZE: usize = 1 + 32 + 32 + 8 + 8 + 4 + 32 + 32 + 2 + 32 + 2 + 32; // 217
+pub const HEADER_SIZE: usize = 1 + 32 + 32 + 8 + 8 + 4 + 32 + 32 + 2 + 32 + 2 + 32; // 217
 const NONCE_START: usize = 1 + 32 + 32 + 8 + 8 + 4 + 32; // 117
 const NONCE_END: usize = 1 + 32 + 32 + 8 + 8 + 4 + 32 + 4; // 221
-const HASH_SIZE: usize = 32;
+pub const HASH_SIZE: usize = 32;
 const WORK_PAR_START: usize = 1 + 32 + 32 + 8 + 8 + 4 + 32 + 32 + 2 + 32 + 2;
 const WORK_PAR_END: usize = 1 + 32 + 32 + 8 + 8 + 4 + 32 + 32 + 2 + 32 + 2 + 32;
 
+#[wasm_bindgen]
+pub fn get_work_par(header: Vec<u8>) -> Result<Vec<u8>, String> {
+    if header.len() != HEADER_SIZE {
+        return Err("header is not the correct size".to_string());
+    }
+    Ok(work_par_with(&header, blake3_hash)?.to_vec())
+}
+
+#[wasm_bindgen]
+pub fn elementary_iteration(header: Vec<u8>) -> Result<Vec<u8>, String> {
+    if header.len() != HEADER_SIZE {
+        return Err("header is not the correct size".to_string());
+    }
+    Ok(elementary_iteration_with(&header, blake3_hash)?.to_vec())
+}
+
+// the hash function used by get_work_par and elementary_iteration. with the
+// "fast" feature this is the optimized blake3 crate, which uses simd where the
+// cpu supports it. otherwise it is the reference implementation, which mirrors
+// the wgsl code. both produce the same hashes.
+#[cfg(feature = "fast")]
+fn blake3_hash(data: &[u8]) -> [u8; HASH_SIZE] {
+    fast_hash(data)
+}
+
+#[cfg(not(feature = "fast"))]
+fn blake3_hash(data: &[u8]) -> [u8; HASH_SIZE] {
+    reference_hash(data)
+}
+
+pub fn reference_hash(data: &[u8]) -> [u8; HASH_SIZE] {
+    let mut hash = [0u8; HASH_SIZE];
+    hash.copy_from_slice(&blake3_reference_hash(data.to_vec()));
+    hash
+}
+
+#[cfg(feature = "fast")]
+pub fn fast_hash(data: &[u8]) -> [u8; HASH_SIZE] {
+    *blake3::hash(data).as_bytes()
+}
+
 // what we need to do is something like the wgsl code above, with a bit of modifications because we
 // are in rust instead of wgsl. in wgsl, there is no such thing as a u8. thus, the final hash has
 // to be compressed. in rust, we have u8, and deal with u8 arrays. thus, there is no reason to
@@ -20,74 +61,64 @@ const WORK_PAR_END: usize = 1 + 32 + 32 + 8 + 8 + 4 + 32 + 32 + 2 + 32 + 2 + 32;
 //   hash
 // - after 32 iterations, we have a matrix_C_row_1, which we hash to get the final hash. this is
 //   the "parallel work" or "work_par"
-#[wasm_bindgen]
-pub fn get_work_par(header: Vec<u8>) -> Result<Vec<u8>, String> {
+//
+// the header must be HEADER_SIZE bytes long.
+pub fn work_par_with(
+    header: &[u8],
+    hash: fn(&[u8]) -> [u8; HASH_SIZE],
+) -> Result<[u8; HASH_SIZE], String> {
     if header.len() != HEADER_SIZE {
         return Err("header is not the correct size".to_string());
     }
     // first, hash the header with blake3
-    let matrix_a_row_1 = blake3_reference_hash(header.clone());
+    let matrix_a_row_1 = hash(header);
 
     // next, we will do the following. we will hash this hash over and over, 32
     // times. we will then multiply and add (similar to matmul) each value of
     // matrix_A_row_1 against each value of the new columns, of which there are
     // 32. these values will go into the final hash.
-    let mut matrix_c_working_column = matrix_a_row_1.clone();
+    let mut matrix_c_working_column = matrix_a_row_1;
     let mut matrix_c_row_1 = [0u32; HASH_SIZE];
-    #[allow(clippy::needless_range_loop)]
-    for i in 0..32 {
+    for matrix_c_value in matrix_c_row_1.iter_mut() {
         // now, hash the working column to get a new matrix_B_working_column
-        matrix_c_working_column = blake3_reference_hash(matrix_c_working_column.to_vec());
+        matrix_c_working_column = hash(&matrix_c_working_column);
 
         // the working column has been updated. now we "multiply and add" it
         // against the header hash.
         for j in 0..32 {
-            matrix_c_row_1[i] += (matrix_a_row_1[j] as u32) * (matrix_c_working_column[j] as u32);
+            *matrix_c_value += (matrix_a_row_1[j] as u32) * (matrix_c_working_column[j] as u32);
         }
     }
 
     // now we need to convert the matrix_c_row_1 to a u8 array - in *big-endian*
     // format
     let mut final_pre_hash: [u8; 32 * 4] = [0u8; 32 * 4];
-    #[allow(clippy::needless_range_loop)]
-    for i in 0..32 {
-        let x = matrix_c_row_1[i];
-        let j = i * 4;
-        final_pre_hash[j] = (x >> 24) as u8;
-        final_pre_hash[j + 1] = (x >> 16) as u8;
-        final_pre_hash[j + 2] = (x >> 8) as u8;
-        final_pre_hash[j + 3] = x as u8;
+    for (bytes, x) in final_pre_hash.chunks_exact_mut(4).zip(matrix_c_row_1) {
+        bytes.copy_from_slice(&x.to_be_bytes());
     }
 
     // we have now produced the first row of a matrix C via a matmul-esque operation. we will now
     // hash this row to get the "parallel work" or "work_par".
-    let work_par = blake3_reference_hash(final_pre_hash.to_vec());
-
-    Ok(work_par.to_vec())
+    Ok(hash(&final_pre_hash))
 }
 
-#[wasm_bindgen]
-pub fn elementary_iteration(header: Vec<u8>) -> Result<Vec<u8>, String> {
-    if header.len() != HEADER_SIZE {
-        return Err("header is not the correct size".to_string());
-    }
-
-    let work_par = get_work_par(header.clone())?;
+// the header must be HEADER_SIZE bytes long.
+pub fn elementary_iteration_with(
+    header: &[u8],
+    hash: fn(&[u8]) -> [u8; HASH_SIZE],
+) -> Result<[u8; HASH_SIZE], String> {
+    let work_par = work_par_with(header, hash)?;
 
     // now we need to insert to the work_par into the header
-    let mut working_header = header.clone();
-    #[allow(clippy::manual_memcpy)]
-    for i in WORK_PAR_START..WORK_PAR_END {
-        working_header[i] = work_par[i - WORK_PAR_START];
-    }
+    let mut working_header = [0u8; HEADER_SIZE];
+    working_header.copy_from_slice(header);
+    working_header[WORK_PAR_START..WORK_PAR_END].copy_from_slice(&work_par);
 
     // now we need to hash the header
-    let hash_1 = blake3_reference_hash(working_header);
+    let hash_1 = hash(&working_header);
 
     // now we need to hash it again because the "id" is actually the hash of the hash
-    let hash_2 = blake3_reference_hash(hash_1);
-
-    Ok(hash_2)
+    Ok(hash(&hash_1))
 }
 
 #[wasm_bindgen]
@@ -103,6 +134,7 @@ pub fn insert_nonce(header: Vec<u8>, nonce: u32) -> Result<Vec<u8>, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::RngCore;
     #[test]
     fn test_debug_get_work_par() {
         let expect_hex = "6fe9eddc39bb4183c44853c41876801be94a138ea9adea89f40a08442d2f79b8";
@@ -129,6 +161,35 @@ mod tests {
         assert_eq!(hex::encode(result), expect_hex);
     }
 
+    fn random_header() -> Vec<u8> {
+        let mut header = vec![0u8; HEADER_SIZE];
+        rand::thread_rng().fill_bytes(&mut header);
+        header
+    }
+
+    fn library_hash(data: &[u8]) -> [u8; HASH_SIZE] {
+        *blake3::hash(data).as_bytes()
+    }
+
+    #[test]
+    fn test_reference_hash_matches_library_for_random_headers() {
+        for _ in 0..1000 {
+            let header = random_header();
+            assert_eq!(
+                work_par_with(&header, reference_hash),
+                work_par_with(&header, library_hash)
+            );
+            assert_eq!(
+                elementary_iteration_with(&header, reference_hash),
+                elementary_iteration_with(&header, library_hash)
+            );
+        }
+
+        let header = vec![0u8; HEADER_SIZE - 1];
+        assert!(work_par_with(&header, reference_hash).is_err());
+        assert!(elementary_iteration_with(&header, reference_hash).is_err());
+    }
+
     #[test]
     fn test_work() {
         let expect_hex = "00000004f0ac89d75f135f184abbf0a82fad1e07fb4a29adb159648d70adf474";
@@ -470,6 +470,10 @@ export class Header implements HeaderInterface {
     return Header.targetFromDifficulty(newDifficulty);
   }
 
+  // fixed-point scale and ln(10) at that scale, for the difficulty adjustment
+  static readonly FIXED_POINT_SCALE = 1_000_000_000_000_000_000n;
+  static readonly FIXED_POINT_LN_10 = 2_302_585_092_994_045_684n;
+
   static newDifficultyFromPrevHeaders(
     prevHeader: Header,
     prevPrevHeader: Header | null,
@@ -478,40 +482,71 @@ export class Header implements HeaderInterface {
     if (!prevPrevHeader) {
       return prevHeader.difficulty();
     }
-    const prevTimeDiff = prevHeader.timestamp.n - prevPrevHeader.timestamp.n;
-    const prevDifficulty = prevHeader.difficulty().n;
-    const idealTimeDiff = Header.BLOCK_INTERVAL_MS.n;
-
-    // First, we want to adjust the difficulty based on the time difference
-    // between the previous block and the block before that. This is to ensure
-    // that the difficulty adjusts correctly if the time difference between
-    // blocks is greater or less than the ideal time difference of 10 minutes.
-    let newDifficulty = (prevDifficulty * idealTimeDiff) / prevTimeDiff;
-
-    // Next, we want to incentivize miners to mine at the ideal time interval of
-    // 10 minutes per block. We can do this by adjusting the difficulty based on
-    // how close the time difference is to the ideal time difference. We can use
-    // an exponential decay function to adjust the difficulty based on the time
-    // difference ratio. It is more difficult to mine a block if the time
-    // difference is less than the ideal time difference, and less difficult if
-    // the time difference is greater than the ideal time difference.
-
-    // Exponential decay function parameters
-    const m = 10; // Maximum adjustment factor
-    const k = Math.log(m); // Steepness of the curve
-
-    // Calculate the time difference ratio
-    const timeDiffRatio =
-      (newTimestamp.n - prevHeader.timestamp.n) / idealTimeDiff;
-
-    // Calculate the adjustment factor using the logistic function
-    const adjustmentFactor = Math.exp(-k * (timeDiffRatio - 1));
+    if (
+      prevHeader.timestamp.bn <= prevPrevHeader.timestamp.bn ||
+      newTimestamp.bn < prevHeader.timestamp.bn
+    ) {
+      throw new Error("timestamps must be increasing");
+    }
+    const prevTimeDiff = prevHeader.timestamp.bn - prevPrevHeader.timestamp.bn;
+    const timeDiff = newTimestamp.bn - prevHeader.timestamp.bn;
+    return Header.newDifficultyFromTimeDiffs(
+      prevHeader.difficulty(),
+      prevTimeDiff,
+      timeDiff,
+    );
+  }
 
-    // Apply the adjustment factor to the difficulty
-    newDifficulty = newDifficulty * adjustmentFactor;
-    newDifficulty = Math.max(newDifficulty, Header.MIN_DIFFICULTY.n);
+  /**
+   * First, the difficulty is scaled by how far the time between the previous
+   * two blocks was from the ideal block interval. Second, it is multiplied by
+   * an exponential decay factor exp(-ln(10) * (timeDiff / interval - 1)), so
+   * that mining a block early is up to 10x harder and mining it late becomes
+   * exponentially easier. The factor equals 10^(1 - timeDiff / interval) and
+   * is computed with integers so that every implementation gets exactly the
+   * same result.
+   */
+  static newDifficultyFromTimeDiffs(
+    prevDifficulty: U64BE,
+    prevTimeDiff: bigint,
+    timeDiff: bigint,
+  ): U64BE {
+    if (prevTimeDiff <= 0n) {
+      throw new Error("timestamps must be increasing");
+    }
+    const interval = Header.BLOCK_INTERVAL_MS.bn;
+    const scale = Header.FIXED_POINT_SCALE;
 
-    return new U64BE(Math.floor(newDifficulty));
+    // 10^(-timeDiff / interval) = 10^(-wholeIntervals) * e^(-y)
+    const wholeIntervals = timeDiff / interval;
+    if (wholeIntervals >= 100n) {
+      return Header.MIN_DIFFICULTY;
+    }
+    const partialInterval = timeDiff % interval;
+    const y = (Header.FIXED_POINT_LN_10 * partialInterval) / interval;
+    const expY = Header.fixedPointExp(y);
+
+    const numerator = prevDifficulty.bn * interval * 10n * scale;
+    const denominator = prevTimeDiff * 10n ** wholeIntervals * expY;
+    let newDifficulty = numerator / denominator;
+    if (newDifficulty < Header.MIN_DIFFICULTY.bn) {
+      newDifficulty = Header.MIN_DIFFICULTY.bn;
+    }
+    return new U64BE(newDifficulty);
+  }
+
+  // e^x for 0 <= x < ln(10), both in fixed point, by its taylor series
+  static fixedPointExp(x: bigint): bigint {
+    const scale = Header.FIXED_POINT_SCALE;
+    let sum = scale;
+    let term = scale;
+    for (let n = 1n; ; n++) {
+      term = (term * x) / (n * scale);
+      if (term === 0n) {
+        return sum;
+      }
+      sum += term;
+    }
   }
 
   static mintTxAmount(blockNum: U32BE): U64BE {
@@ -0,0 +1,57 @@
+import { describe, expect, test } from "vitest";
+import { CompuchaChallenge } from "../src/compucha-challenge.js";
+import { Header } from "../src/header.js";
+import { FixedBuf } from "@webbuf/fixedbuf";
+import { U64BE, U128BE } from "@webbuf/numbers";
+import fs from "node:fs";
+import path from "node:path";
+
+describe("CompuchaChallenge", () => {
+  describe("standard test vectors: compucha_challenge.json", () => {
+    const data = JSON.parse(
+      fs.readFileSync(
+        path.resolve(__dirname, "../test-vectors/compucha_challenge.json"),
+        "utf-8",
+      ),
+    );
+
+    test("compucha_challenge", () => {
+      interface TestVector {
+        challenge_id: string;
+        nonce: string;
+        compucha_challenge: string;
+        id: string;
+      }
+      const testVectors: TestVector[] = data.compucha_challenge;
+      for (const testVector of testVectors) {
+        const challenge = new CompuchaChallenge(
+          FixedBuf.fromHex(16, testVector.challenge_id),
+          new U128BE(BigInt(`0x${testVector.nonce}`)),
+        );
+        expect(challenge.toHex()).toBe(testVector.compucha_challenge);
+        expect(challenge.id().toHex()).toBe(testVector.id);
+        const challenge2 = CompuchaChallenge.fromHex(
+          testVector.compucha_challenge,
+        );
+        expect(challenge2.toHex()).toBe(testVector.compucha_challenge);
+      }
+    });
+
+    test("difficulty", () => {
+      interface TestVector {
+        difficulty: number;
+        compucha_challenge: string;
+      }
+      const testVectors: TestVector[] = data.difficulty;
+      for (const testVector of testVectors) {
+        const challenge = CompuchaChallenge.fromHex(
+          testVector.compucha_challenge,
+        );
+        const target = Header.targetFromDifficulty(
+          new U64BE(testVector.difficulty),
+        );
+        expect(challenge.isTargetValid(target)).toBe(true);
+      }
+    });
+  });
+});
@@ -0,0 +1,59 @@
+import { describe, expect, test } from "vitest";
+import fs from "node:fs";
+import path from "node:path";
+import { DomainPrivKey } from "../src/domain-priv-key.js";
+import { DomainPubKey } from "../src/domain-pub-key.js";
+
+describe("DomainPubKey", () => {
+  test("fromDomainPrivKey", () => {
+    const domainPrivKey = DomainPrivKey.fromRandom("earthbucks.com");
+    const domainPubKey = DomainPubKey.fromDomainPrivKey(domainPrivKey);
+    expect(domainPubKey.domain).toBe("earthbucks.com");
+    expect(DomainPubKey.fromString(domainPubKey.toString()).toString()).toBe(
+      domainPubKey.toString(),
+    );
+  });
+
+  describe("standard test vectors: domain_key.json", () => {
+    const data = fs.readFileSync(
+      path.resolve(__dirname, "../test-vectors/domain_key.json"),
+      "utf-8",
+    );
+    const testVectors = JSON.parse(data);
+
+    test("domain_key", () => {
+      interface TestVector {
+        domain_priv_key: string;
+        domain_pub_key: string;
+      }
+      const vectors: TestVector[] = testVectors.domain_key;
+      for (const testVector of vectors) {
+        const domainPrivKey = DomainPrivKey.fromString(
+          testVector.domain_priv_key,
+        );
+        expect(domainPrivKey.toString()).toBe(testVector.domain_priv_key);
+        const domainPubKey = DomainPubKey.fromDomainPrivKey(domainPrivKey);
+        expect(domainPubKey.toString()).toBe(testVector.domain_pub_key);
+        expect(
+          DomainPubKey.fromString(testVector.domain_pub_key).toString(),
+        ).toBe(testVector.domain_pub_key);
+      }
+    });
+
+    test("domain_pub_key_from_str", () => {
+      interface TestVector {
+        str: string;
+        domain: string | null;
+      }
+      const vectors: TestVector[] = testVectors.domain_pub_key_from_str;
+      for (const testVector of vectors) {
+        if (testVector.domain === null) {
+          expect(() => DomainPubKey.fromString(testVector.str)).toThrow();
+        } else {
+          const domainPubKey = DomainPubKey.fromString(testVector.str);
+          expect(domainPubKey.domain).toBe(testVector.domain);
+        }
+      }
+    });
+  });
+});
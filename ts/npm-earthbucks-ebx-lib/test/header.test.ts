@@ -6,6 +6,8 @@ import { BufReader } from "@webbuf/rw";
 import { BufWriter } from "@webbuf/rw";
 import { WORK_SER_ALGO_NUM } from "../src/work-ser-algo.js";
 import { WORK_PAR_ALGO_NUM } from "../src/work-par-algo.js";
+import fs from "node:fs";
+import path from "node:path";
 
 describe("Header", () => {
   test("toBuf and fromBuf constructor", () => {
@@ -279,4 +281,45 @@ describe("Header", () => {
   //     expect(difficulty.bn).toBe(262140n);
   //   });
   // });
+
+  describe("newDifficultyFromPrevHeaders test vectors", () => {
+    interface DifficultyVector {
+      prev_difficulty: string;
+      prev_time_diff: number;
+      time_diff: number;
+      new_difficulty: string;
+    }
+
+    const filePath = path.resolve(
+      __dirname,
+      "../test-vectors/header_difficulty.json",
+    );
+    const jsonString = fs.readFileSync(filePath, "utf-8");
+    const vectors: DifficultyVector[] = JSON.parse(jsonString).new_difficulty;
+
+    test("should match the integer difficulty adjustment", () => {
+      for (const vector of vectors) {
+        const target = Header.targetFromDifficulty(
+          new U64BE(BigInt(vector.prev_difficulty)),
+        );
+        const prevPrevHeader = new Header({
+          timestamp: new U64BE(1_000_000),
+          target,
+        });
+        const prevHeader = new Header({
+          timestamp: new U64BE(1_000_000 + vector.prev_time_diff),
+          target,
+        });
+        const newTimestamp = new U64BE(
+          prevHeader.timestamp.bn + BigInt(vector.time_diff),
+        );
+        const difficulty = Header.newDifficultyFromPrevHeaders(
+          prevHeader,
+          prevPrevHeader,
+          newTimestamp,
+        );
+        expect(difficulty.bn.toString()).toBe(vector.new_difficulty);
+      }
+    });
+  });
 });
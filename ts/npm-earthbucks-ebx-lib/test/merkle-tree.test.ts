@@ -1,8 +1,11 @@
 import { describe, expect, test } from "vitest";
 import { MerkleTree } from "../src/merkle-tree.js";
+import { MerkleProof } from "../src/merkle-proof.js";
 import { Hash } from "../src/hash.js";
 import { WebBuf } from "@webbuf/webbuf";
 import { FixedBuf } from "@webbuf/fixedbuf";
+import fs from "node:fs";
+import path from "node:path";
 
 describe("MerkleTree", () => {
   describe("computeAllLeaves", () => {
@@ -2178,4 +2181,93 @@ describe("MerkleTree", () => {
       expect(mt1.hash?.toHex()).toEqual(mt2.hash?.toHex());
     });
   });
+
+  describe("standard test vectors: merkle_tree.json", () => {
+    const data = fs.readFileSync(
+      path.resolve(__dirname, "../test-vectors/merkle_tree.json"),
+      "utf-8",
+    );
+    const testVectors = JSON.parse(data);
+    const leafHash = (name: string) =>
+      Hash.doubleBlake3Hash(WebBuf.from(name));
+    const leafHashes = (names: string[]) => names.map(leafHash);
+
+    test("from_leaf_hashes", () => {
+      interface TestVector {
+        leaves: string[];
+        root: string;
+      }
+      const vectors: TestVector[] = testVectors.from_leaf_hashes;
+      for (const testVector of vectors) {
+        const tree = MerkleTree.fromLeafHashes(leafHashes(testVector.leaves));
+        expect(tree.hash?.toHex()).toBe(testVector.root);
+        expect(tree.computeHash()?.toHex()).toBe(testVector.root);
+        expect(tree.countNonNullLeaves()).toBe(testVector.leaves.length);
+        expect(tree.isNullBalanced()).toBe(true);
+      }
+    });
+
+    test("update_balanced_leaf_hash", () => {
+      interface TestVector {
+        leaves: string[];
+        pos: number;
+        leaf: string;
+        root: string;
+      }
+      const vectors: TestVector[] = testVectors.update_balanced_leaf_hash;
+      for (const testVector of vectors) {
+        const tree = MerkleTree.fromLeafHashes(leafHashes(testVector.leaves));
+        const updated = tree.updateBalancedLeafHash(
+          testVector.pos,
+          leafHash(testVector.leaf),
+        );
+        expect(updated.hash?.toHex()).toBe(testVector.root);
+      }
+    });
+
+    test("add_leaf_hash", () => {
+      interface TestVector {
+        leaves: string[];
+        leaf: string;
+        root: string;
+      }
+      const vectors: TestVector[] = testVectors.add_leaf_hash;
+      for (const testVector of vectors) {
+        const tree = MerkleTree.fromLeafHashes(leafHashes(testVector.leaves));
+        const updated = tree.addLeafHash(leafHash(testVector.leaf));
+        expect(updated.hash?.toHex()).toBe(testVector.root);
+      }
+    });
+
+    test("get_merkle_proof", () => {
+      interface TestVector {
+        leaves: string[];
+        pos: number;
+        proof: string;
+      }
+      const vectors: TestVector[] = testVectors.get_merkle_proof;
+      for (const testVector of vectors) {
+        const leaves = leafHashes(testVector.leaves);
+        const tree = MerkleTree.fromLeafHashes(leaves);
+        const merkleProof = tree.getMerkleProof(testVector.pos);
+        expect(merkleProof.toHex()).toBe(testVector.proof);
+        expect(
+          MerkleProof.fromHex(testVector.proof).verify(
+            leaves[testVector.pos] as FixedBuf<32>,
+          ),
+        ).toBe(true);
+      }
+    });
+
+    test("known_leaf_hashes", () => {
+      const hashes = (testVectors.known_leaf_hashes as string[]).map((hex) =>
+        FixedBuf.fromHex(32, hex),
+      );
+      for (let len = 1; len <= hashes.length; len++) {
+        const tree1 = MerkleTree.fromLeafHashes(hashes.slice(0, len));
+        const tree2 = new MerkleTree().addLeafHashes(hashes.slice(0, len));
+        expect(tree2.hash?.toHex()).toBe(tree1.hash?.toHex());
+      }
+    });
+  });
 });
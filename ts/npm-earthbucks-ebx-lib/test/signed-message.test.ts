@@ -3,6 +3,8 @@ import { SignedMessage } from "../src/signed-message.js";
 import { PubKey } from "../src/pub-key.js";
 import { PrivKey } from "../src/priv-key.js";
 import { WebBuf } from "@webbuf/webbuf";
+import fs from "node:fs";
+import path from "node:path";
 
 describe("SignedMessage", () => {
   test("sign and verify", async () => {
@@ -17,4 +19,42 @@ describe("SignedMessage", () => {
     );
     expect(signedMessage.isValid(pubKey, keyStr)).toBe(true);
   });
+
+  describe("standard test vectors: signed_message.json", () => {
+    const data = fs.readFileSync(
+      path.resolve(__dirname, "../test-vectors/signed_message.json"),
+      "utf-8",
+    );
+
+    test("sign and verify", () => {
+      interface TestVector {
+        priv_key: string;
+        message: string;
+        key_str: string;
+        signed_message: string;
+      }
+      const testVectors: TestVector[] = JSON.parse(data).signed_message;
+
+      for (const testVector of testVectors) {
+        const privKey = PrivKey.fromString(testVector.priv_key);
+        const pubKey = PubKey.fromPrivKey(privKey);
+        const message = WebBuf.from(testVector.message, "hex");
+        const signedMessage = SignedMessage.fromSignMessage(
+          privKey,
+          message,
+          testVector.key_str,
+        );
+        expect(signedMessage.toBuf().toString("hex")).toBe(
+          testVector.signed_message,
+        );
+
+        const signedMessage2 = SignedMessage.fromBuf(
+          WebBuf.from(testVector.signed_message, "hex"),
+          testVector.key_str,
+        );
+        expect(signedMessage2.isValid(pubKey, testVector.key_str)).toBe(true);
+        expect(signedMessage2.isValid(pubKey, "other key string")).toBe(false);
+      }
+    });
+  });
 });
@@ -0,0 +1,68 @@
+import { describe, expect, test } from "vitest";
+import fs from "node:fs";
+import path from "node:path";
+import { DomainPrivKey } from "../src/domain-priv-key.js";
+import { PubKey } from "../src/pub-key.js";
+import { SignedWorkData } from "../src/signed-work-data.js";
+import { WorkData } from "../src/work-data.js";
+
+describe("WorkData", () => {
+  describe("standard test vectors: work_data.json", () => {
+    const data = fs.readFileSync(
+      path.resolve(__dirname, "../test-vectors/work_data.json"),
+      "utf-8",
+    );
+    const testVectors = JSON.parse(data);
+
+    test("work_data", () => {
+      interface TestVector {
+        description: string;
+        expires_at: number;
+        work_data: string;
+      }
+      const vectors: TestVector[] = testVectors.work_data;
+      for (const testVector of vectors) {
+        const workData = WorkData.fromHex(testVector.work_data);
+        expect(workData.expiresAt.n).toBe(testVector.expires_at);
+        expect(workData.toHex()).toBe(testVector.work_data);
+      }
+    });
+
+    test("signed_work_data", () => {
+      interface TestVector {
+        domain_priv_key: string;
+        work_data: string;
+        signed_work_data: string;
+      }
+      const vectors: TestVector[] = testVectors.signed_work_data;
+      for (const testVector of vectors) {
+        const domainPrivKey = DomainPrivKey.fromString(
+          testVector.domain_priv_key,
+        );
+        const domain = domainPrivKey.domain;
+        const pubKey = PubKey.fromPrivKey(domainPrivKey.privKey);
+        const workData = WorkData.fromHex(testVector.work_data);
+        const signedWorkData = SignedWorkData.fromWorkData(
+          domainPrivKey.privKey,
+          domain,
+          workData,
+        );
+        expect(signedWorkData.toHex()).toBe(testVector.signed_work_data);
+
+        const signedWorkData2 = SignedWorkData.fromHex(
+          testVector.signed_work_data,
+          domain,
+        );
+        expect(signedWorkData2.toHex()).toBe(testVector.signed_work_data);
+        expect(signedWorkData2.toWorkData().toHex()).toBe(testVector.work_data);
+        const expiresAt = new Date(workData.expiresAt.n);
+        expect(signedWorkData2.isValid(pubKey, domain, expiresAt)).toBe(true);
+        expect(signedWorkData2.isValid(pubKey, "other.com", expiresAt)).toBe(
+          false,
+        );
+        const later = new Date(workData.expiresAt.n + 1);
+        expect(signedWorkData2.isValid(pubKey, domain, later)).toBe(false);
+      }
+    });
+  });
+});